@@ -26,8 +26,8 @@ CREATE TABLE IF NOT EXISTS provider_delivery_zones
 CREATE TABLE IF NOT EXISTS oil_prices
 (
     id          SERIAL PRIMARY KEY,
-    price       DOUBLE PRECISION NOT NULL,
-    provider_id INT              NOT NULL REFERENCES providers (id) ON DELETE CASCADE,
+    price       NUMERIC(12, 4) NOT NULL,
+    provider_id INT            NOT NULL REFERENCES providers (id) ON DELETE CASCADE,
     created_at  TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -49,30 +49,521 @@ CREATE TABLE IF NOT EXISTS scraping_runs
 DO
 $$
     BEGIN
-        IF EXISTS (SELECT 1
-                   FROM information_schema.columns
-                   WHERE table_name = 'oil_prices'
-                     AND column_name = 'price'
-                     AND data_type = 'double precision') THEN
-            RAISE NOTICE 'Column price is already of type FLOAT8.';
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'providers'
+                         AND column_name = 'last_accessed') THEN
+            EXECUTE 'ALTER TABLE providers ADD COLUMN last_accessed TIMESTAMP DEFAULT CURRENT_TIMESTAMP;';
+            RAISE NOTICE 'Column last_accessed added.';
         ELSE
-            EXECUTE 'ALTER TABLE oil_prices ALTER COLUMN price TYPE FLOAT8 USING price::FLOAT8;';
-            RAISE NOTICE 'Column price type changed to FLOAT8.';
+            RAISE NOTICE 'Column last_accessed already exists.';
         END IF;
     END
 $$;
 
+CREATE TABLE IF NOT EXISTS products
+(
+    id          SERIAL PRIMARY KEY,
+    slug        VARCHAR(64)  NOT NULL UNIQUE,
+    name        VARCHAR(255) NOT NULL,
+    description TEXT
+);
+
+INSERT INTO products (slug, name)
+VALUES ('fyringsolie', 'Fyringsolie'),
+       ('diesel', 'Diesel'),
+       ('hvo', 'HVO/bioolie'),
+       ('paraffin', 'Paraffin')
+ON CONFLICT (slug) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS provider_products
+(
+    provider_id  INT          NOT NULL REFERENCES providers (id) ON DELETE CASCADE,
+    product_id   INT          NOT NULL REFERENCES products (id) ON DELETE CASCADE,
+    html_element VARCHAR(255) NOT NULL,
+    PRIMARY KEY (provider_id, product_id)
+);
+
 DO
 $$
     BEGIN
         IF NOT EXISTS (SELECT 1
                        FROM information_schema.columns
-                       WHERE table_name = 'providers'
-                         AND column_name = 'last_accessed') THEN
-            EXECUTE 'ALTER TABLE providers ADD COLUMN last_accessed TIMESTAMP DEFAULT CURRENT_TIMESTAMP;';
-            RAISE NOTICE 'Column last_accessed added.';
+                       WHERE table_name = 'oil_prices'
+                         AND column_name = 'product_id') THEN
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN product_id INT REFERENCES products (id);';
+            EXECUTE 'UPDATE oil_prices SET product_id = (SELECT id FROM products WHERE slug = ''fyringsolie'');';
+            EXECUTE 'ALTER TABLE oil_prices ALTER COLUMN product_id SET NOT NULL;';
+            EXECUTE 'INSERT INTO provider_products (provider_id, product_id, html_element)
+                     SELECT id, (SELECT id FROM products WHERE slug = ''fyringsolie''), html_element
+                     FROM providers
+                     ON CONFLICT DO NOTHING;';
+            RAISE NOTICE 'Column product_id added.';
         ELSE
-            RAISE NOTICE 'Column last_accessed already exists.';
+            RAISE NOTICE 'Column product_id already exists.';
+        END IF;
+    END
+$$;
+
+CREATE TABLE IF NOT EXISTS oil_price_tiers
+(
+    price_id   INT            NOT NULL REFERENCES oil_prices (id) ON DELETE CASCADE,
+    min_liters INT            NOT NULL CHECK (min_liters > 0),
+    price      NUMERIC(12, 4) NOT NULL,
+    PRIMARY KEY (price_id, min_liters)
+);
+
+CREATE TABLE IF NOT EXISTS provider_fees
+(
+    provider_id          INT PRIMARY KEY REFERENCES providers (id) ON DELETE CASCADE,
+    delivery_fee         NUMERIC(12, 2),
+    free_above_liters    INT,
+    minimum_order_liters INT
+);
+
+CREATE TABLE IF NOT EXISTS provider_zone_fees
+(
+    provider_id  INT            NOT NULL REFERENCES providers (id) ON DELETE CASCADE,
+    zone_id      INT            NOT NULL REFERENCES delivery_zones (id) ON DELETE CASCADE,
+    delivery_fee NUMERIC(12, 2) NOT NULL,
+    PRIMARY KEY (provider_id, zone_id)
+);
+
+CREATE TABLE IF NOT EXISTS delivery_zone_postcodes
+(
+    zone_id       INT NOT NULL REFERENCES delivery_zones (id) ON DELETE CASCADE,
+    postcode_from INT NOT NULL,
+    postcode_to   INT NOT NULL,
+    PRIMARY KEY (zone_id, postcode_from),
+    CHECK (postcode_from <= postcode_to)
+);
+
+DO
+$$
+    DECLARE
+        money_column RECORD;
+    BEGIN
+        FOR money_column IN
+            SELECT *
+            FROM (VALUES ('oil_prices', 'price', 4),
+                         ('oil_price_tiers', 'price', 4),
+                         ('provider_fees', 'delivery_fee', 2),
+                         ('provider_zone_fees', 'delivery_fee', 2)) AS c (table_name, column_name, scale)
+            LOOP
+                IF EXISTS (SELECT 1
+                           FROM information_schema.columns
+                           WHERE table_name = money_column.table_name
+                             AND column_name = money_column.column_name
+                             AND data_type = 'double precision') THEN
+                    EXECUTE format('ALTER TABLE %I ALTER COLUMN %I TYPE NUMERIC(12, %s) USING ROUND(%I::NUMERIC, %s);',
+                                   money_column.table_name, money_column.column_name, money_column.scale,
+                                   money_column.column_name, money_column.scale);
+                    RAISE NOTICE 'Column %.% type changed to NUMERIC.', money_column.table_name, money_column.column_name;
+                ELSE
+                    RAISE NOTICE 'Column %.% is already of type NUMERIC.', money_column.table_name, money_column.column_name;
+                END IF;
+            END LOOP;
+    END
+$$;
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'oil_prices'
+                         AND column_name = 'currency') THEN
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN currency CHAR(3) NOT NULL DEFAULT ''DKK'';';
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN unit VARCHAR(16) NOT NULL DEFAULT ''liter'';';
+            RAISE NOTICE 'Columns currency and unit added.';
+        ELSE
+            RAISE NOTICE 'Columns currency and unit already exist.';
+        END IF;
+    END
+$$;
+
+CREATE TABLE IF NOT EXISTS vat_rates
+(
+    id         SERIAL PRIMARY KEY,
+    rate       NUMERIC(6, 4) NOT NULL CHECK (rate >= 0),
+    valid_from TIMESTAMP     NOT NULL UNIQUE
+);
+
+INSERT INTO vat_rates (rate, valid_from)
+VALUES (0.25, '1992-01-01 00:00:00')
+ON CONFLICT (valid_from) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION vat_rate_at(at TIMESTAMP) RETURNS NUMERIC
+    LANGUAGE sql
+    STABLE
+AS
+$$
+SELECT COALESCE((SELECT rate FROM vat_rates WHERE valid_from <= at ORDER BY valid_from DESC LIMIT 1), 0)
+$$;
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'provider_products'
+                         AND column_name = 'includes_vat') THEN
+            EXECUTE 'ALTER TABLE provider_products ADD COLUMN includes_vat BOOLEAN NOT NULL DEFAULT TRUE;';
+            EXECUTE 'ALTER TABLE provider_products ADD COLUMN per_liters INT NOT NULL DEFAULT 1 CHECK (per_liters > 0);';
+            RAISE NOTICE 'Columns includes_vat and per_liters added.';
+        ELSE
+            RAISE NOTICE 'Columns includes_vat and per_liters already exist.';
+        END IF;
+    END
+$$;
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'oil_prices'
+                         AND column_name = 'raw_price') THEN
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN raw_price NUMERIC(12, 4);';
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN raw_includes_vat BOOLEAN NOT NULL DEFAULT TRUE;';
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN raw_per_liters INT NOT NULL DEFAULT 1;';
+            -- Existing prices were stored as published, which is per liter including VAT
+            EXECUTE 'UPDATE oil_price_tiers t
+                     SET price = ROUND(t.price / (1 + vat_rate_at(op.created_at)), 4)
+                     FROM oil_prices op
+                     WHERE op.id = t.price_id;';
+            EXECUTE 'UPDATE oil_prices
+                     SET raw_price = price,
+                         price     = ROUND(price / (1 + vat_rate_at(created_at)), 4);';
+            RAISE NOTICE 'Prices normalised to exclude VAT.';
+        ELSE
+            RAISE NOTICE 'Prices already normalised.';
+        END IF;
+    END
+$$;
+
+CREATE TABLE IF NOT EXISTS price_alerts
+(
+    id                  SERIAL PRIMARY KEY,
+    token               VARCHAR(64)  NOT NULL UNIQUE,
+    recipient           VARCHAR(255) NOT NULL,
+    channel             VARCHAR(32)  NOT NULL,
+    product_id          INT          NOT NULL REFERENCES products (id) ON DELETE CASCADE,
+    zone_id             INT REFERENCES delivery_zones (id) ON DELETE CASCADE,
+    postcode            INT,
+    target_price        NUMERIC(12, 4),
+    percent_drop        NUMERIC(5, 2),
+    reference_price     NUMERIC(12, 4),
+    last_notified_price NUMERIC(12, 4),
+    created_at          TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK (zone_id IS NOT NULL OR postcode IS NOT NULL),
+    CHECK (target_price IS NOT NULL OR percent_drop IS NOT NULL)
+);
+
+CREATE TABLE IF NOT EXISTS price_alert_notifications
+(
+    alert_id INT            NOT NULL REFERENCES price_alerts (id) ON DELETE CASCADE,
+    price_id INT            NOT NULL REFERENCES oil_prices (id) ON DELETE CASCADE,
+    price    NUMERIC(12, 4) NOT NULL,
+    sent_at  TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (alert_id, price_id)
+);
+
+CREATE TABLE IF NOT EXISTS webhooks
+(
+    id          SERIAL PRIMARY KEY,
+    url         VARCHAR(2048) NOT NULL,
+    secret      VARCHAR(255)  NOT NULL,
+    event_types TEXT[]        NOT NULL,
+    active      BOOLEAN       NOT NULL DEFAULT TRUE,
+    created_at  TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries
+(
+    id               BIGSERIAL PRIMARY KEY,
+    webhook_id       INT          NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    event_type       VARCHAR(64)  NOT NULL,
+    payload          JSONB        NOT NULL,
+    status           VARCHAR(16)  NOT NULL DEFAULT 'pending',
+    attempts         INT          NOT NULL DEFAULT 0,
+    next_attempt_at  TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_status_code INT,
+    last_error       TEXT,
+    created_at       TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    delivered_at     TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_pending_idx
+    ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_webhook_idx
+    ON webhook_deliveries (webhook_id, created_at DESC);
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'delivery_zones'
+                         AND column_name = 'created_at') THEN
+            EXECUTE 'ALTER TABLE delivery_zones ADD COLUMN created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP;';
+            RAISE NOTICE 'Column delivery_zones.created_at added.';
+        ELSE
+            RAISE NOTICE 'Column delivery_zones.created_at already exists.';
         END IF;
     END
 $$;
+
+CREATE INDEX IF NOT EXISTS oil_prices_created_at_idx
+    ON oil_prices (created_at, id);
+
+CREATE INDEX IF NOT EXISTS oil_prices_provider_created_at_idx
+    ON oil_prices (provider_id, created_at, id);
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'oil_prices'
+                         AND column_name = 'scraping_run_id') THEN
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN scraping_run_id INT REFERENCES scraping_runs (id) ON DELETE SET NULL;';
+            RAISE NOTICE 'Column oil_prices.scraping_run_id added.';
+        ELSE
+            RAISE NOTICE 'Column oil_prices.scraping_run_id already exists.';
+        END IF;
+    END
+$$;
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'oil_prices'
+                         AND column_name = 'valid_from') THEN
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN valid_from TIMESTAMP, ADD COLUMN valid_to TIMESTAMP, ADD COLUMN last_seen_at TIMESTAMP;';
+            EXECUTE 'UPDATE oil_prices SET valid_from = COALESCE(created_at, CURRENT_TIMESTAMP), last_seen_at = COALESCE(created_at, CURRENT_TIMESTAMP);';
+            EXECUTE 'UPDATE oil_prices o SET valid_to = n.next_from
+                     FROM (SELECT id, LEAD(valid_from) OVER (PARTITION BY provider_id, product_id ORDER BY valid_from, id) AS next_from
+                           FROM oil_prices) n
+                     WHERE o.id = n.id;';
+            EXECUTE 'ALTER TABLE oil_prices ALTER COLUMN valid_from SET DEFAULT CURRENT_TIMESTAMP, ALTER COLUMN valid_from SET NOT NULL, ALTER COLUMN last_seen_at SET DEFAULT CURRENT_TIMESTAMP, ALTER COLUMN last_seen_at SET NOT NULL;';
+            RAISE NOTICE 'Columns oil_prices.valid_from, valid_to and last_seen_at added.';
+        ELSE
+            RAISE NOTICE 'Columns oil_prices.valid_from, valid_to and last_seen_at already exist.';
+        END IF;
+    END
+$$;
+
+CREATE INDEX IF NOT EXISTS oil_prices_provider_product_valid_from_idx
+    ON oil_prices (provider_id, product_id, valid_from, id);
+
+CREATE TABLE IF NOT EXISTS price_plausibility_rules
+(
+    product_id                   INT PRIMARY KEY REFERENCES products (id) ON DELETE CASCADE,
+    min_price                    NUMERIC(12, 4),
+    max_price                    NUMERIC(12, 4),
+    max_change_percent           NUMERIC(7, 2),
+    max_median_deviation_percent NUMERIC(7, 2)
+);
+
+CREATE TABLE IF NOT EXISTS price_quarantine
+(
+    id               SERIAL PRIMARY KEY,
+    provider_id      INT            NOT NULL REFERENCES providers (id) ON DELETE CASCADE,
+    product_id       INT            NOT NULL REFERENCES products (id) ON DELETE CASCADE,
+    price            NUMERIC(12, 4) NOT NULL,
+    raw_price        NUMERIC(12, 4) NOT NULL,
+    raw_includes_vat BOOLEAN        NOT NULL,
+    raw_per_liters   INT            NOT NULL,
+    tiers            JSONB          NOT NULL DEFAULT '[]',
+    reasons          TEXT[]         NOT NULL,
+    status           VARCHAR(16)    NOT NULL DEFAULT 'pending',
+    received_at      TIMESTAMP      NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    reviewed_by      VARCHAR(255),
+    reviewed_at      TIMESTAMP,
+    price_id         INT REFERENCES oil_prices (id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS price_quarantine_pending_idx
+    ON price_quarantine (received_at) WHERE status = 'pending';
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'oil_prices'
+                         AND column_name = 'deleted_at') THEN
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN deleted_at TIMESTAMP, ADD COLUMN deleted_by VARCHAR(255), ADD COLUMN deletion_reason TEXT;';
+            RAISE NOTICE 'Columns oil_prices.deleted_at, deleted_by and deletion_reason added.';
+        ELSE
+            RAISE NOTICE 'Columns oil_prices.deleted_at, deleted_by and deletion_reason already exist.';
+        END IF;
+    END
+$$;
+
+CREATE TABLE IF NOT EXISTS oil_price_revisions
+(
+    id               SERIAL PRIMARY KEY,
+    price_id         INT            NOT NULL REFERENCES oil_prices (id) ON DELETE CASCADE,
+    price            NUMERIC(12, 4) NOT NULL,
+    raw_price        NUMERIC(12, 4),
+    raw_includes_vat BOOLEAN        NOT NULL,
+    raw_per_liters   INT            NOT NULL,
+    tiers            JSONB          NOT NULL DEFAULT '[]',
+    reason           TEXT,
+    revised_by       VARCHAR(255)   NOT NULL,
+    revised_at       TIMESTAMP      NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS oil_price_revisions_price_idx
+    ON oil_price_revisions (price_id, revised_at);
+
+CREATE TABLE IF NOT EXISTS market_index
+(
+    day           DATE           NOT NULL,
+    product_id    INT            NOT NULL REFERENCES products (id) ON DELETE CASCADE,
+    providers     INT            NOT NULL,
+    median        NUMERIC(12, 4) NOT NULL,
+    trimmed_mean  NUMERIC(12, 4) NOT NULL,
+    weighted_mean NUMERIC(12, 4),
+    computed_at   TIMESTAMP      NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (product_id, day)
+);
+
+CREATE TABLE IF NOT EXISTS reference_series
+(
+    id              SERIAL PRIMARY KEY,
+    slug            VARCHAR(64)    NOT NULL UNIQUE,
+    name            VARCHAR(255)   NOT NULL,
+    description     TEXT,
+    currency        CHAR(3)        NOT NULL,
+    unit            VARCHAR(32)    NOT NULL,
+    liters_per_unit NUMERIC(12, 4) NOT NULL CHECK (liters_per_unit > 0),
+    created_at      TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS reference_observations
+(
+    series_id   INT            NOT NULL REFERENCES reference_series (id) ON DELETE CASCADE,
+    observed_on DATE           NOT NULL,
+    value       NUMERIC(14, 4) NOT NULL,
+    PRIMARY KEY (series_id, observed_on)
+);
+
+CREATE TABLE IF NOT EXISTS fx_rates
+(
+    currency CHAR(3)        NOT NULL,
+    day      DATE           NOT NULL,
+    rate     NUMERIC(14, 6) NOT NULL CHECK (rate > 0),
+    PRIMARY KEY (currency, day)
+);
+
+CREATE TABLE IF NOT EXISTS widget_api_keys
+(
+    id                  SERIAL PRIMARY KEY,
+    key                 VARCHAR(64)  NOT NULL UNIQUE,
+    name                VARCHAR(255) NOT NULL,
+    requests_per_minute INT          NOT NULL CHECK (requests_per_minute > 0),
+    active              BOOLEAN      NOT NULL DEFAULT TRUE,
+    window_start        TIMESTAMP,
+    window_requests     INT          NOT NULL DEFAULT 0,
+    created_at          TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS widget_usage
+(
+    key_id   INT    NOT NULL REFERENCES widget_api_keys (id) ON DELETE CASCADE,
+    day      DATE   NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    rejected BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (key_id, day)
+);
+
+CREATE TABLE IF NOT EXISTS duty_rates
+(
+    id         SERIAL PRIMARY KEY,
+    product_id INT            NOT NULL REFERENCES products (id) ON DELETE CASCADE,
+    name       VARCHAR(64)    NOT NULL,
+    amount     NUMERIC(12, 4) NOT NULL CHECK (amount >= 0),
+    valid_from TIMESTAMP      NOT NULL,
+    UNIQUE (product_id, name, valid_from)
+);
+
+CREATE OR REPLACE FUNCTION duties_at(product INT, at TIMESTAMP) RETURNS NUMERIC
+    LANGUAGE sql
+    STABLE
+AS
+$$
+SELECT COALESCE(SUM(amount), 0)
+FROM (SELECT DISTINCT ON (name) amount
+      FROM duty_rates
+      WHERE product_id = product
+        AND valid_from <= at
+      ORDER BY name, valid_from DESC) duties
+$$;
+
+DROP INDEX IF EXISTS oil_prices_provider_product_created_at_key;
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'oil_prices'
+                         AND column_name = 'imported') THEN
+            EXECUTE 'ALTER TABLE oil_prices ADD COLUMN imported BOOLEAN NOT NULL DEFAULT FALSE;';
+            RAISE NOTICE 'Column oil_prices.imported added.';
+        ELSE
+            RAISE NOTICE 'Column oil_prices.imported already exists.';
+        END IF;
+    END
+$$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS oil_prices_imported_key
+    ON oil_prices (provider_id, product_id, created_at)
+    WHERE imported AND deleted_at IS NULL;
+
+DO
+$$
+    BEGIN
+        IF NOT EXISTS (SELECT 1
+                       FROM information_schema.columns
+                       WHERE table_name = 'price_quarantine'
+                         AND column_name = 'observed_at') THEN
+            EXECUTE 'ALTER TABLE price_quarantine ADD COLUMN observed_at TIMESTAMP, ADD COLUMN corrects_price_id INT REFERENCES oil_prices (id) ON DELETE CASCADE, ADD COLUMN correction_reason TEXT, ADD COLUMN requested_by VARCHAR(255);';
+            RAISE NOTICE 'Columns price_quarantine.observed_at, corrects_price_id, correction_reason and requested_by added.';
+        ELSE
+            RAISE NOTICE 'Columns price_quarantine.observed_at, corrects_price_id, correction_reason and requested_by already exist.';
+        END IF;
+    END
+$$;
+
+CREATE TABLE IF NOT EXISTS market_index_dirty
+(
+    id         BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    dirty_from DATE NOT NULL
+);
+
+DO
+$$
+    BEGIN
+        IF EXISTS (SELECT 1
+                   FROM information_schema.columns
+                   WHERE table_name = 'widget_api_keys'
+                     AND column_name = 'key') THEN
+            EXECUTE 'ALTER TABLE widget_api_keys ADD COLUMN key_hash CHAR(64), ADD COLUMN key_prefix VARCHAR(8);';
+            EXECUTE 'UPDATE widget_api_keys SET key_hash = encode(sha256(convert_to(key, ''UTF8'')), ''hex''), key_prefix = LEFT(key, 6);';
+            EXECUTE 'ALTER TABLE widget_api_keys ALTER COLUMN key_hash SET NOT NULL, ALTER COLUMN key_prefix SET NOT NULL, ADD CONSTRAINT widget_api_keys_key_hash_key UNIQUE (key_hash), DROP COLUMN key;';
+            RAISE NOTICE 'Column widget_api_keys.key replaced by key_hash and key_prefix.';
+        ELSE
+            RAISE NOTICE 'Column widget_api_keys.key already replaced by key_hash and key_prefix.';
+        END IF;
+    END
+$$;
+
+CREATE INDEX IF NOT EXISTS oil_prices_valid_from_idx
+    ON oil_prices (valid_from DESC, id DESC);
@@ -1,13 +1,23 @@
+use crate::charts::cache::ChartCache;
+use crate::notifications::notifier::Notifier;
+use crate::streams::events::EventBus;
 use sqlx::PgPool;
+use std::sync::Arc;
 
 /// Application state.
 ///
-/// This struct is used to store the database connection pool.
+/// This struct is used to store the database connection pool and shared services.
 ///
 /// # Fields
 ///
 /// * `db` - The database connection pool.
+/// * `notifiers` - The configured price alert notifiers, one per delivery channel.
+/// * `events` - The bus live events such as newly inserted prices are published on.
+/// * `charts` - The cache of rendered charts.
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
+    pub(crate) notifiers: Arc<Vec<Arc<dyn Notifier>>>,
+    pub(crate) events: Arc<EventBus>,
+    pub(crate) charts: Arc<ChartCache>,
 }
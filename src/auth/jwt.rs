@@ -1,3 +1,4 @@
+use crate::errors::ErrorBody;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::{async_trait, Json};
@@ -9,7 +10,7 @@ use jsonwebtoken::{decode, DecodingKey, EncodingKey, Validation};
 use once_cell::sync::Lazy;
 use rand::distributions::{Alphanumeric, DistString};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use utoipa::ToSchema;
 
 /// Struct to hold encoding and decoding keys for JWT.
 pub(crate) struct Keys {
@@ -58,9 +59,9 @@ impl IntoResponse for AuthError {
             AuthError::TokenCreation => (StatusCode::INTERNAL_SERVER_ERROR, "Token creation error"),
             AuthError::InvalidToken => (StatusCode::BAD_REQUEST, "Invalid token"),
         };
-        let body = Json(json!({
-            "error": error_message,
-        }));
+        let body = Json(ErrorBody {
+            error: error_message.to_string(),
+        });
         (status, body).into_response()
     }
 }
@@ -96,10 +97,25 @@ where
             .await
             .map_err(|_| AuthError::InvalidToken)?;
         // Decode the user data
+        Claims::from_token(bearer.token())
+    }
+}
+
+impl Claims {
+    /// Decodes and validates a JWT token.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The encoded token.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Claims, AuthError>` - The claims of the token or an authentication error.
+    pub(crate) fn from_token(token: &str) -> Result<Self, AuthError> {
         let mut validation = Validation::default();
         validation.validate_exp = true; // Ensure expiration is validated
 
-        let token_data = decode::<Claims>(bearer.token(), &KEYS.decoding, &validation)
+        let token_data = decode::<Claims>(token, &KEYS.decoding, &validation)
             .map_err(|_| AuthError::InvalidToken)?;
 
         Ok(token_data.claims)
@@ -107,7 +123,7 @@ where
 }
 
 /// Struct representing the authentication response body.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub(crate) struct AuthBody {
     access_token: String,
     token_type: String,
@@ -132,7 +148,7 @@ impl AuthBody {
 }
 
 /// Struct representing the authentication payload.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub(crate) struct AuthPayload {
     pub(crate) client_id: String,
     pub(crate) client_secret: String,
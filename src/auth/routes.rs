@@ -11,6 +11,7 @@ use crate::app_state::AppState;
 use crate::auth::jwt::{AuthBody, AuthPayload, KEYS};
 use crate::auth::jwt::{AuthError, Claims};
 use crate::auth::security::{hash_password, verify_password};
+use crate::errors::ErrorBody;
 
 #[derive(Debug, sqlx::FromRow)]
 struct User {
@@ -43,6 +44,17 @@ impl From<ArgonError> for AuthError {
 /// # Returns
 ///
 /// * `Result<Json<AuthBody>, AuthError>` - The result of the operation, either a JSON response with the JWT token or an authentication error.
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = AuthPayload,
+    responses(
+        (status = 200, description = "A token for the `Authorization: Bearer` header", body = AuthBody),
+        (status = 400, description = "The credentials are missing", body = ErrorBody),
+        (status = 401, description = "The credentials are wrong", body = ErrorBody),
+    )
+)]
 pub(crate) async fn authorize(
     State(state): State<AppState>,
     Json(payload): Json<AuthPayload>,
@@ -98,6 +110,16 @@ pub(crate) async fn authorize(
 /// # Returns
 ///
 /// * `Result<impl IntoResponse, impl IntoResponse>` - The result of the operation, either a success status code or an error response.
+#[utoipa::path(
+    post,
+    path = "/auth/create",
+    tag = "auth",
+    request_body = AuthPayload,
+    responses(
+        (status = 200, description = "The user was created"),
+        (status = 400, description = "The user already exists or could not be created", body = String, content_type = "text/plain"),
+    )
+)]
 pub(crate) async fn create_user(
     State(state): State<AppState>,
     Json(payload): Json<AuthPayload>,
@@ -109,7 +131,7 @@ pub(crate) async fn create_user(
         .await
         .unwrap();
 
-    if !check_user.is_some() {
+    if check_user.is_none() {
         if let Err(e) = sqlx::query("INSERT INTO users (client_id, password_hash) VALUES ($1, $2)")
             .bind(payload.client_id)
             .bind(password_hash)
@@ -0,0 +1,87 @@
+use std::process::ExitCode;
+
+/// The API used when `OLIEPRISER_API_URL` is not set.
+const DEFAULT_API_URL: &str = "http://localhost:8000";
+
+const USAGE: &str = "\
+Usage: import_prices <file.csv> [--dry-run] [option=value ...]
+
+Imports historical prices from a CSV file through POST /imports/prices.
+
+Options are passed on as query parameters, e.g. provider_column=Leverandør,
+price_column=Pris, timestamp_column=Dato, product=diesel, delimiter=;,
+decimal_comma=true or timestamp_format=%d-%m-%Y.
+
+Environment:
+  OLIEPRISER_API_URL    The URL of the API (default: http://localhost:8000)
+  OLIEPRISER_API_TOKEN  A JWT as returned by POST /auth/login";
+
+///
+/// Imports historical prices from a CSV file
+///
+/// Sends the file to the import endpoint of a running API and prints the import report.
+///
+/// # Returns
+///
+/// * `ExitCode` - Success if the import went through, failure otherwise
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next().filter(|path| !path.starts_with('-')) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let mut query = vec![];
+    for arg in args {
+        if arg == "--dry-run" {
+            query.push(("dry_run".to_string(), "true".to_string()));
+        } else if let Some((key, value)) = arg.split_once('=') {
+            query.push((key.to_string(), value.to_string()));
+        } else {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let Ok(token) = std::env::var("OLIEPRISER_API_TOKEN") else {
+        eprintln!("OLIEPRISER_API_TOKEN must be set");
+        return ExitCode::FAILURE;
+    };
+    let api_url =
+        std::env::var("OLIEPRISER_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+
+    let body = match std::fs::read(&path) {
+        Ok(body) => body,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/imports/prices", api_url.trim_end_matches('/')))
+        .query(&query)
+        .bearer_auth(token)
+        .header("Content-Type", "text/csv")
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            println!("{}", response.text().await.unwrap_or_default());
+            if status.is_success() {
+                ExitCode::SUCCESS
+            } else {
+                eprintln!("Import failed with status {}", status);
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            eprintln!("Could not reach {}: {}", api_url, e);
+            ExitCode::FAILURE
+        }
+    }
+}
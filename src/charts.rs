@@ -0,0 +1,2 @@
+pub(crate) mod cache;
+pub(crate) mod render;
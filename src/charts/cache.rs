@@ -0,0 +1,65 @@
+use axum::body::Bytes;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a rendered chart is served from the cache.
+pub(crate) const CHART_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// The most charts kept in the cache at once.
+const CAPACITY: usize = 500;
+
+/// Keeps rendered charts in memory, keyed by the parameters they were rendered with.
+///
+/// Charts expire after `CHART_TTL`, so new prices show up within that time.
+pub(crate) struct ChartCache {
+    entries: Mutex<HashMap<String, (Instant, Bytes)>>,
+}
+
+impl ChartCache {
+    /// Creates a new, empty `ChartCache`.
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a chart rendered with the given parameters, unless it has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The parameters the chart was rendered with.
+    pub(crate) fn get(&self, key: &str) -> Option<Bytes> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(key)
+            .filter(|(rendered_at, _)| rendered_at.elapsed() < CHART_TTL)
+            .map(|(_, chart)| chart.clone())
+    }
+
+    /// Stores a rendered chart.
+    ///
+    /// Expired charts are dropped when the cache is full, and if that frees no room the chart
+    /// closest to expiring is dropped too.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The parameters the chart was rendered with.
+    /// * `chart` - The rendered chart.
+    pub(crate) fn insert(&self, key: String, chart: Bytes) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= CAPACITY && !entries.contains_key(&key) {
+            entries.retain(|_, (rendered_at, _)| rendered_at.elapsed() < CHART_TTL);
+            if entries.len() >= CAPACITY {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (rendered_at, _))| *rendered_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (Instant::now(), chart));
+    }
+}
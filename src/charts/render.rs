@@ -0,0 +1,255 @@
+use crate::helpers::escape_xml;
+use crate::models::charts::ChartTheme;
+use chrono::{Datelike, NaiveDate};
+use once_cell::sync::Lazy;
+use resvg::{tiny_skia, usvg};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// The fonts text is rendered with in PNG charts, loaded once from the system.
+static FONTS: Lazy<Arc<usvg::fontdb::Database>> = Lazy::new(|| {
+    let mut fonts = usvg::fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+/// The font families text is set in, in order of preference.
+const FONT_FAMILY: &str = "Arial, 'DejaVu Sans', sans-serif";
+
+/// The Danish abbreviations of the months, January first.
+const MONTHS: [&str; 12] = [
+    "jan.", "feb.", "mar.", "apr.", "maj", "jun.", "jul.", "aug.", "sep.", "okt.", "nov.", "dec.",
+];
+
+/// The space around the plot area for the title and axis labels, in pixels.
+const MARGIN_TOP: f64 = 56.0;
+const MARGIN_RIGHT: f64 = 24.0;
+const MARGIN_BOTTOM: f64 = 36.0;
+const MARGIN_LEFT: f64 = 72.0;
+
+/// The number of grid lines and labels aimed for on each axis.
+const Y_TICKS: f64 = 5.0;
+const X_TICKS: i64 = 6;
+
+/// A daily price series to draw as a line chart.
+pub(crate) struct Chart {
+    pub(crate) title: String,
+    /// The unit of the prices, shown under the title.
+    pub(crate) unit: String,
+    /// The price of every day, oldest first. Days without a price leave a gap in the line.
+    pub(crate) points: Vec<(NaiveDate, Option<f64>)>,
+}
+
+/// Renders a chart as an SVG document.
+///
+/// Numbers and dates are formatted the Danish way, with a decimal comma and abbreviated Danish
+/// month names.
+///
+/// # Arguments
+///
+/// * `chart` - The chart to render.
+/// * `width` - The width of the image, in pixels.
+/// * `height` - The height of the image, in pixels.
+/// * `theme` - The colour scheme of the chart.
+///
+/// # Returns
+///
+/// * `String` - The SVG document.
+pub(crate) fn render_svg(chart: &Chart, width: u32, height: u32, theme: ChartTheme) -> String {
+    let colors = theme.colors();
+    let (width, height) = (f64::from(width), f64::from(height));
+    let plot_width = width - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = height - MARGIN_TOP - MARGIN_BOTTOM;
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="{FONT_FAMILY}">"#
+    );
+    let _ = write!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        colors.background
+    );
+    let _ = write!(
+        svg,
+        r#"<text x="{MARGIN_LEFT}" y="24" font-size="16" font-weight="bold" fill="{}">{}</text>"#,
+        colors.text,
+        escape_xml(&chart.title)
+    );
+    let _ = write!(
+        svg,
+        r#"<text x="{MARGIN_LEFT}" y="42" font-size="12" fill="{}">{}</text>"#,
+        colors.text,
+        escape_xml(&chart.unit)
+    );
+
+    let values: Vec<f64> = chart
+        .points
+        .iter()
+        .filter_map(|(_, value)| *value)
+        .collect();
+    let (Some(min), Some(max)) = (
+        values.iter().copied().reduce(f64::min),
+        values.iter().copied().reduce(f64::max),
+    ) else {
+        let _ = write!(
+            svg,
+            r#"<text x="{}" y="{}" font-size="14" text-anchor="middle" fill="{}">Ingen priser i perioden</text></svg>"#,
+            width / 2.0,
+            MARGIN_TOP + plot_height / 2.0,
+            colors.text
+        );
+        return svg;
+    };
+
+    // Widen a flat series so the line sits in the middle of the plot.
+    let (min, max) = if max - min < 0.01 {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min, max)
+    };
+    let step = nice_step((max - min) / Y_TICKS);
+    let (low, high) = ((min / step).floor() * step, (max / step).ceil() * step);
+    let decimals = (0..3)
+        .find(|decimals| {
+            let scaled = step * 10f64.powi(*decimals);
+            (scaled - scaled.round()).abs() < 1e-6
+        })
+        .unwrap_or(3) as usize;
+
+    let y = |value: f64| MARGIN_TOP + plot_height - (value - low) / (high - low) * plot_height;
+    let first = chart.points[0].0;
+    let days = (chart.points[chart.points.len() - 1].0 - first)
+        .num_days()
+        .max(1);
+    let x =
+        |date: NaiveDate| MARGIN_LEFT + (date - first).num_days() as f64 / days as f64 * plot_width;
+
+    let mut tick = low;
+    while tick <= high + step / 2.0 {
+        let _ = write!(
+            svg,
+            r#"<line x1="{MARGIN_LEFT}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{}" stroke-width="1"/>"#,
+            width - MARGIN_RIGHT,
+            colors.grid,
+            y = y(tick)
+        );
+        let _ = write!(
+            svg,
+            r#"<text x="{}" y="{:.1}" font-size="11" text-anchor="end" fill="{}">{}</text>"#,
+            MARGIN_LEFT - 8.0,
+            y(tick) + 4.0,
+            colors.text,
+            format_number(tick, decimals)
+        );
+        tick += step;
+    }
+
+    let with_year = days > 300;
+    let every = (days / X_TICKS).max(1);
+    for day in (0..=days).step_by(every as usize) {
+        let date = first + chrono::Duration::days(day);
+        let _ = write!(
+            svg,
+            r#"<text x="{:.1}" y="{}" font-size="11" text-anchor="middle" fill="{}">{}</text>"#,
+            x(date),
+            height - MARGIN_BOTTOM + 18.0,
+            colors.text,
+            format_date(date, with_year)
+        );
+    }
+
+    let mut path = String::new();
+    let mut pen_down = false;
+    for (date, value) in &chart.points {
+        match value {
+            Some(value) => {
+                let _ = write!(
+                    path,
+                    "{}{:.1},{:.1} ",
+                    if pen_down { "L" } else { "M" },
+                    x(*date),
+                    y(*value)
+                );
+                pen_down = true;
+            }
+            None => pen_down = false,
+        }
+    }
+    let _ = write!(
+        svg,
+        r#"<path d="{}" fill="none" stroke="{}" stroke-width="2" stroke-linejoin="round"/></svg>"#,
+        path.trim_end(),
+        colors.line
+    );
+
+    svg
+}
+
+/// Renders an SVG document as a PNG image.
+///
+/// # Arguments
+///
+/// * `svg` - The SVG document.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, String>` - The PNG image, or a description of why it could not be rendered.
+pub(crate) fn render_png(svg: &str) -> Result<Vec<u8>, String> {
+    let options = usvg::Options {
+        fontdb: FONTS.clone(),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| e.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| "the image has no area".to_string())?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+/// Rounds a step between grid lines up to 1, 2, 2.5 or 5 times a power of ten.
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let nice = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .find(|factor| factor * magnitude >= raw)
+        .unwrap_or(10.0);
+    nice * magnitude
+}
+
+/// Formats a number the Danish way, such as `12.345,67`.
+fn format_number(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push('.');
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if value < 0.0 && formatted.chars().any(|c| c != '0' && c != '.') {
+        "-"
+    } else {
+        ""
+    };
+    if fraction.is_empty() {
+        format!("{sign}{grouped}")
+    } else {
+        format!("{sign}{grouped},{fraction}")
+    }
+}
+
+/// Formats a date the Danish way, such as `18. okt.` or `okt. 2026`.
+fn format_date(date: NaiveDate, with_year: bool) -> String {
+    let month = MONTHS[date.month0() as usize];
+    if with_year {
+        format!("{} {}", month, date.year())
+    } else {
+        format!("{}. {}", date.day(), month)
+    }
+}
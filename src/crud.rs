@@ -1,4 +1,20 @@
+pub(crate) mod charts;
 pub(crate) mod delivery_zones;
+pub(crate) mod exports;
+pub(crate) mod feeds;
+pub(crate) mod fees;
+pub(crate) mod forecast;
+pub(crate) mod imports;
+pub(crate) mod live_updates;
+pub(crate) mod market_index;
+pub(crate) mod price_alerts;
 pub(crate) mod prices;
+pub(crate) mod products;
 pub(crate) mod providers;
+pub(crate) mod quarantine;
+pub(crate) mod quotes;
+pub(crate) mod references;
 pub(crate) mod scraping_runs;
+pub(crate) mod vat_rates;
+pub(crate) mod webhooks;
+pub(crate) mod widget;
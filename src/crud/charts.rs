@@ -0,0 +1,379 @@
+use crate::app_state::AppState;
+use crate::charts::cache::CHART_TTL;
+use crate::charts::render::{render_png, render_svg, Chart};
+use crate::errors::{ChartsError, ErrorBody};
+use crate::helpers::fetch_product_by_slug;
+use crate::models::charts::{
+    ChartFormat, ChartParams, CHART_HEIGHT_RANGE, CHART_WIDTH_RANGE, DEFAULT_CHART_DAYS,
+    DEFAULT_CHART_HEIGHT, DEFAULT_CHART_WIDTH, MAX_CHART_DAYS,
+};
+use crate::models::products::DEFAULT_PRODUCT;
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use chrono::NaiveDate;
+
+/// The period and size of a chart, with the defaults filled in.
+struct ChartRequest {
+    product: String,
+    start: NaiveDate,
+    end: NaiveDate,
+    width: u32,
+    height: u32,
+    format: ChartFormat,
+}
+
+impl ChartRequest {
+    /// Checks the parameters of a chart and fills in the defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The query parameters of the chart.
+    /// * `format` - The image format the chart is rendered in.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ChartRequest, ChartsError>` - The checked request, or an error if a parameter is out of range.
+    fn new(params: &ChartParams, format: ChartFormat) -> Result<Self, ChartsError> {
+        let end = params
+            .end
+            .unwrap_or_else(|| chrono::Utc::now().date_naive());
+        let start = params
+            .start
+            .unwrap_or(end - chrono::Duration::days(DEFAULT_CHART_DAYS));
+        if start >= end {
+            return Err(ChartsError::invalid_input("start must be before end"));
+        }
+        if (end - start).num_days() > MAX_CHART_DAYS {
+            return Err(ChartsError::invalid_input(format!(
+                "a chart may cover at most {} days",
+                MAX_CHART_DAYS
+            )));
+        }
+
+        let width = params.width.unwrap_or(DEFAULT_CHART_WIDTH);
+        let height = params.height.unwrap_or(DEFAULT_CHART_HEIGHT);
+        if !CHART_WIDTH_RANGE.contains(&width) || !CHART_HEIGHT_RANGE.contains(&height) {
+            return Err(ChartsError::invalid_input(format!(
+                "width must be between {} and {} and height between {} and {}",
+                CHART_WIDTH_RANGE.start(),
+                CHART_WIDTH_RANGE.end(),
+                CHART_HEIGHT_RANGE.start(),
+                CHART_HEIGHT_RANGE.end()
+            )));
+        }
+
+        Ok(Self {
+            product: params
+                .product
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PRODUCT.to_string()),
+            start,
+            end,
+            width,
+            height,
+            format,
+        })
+    }
+
+    /// Returns the key a chart of the given subject is cached under.
+    fn cache_key(&self, subject: &str, params: &ChartParams) -> String {
+        format!(
+            "{}|{}|{}|{}|{}x{}|{:?}|{}|{:?}",
+            subject,
+            self.product,
+            self.start,
+            self.end,
+            self.width,
+            self.height,
+            params.theme,
+            params.vat.includes_vat(),
+            self.format
+        )
+    }
+}
+
+/// Renders a provider's price of a product as an SVG chart.
+///
+/// See `provider_chart` for the parameters.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/prices/chart.svg",
+    tag = "charts",
+    params(("id" = i32, Path, description = "The ID of the provider"), ChartParams),
+    responses(
+        (status = 200, description = "The chart", body = String, content_type = "image/svg+xml"),
+        (status = 400, description = "The period, the size or the product is invalid", body = ErrorBody),
+        (status = 404, description = "The provider does not exist", body = ErrorBody),
+        (status = 500, description = "The chart could not be rendered", body = ErrorBody),
+    )
+)]
+pub(crate) async fn provider_chart_svg(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<ChartParams>,
+) -> Result<Response, ChartsError> {
+    provider_chart(state, id, params, ChartFormat::Svg).await
+}
+
+/// Renders a provider's price of a product as a PNG chart.
+///
+/// See `provider_chart` for the parameters.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/prices/chart.png",
+    tag = "charts",
+    params(("id" = i32, Path, description = "The ID of the provider"), ChartParams),
+    responses(
+        (status = 200, description = "The chart", body = String, content_type = "image/png"),
+        (status = 400, description = "The period, the size or the product is invalid", body = ErrorBody),
+        (status = 404, description = "The provider does not exist", body = ErrorBody),
+        (status = 500, description = "The chart could not be rendered", body = ErrorBody),
+    )
+)]
+pub(crate) async fn provider_chart_png(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<ChartParams>,
+) -> Result<Response, ChartsError> {
+    provider_chart(state, id, params, ChartFormat::Png).await
+}
+
+/// Renders the market index of a product as an SVG chart.
+///
+/// See `market_index_chart` for the parameters.
+#[utoipa::path(
+    get,
+    path = "/index/chart.svg",
+    tag = "charts",
+    params(ChartParams),
+    responses(
+        (status = 200, description = "The chart", body = String, content_type = "image/svg+xml"),
+        (status = 400, description = "The period, the size or the product is invalid", body = ErrorBody),
+        (status = 500, description = "The chart could not be rendered", body = ErrorBody),
+    )
+)]
+pub(crate) async fn market_index_chart_svg(
+    State(state): State<AppState>,
+    Query(params): Query<ChartParams>,
+) -> Result<Response, ChartsError> {
+    market_index_chart(state, params, ChartFormat::Svg).await
+}
+
+/// Renders the market index of a product as a PNG chart.
+///
+/// See `market_index_chart` for the parameters.
+#[utoipa::path(
+    get,
+    path = "/index/chart.png",
+    tag = "charts",
+    params(ChartParams),
+    responses(
+        (status = 200, description = "The chart", body = String, content_type = "image/png"),
+        (status = 400, description = "The period, the size or the product is invalid", body = ErrorBody),
+        (status = 500, description = "The chart could not be rendered", body = ErrorBody),
+    )
+)]
+pub(crate) async fn market_index_chart_png(
+    State(state): State<AppState>,
+    Query(params): Query<ChartParams>,
+) -> Result<Response, ChartsError> {
+    market_index_chart(state, params, ChartFormat::Png).await
+}
+
+/// Renders a chart of the price a provider had in effect at the end of each day.
+///
+/// The period defaults to the last 90 days, the size to 800 by 400 pixels and the product to the
+/// default product. Charts are cached by their parameters.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool and the chart cache.
+/// * `id` - The ID of the provider.
+/// * `params` - The query parameters choosing the product, the period, the size, the theme and whether prices include VAT.
+/// * `format` - The image format to render the chart in.
+///
+/// # Returns
+///
+/// * `Result<Response, ChartsError>` - The result of the operation, either the image or an error.
+async fn provider_chart(
+    state: AppState,
+    id: i32,
+    params: ChartParams,
+    format: ChartFormat,
+) -> Result<Response, ChartsError> {
+    let request = ChartRequest::new(&params, format)?;
+    let key = request.cache_key(&format!("provider:{}", id), &params);
+    if let Some(image) = state.charts.get(&key) {
+        return Ok(image_response(format, image));
+    }
+
+    let provider: String = sqlx::query_scalar("SELECT name FROM providers WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(ChartsError::fetch_error)?
+        .ok_or_else(ChartsError::not_found)?;
+    let (product_id, product) = fetch_product(&state, &request.product).await?;
+
+    let points = sqlx::query_as::<_, (NaiveDate, Option<f64>)>(
+        r#"
+        SELECT
+            d.day::DATE,
+            CAST(
+                CASE
+                    WHEN $5 THEN p.price * (1 + vat_rate_at(d.day))
+                    ELSE p.price
+                END AS DOUBLE PRECISION
+            )
+        FROM
+            generate_series($3::TIMESTAMP, $4::TIMESTAMP, INTERVAL '1 day') AS d(day)
+        LEFT JOIN LATERAL (
+            SELECT op.price
+            FROM oil_prices op
+            WHERE op.provider_id = $1
+              AND op.product_id = $2
+              AND op.valid_from < d.day + INTERVAL '1 day'
+              AND op.deleted_at IS NULL
+            ORDER BY op.valid_from DESC, op.id DESC
+            LIMIT 1
+        ) p ON TRUE
+        ORDER BY
+            d.day
+        "#,
+    )
+    .bind(id)
+    .bind(product_id)
+    .bind(request.start)
+    .bind(request.end)
+    .bind(params.vat.includes_vat())
+    .fetch_all(&state.db)
+    .await
+    .map_err(ChartsError::fetch_error)?;
+
+    let chart = Chart {
+        title: format!("{} – {}", provider, product),
+        unit: unit(params.vat.includes_vat()),
+        points,
+    };
+    render(&state, key, &request, &params, chart).await
+}
+
+/// Renders a chart of the median of the market index of each day.
+///
+/// The period defaults to the last 90 days, the size to 800 by 400 pixels and the product to the
+/// default product. Charts are cached by their parameters.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool and the chart cache.
+/// * `params` - The query parameters choosing the product, the period, the size, the theme and whether prices include VAT.
+/// * `format` - The image format to render the chart in.
+///
+/// # Returns
+///
+/// * `Result<Response, ChartsError>` - The result of the operation, either the image or an error.
+async fn market_index_chart(
+    state: AppState,
+    params: ChartParams,
+    format: ChartFormat,
+) -> Result<Response, ChartsError> {
+    let request = ChartRequest::new(&params, format)?;
+    let key = request.cache_key("index", &params);
+    if let Some(image) = state.charts.get(&key) {
+        return Ok(image_response(format, image));
+    }
+
+    let (product_id, product) = fetch_product(&state, &request.product).await?;
+
+    let points = sqlx::query_as::<_, (NaiveDate, Option<f64>)>(
+        r#"
+        SELECT
+            d.day::DATE,
+            CAST(
+                CASE
+                    WHEN $4 THEN m.median * (1 + vat_rate_at(d.day))
+                    ELSE m.median
+                END AS DOUBLE PRECISION
+            )
+        FROM
+            generate_series($2::TIMESTAMP, $3::TIMESTAMP, INTERVAL '1 day') AS d(day)
+        LEFT JOIN
+            market_index m ON m.product_id = $1 AND m.day = d.day
+        ORDER BY
+            d.day
+        "#,
+    )
+    .bind(product_id)
+    .bind(request.start)
+    .bind(request.end)
+    .bind(params.vat.includes_vat())
+    .fetch_all(&state.db)
+    .await
+    .map_err(ChartsError::fetch_error)?;
+
+    let chart = Chart {
+        title: format!("Markedsindeks – {}", product),
+        unit: format!("Median, {}", unit(params.vat.includes_vat())),
+        points,
+    };
+    render(&state, key, &request, &params, chart).await
+}
+
+/// Looks up the ID and name of the product a chart is drawn for, rejecting unknown slugs.
+async fn fetch_product(state: &AppState, slug: &str) -> Result<(i32, String), ChartsError> {
+    fetch_product_by_slug(&state.db, Some(slug))
+        .await
+        .map_err(ChartsError::fetch_error)?
+        .ok_or_else(|| ChartsError::invalid_input("unknown product"))
+}
+
+/// Returns the unit prices are charted in.
+fn unit(includes_vat: bool) -> String {
+    if includes_vat {
+        "kr. pr. liter inkl. moms".to_string()
+    } else {
+        "kr. pr. liter ekskl. moms".to_string()
+    }
+}
+
+/// Renders a chart in the requested format and caches it.
+///
+/// PNG images are rasterised on a blocking thread.
+async fn render(
+    state: &AppState,
+    key: String,
+    request: &ChartRequest,
+    params: &ChartParams,
+    chart: Chart,
+) -> Result<Response, ChartsError> {
+    let svg = render_svg(&chart, request.width, request.height, params.theme);
+    let image = match request.format {
+        ChartFormat::Svg => Bytes::from(svg),
+        ChartFormat::Png => tokio::task::spawn_blocking(move || render_png(&svg))
+            .await
+            .map_err(|e| ChartsError::render_error(e.to_string()))?
+            .map(Bytes::from)
+            .map_err(ChartsError::render_error)?,
+    };
+
+    state.charts.insert(key, image.clone());
+    Ok(image_response(request.format, image))
+}
+
+/// Wraps a rendered or cached image in a response with its content type, letting clients and
+/// proxies cache it as long as the chart cache keeps it.
+fn image_response(format: ChartFormat, image: Bytes) -> Response {
+    (
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (
+                CACHE_CONTROL,
+                format!("public, max-age={}", CHART_TTL.as_secs()),
+            ),
+        ],
+        image,
+    )
+        .into_response()
+}
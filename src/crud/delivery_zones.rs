@@ -1,10 +1,15 @@
 use crate::app_state::AppState;
 use crate::auth::jwt::Claims;
-use crate::errors::{DeliveryZonesError, DeliveryZonesSuccess};
+use crate::errors::{DeliveryZonesError, DeliveryZonesSuccess, ErrorBody, MessageBody};
 use crate::helpers::zone_exists;
-use crate::models::delivery_zones::{DeliveryZones, DeliveryZonesAdd, DeliveryZonesInsertResponse};
-use axum::extract::{Path, State};
+use crate::models::delivery_zones::{
+    DeliveryZoneListParams, DeliveryZonePostcodes, DeliveryZoneSort, DeliveryZones,
+    DeliveryZonesAdd, DeliveryZonesInsertResponse,
+};
+use crate::pagination::{PageParams, Paginated};
+use axum::extract::{OriginalUri, Path, Query, State};
 use axum::Json;
+use sqlx::{Postgres, QueryBuilder};
 
 /// Creates a new delivery zone in the database.
 ///
@@ -17,6 +22,17 @@ use axum::Json;
 /// # Returns
 ///
 /// * `Result<DeliveryZonesSuccess, DeliveryZonesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/zones",
+    tag = "zones",
+    request_body = DeliveryZonesAdd,
+    responses(
+        (status = 201, description = "The delivery zone was created", body = MessageBody),
+        (status = 500, description = "The delivery zone could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn create_delivery_zone(
     _claims: Claims,
     State(state): State<AppState>,
@@ -34,24 +50,65 @@ pub(crate) async fn create_delivery_zone(
     Ok(DeliveryZonesSuccess::created(row.id))
 }
 
-/// Fetches all delivery zones from the database.
+/// Fetches a page of delivery zones from the database.
+///
+/// The next page is linked in the `Link` and `X-Next-Cursor` response headers.
 ///
 /// # Arguments
 ///
 /// * `state` - The application state containing the database connection pool.
+/// * `uri` - The URI of the request, used to link to the next page.
+/// * `page` - The pagination and sorting parameters.
+/// * `params` - The query parameters for filtering by provider.
 ///
 /// # Returns
 ///
-/// * `Result<Json<Vec<DeliveryZones>>, DeliveryZonesError>` - The result of the operation, either a list of delivery zones or an error.
+/// * `Result<Paginated<DeliveryZones>, DeliveryZonesError>` - The result of the operation, either a page of delivery zones or an error.
+#[utoipa::path(
+    get,
+    path = "/zones",
+    tag = "zones",
+    params(
+        PageParams<DeliveryZoneSort>,
+        ("sort" = Option<DeliveryZoneSort>, Query, description = "The column to sort by"),
+        DeliveryZoneListParams,
+    ),
+    responses(
+        (status = 200, description = "A page of delivery zones, with the next page linked in the `Link` and `X-Next-Cursor` headers", body = Vec<DeliveryZones>),
+        (status = 400, description = "The cursor is malformed", body = ErrorBody),
+        (status = 500, description = "The delivery zones could not be fetched", body = ErrorBody),
+    )
+)]
 pub(crate) async fn fetch_delivery_zones(
     State(state): State<AppState>,
-) -> Result<Json<Vec<DeliveryZones>>, DeliveryZonesError> {
-    let res = sqlx::query_as::<_, DeliveryZones>("SELECT * FROM delivery_zones")
+    OriginalUri(uri): OriginalUri,
+    Query(page): Query<PageParams<DeliveryZoneSort>>,
+    Query(params): Query<DeliveryZoneListParams>,
+) -> Result<Paginated<DeliveryZones>, DeliveryZonesError> {
+    let mut query = QueryBuilder::<Postgres>::new(
+        "SELECT * FROM (SELECT id, name, description, created_at FROM delivery_zones) AS page WHERE TRUE",
+    );
+
+    if let Some(provider_id) = params.provider_id {
+        query
+            .push(
+                " AND EXISTS (SELECT 1 FROM provider_delivery_zones pdz \
+                 WHERE pdz.zone_id = page.id AND pdz.provider_id = ",
+            )
+            .push_bind(provider_id)
+            .push(")");
+    }
+
+    page.push_page(&mut query)
+        .map_err(DeliveryZonesError::invalid_input)?;
+
+    let res = query
+        .build_query_as::<DeliveryZones>()
         .fetch_all(&state.db)
         .await
         .map_err(DeliveryZonesError::fetch_error)?;
 
-    Ok(Json(res))
+    Ok(page.paginate(res, uri))
 }
 
 /// Deletes a delivery zone from the database.
@@ -65,6 +122,17 @@ pub(crate) async fn fetch_delivery_zones(
 /// # Returns
 ///
 /// * `Result<DeliveryZonesSuccess, DeliveryZonesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/zones/{id}",
+    tag = "zones",
+    params(("id" = i32, Path, description = "The ID of the delivery zone")),
+    responses(
+        (status = 200, description = "The delivery zone was deleted", body = MessageBody),
+        (status = 500, description = "The delivery zone could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn delete_delivery_zone(
     _claims: Claims,
     State(state): State<AppState>,
@@ -83,3 +151,119 @@ pub(crate) async fn delete_delivery_zone(
 
     Ok(DeliveryZonesSuccess::deleted(id))
 }
+
+/// Fetches the postcode ranges covered by a delivery zone from the database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the delivery zone.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<DeliveryZonePostcodes>>, DeliveryZonesError>` - The result of the operation, either a list of postcode ranges or an error.
+#[utoipa::path(
+    get,
+    path = "/zones/{id}/postcodes",
+    tag = "zones",
+    params(("id" = i32, Path, description = "The ID of the delivery zone")),
+    responses(
+        (status = 200, description = "The postcode ranges covered by the delivery zone", body = Vec<DeliveryZonePostcodes>),
+        (status = 500, description = "The postcode ranges could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_zone_postcodes(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<DeliveryZonePostcodes>>, DeliveryZonesError> {
+    let res = sqlx::query_as::<_, DeliveryZonePostcodes>(
+        r#"
+        SELECT postcode_from, postcode_to
+        FROM delivery_zone_postcodes
+        WHERE zone_id = $1
+        ORDER BY postcode_from
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(DeliveryZonesError::fetch_error)?;
+
+    Ok(Json(res))
+}
+
+/// Replaces the postcode ranges covered by a delivery zone in the database.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the delivery zone.
+/// * `json` - The JSON payload containing the complete list of postcode ranges.
+///
+/// # Returns
+///
+/// * `Result<DeliveryZonesSuccess, DeliveryZonesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    put,
+    path = "/zones/{id}/postcodes",
+    tag = "zones",
+    params(("id" = i32, Path, description = "The ID of the delivery zone")),
+    request_body = Vec<DeliveryZonePostcodes>,
+    responses(
+        (status = 200, description = "The postcode ranges were replaced", body = MessageBody),
+        (status = 400, description = "A postcode range is invalid", body = ErrorBody),
+        (status = 404, description = "The delivery zone does not exist", body = ErrorBody),
+        (status = 500, description = "The postcode ranges could not be replaced", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn update_zone_postcodes(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(json): Json<Vec<DeliveryZonePostcodes>>,
+) -> Result<DeliveryZonesSuccess, DeliveryZonesError> {
+    if json
+        .iter()
+        .any(|range| range.postcode_from > range.postcode_to)
+    {
+        return Err(DeliveryZonesError::invalid_input(
+            "postcode_from must not be greater than postcode_to",
+        ));
+    }
+
+    if !zone_exists(id, &state.db).await? {
+        return Err(DeliveryZonesError::not_found());
+    }
+
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(DeliveryZonesError::update_error)?;
+
+    sqlx::query("DELETE FROM delivery_zone_postcodes WHERE zone_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DeliveryZonesError::update_error)?;
+
+    for range in &json {
+        sqlx::query(
+            "INSERT INTO delivery_zone_postcodes (zone_id, postcode_from, postcode_to) VALUES ($1, $2, $3)",
+        )
+        .bind(id)
+        .bind(range.postcode_from)
+        .bind(range.postcode_to)
+        .execute(&mut *tx)
+        .await
+        .map_err(DeliveryZonesError::update_error)?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(DeliveryZonesError::update_error)?;
+
+    Ok(DeliveryZonesSuccess::updated(id))
+}
@@ -0,0 +1,158 @@
+use crate::app_state::AppState;
+use crate::export::ExportFormat;
+use crate::models::exports::{ExportParams, PriceExportRow};
+use crate::models::prices::PriceListParams;
+use axum::body::Body;
+use axum::extract::{Query, State};
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+
+/// How many bytes of formatted rows are collected before they are sent to the client.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How many chunks may be waiting to be sent before reading from the database pauses.
+const CHUNK_BUFFER: usize = 16;
+
+/// Streams the price history as a file.
+///
+/// The format is chosen by the `format` parameter or the `Accept` header and is CSV unless
+/// `excel` or `ndjson` is asked for. Rows are read from the database and sent as they arrive,
+/// so exports of any size are never held in memory.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `negotiated` - The format negotiated from the `Accept` header.
+/// * `export` - The query parameters choosing the format.
+/// * `params` - The query parameters for filtering by provider, zone, product, date and price, and choosing whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Response` - The streamed file.
+#[utoipa::path(
+    get,
+    path = "/exports/prices",
+    tag = "exports",
+    params(ExportParams, PriceListParams),
+    responses(
+        (status = 200, description = "The price history as a file", content(
+            (String = "text/csv"),
+            (String = "application/vnd.ms-excel"),
+            (String = "application/x-ndjson"),
+        )),
+    )
+)]
+pub(crate) async fn export_prices(
+    State(state): State<AppState>,
+    negotiated: ExportFormat,
+    Query(export): Query<ExportParams>,
+    Query(params): Query<PriceListParams>,
+) -> Response {
+    let format = match export.format.unwrap_or(negotiated) {
+        ExportFormat::Json => ExportFormat::Csv,
+        format => format,
+    };
+    let extension = match format {
+        ExportFormat::Ndjson => "ndjson",
+        _ => "csv",
+    };
+
+    let (tx, rx) = mpsc::channel::<Result<String, sqlx::Error>>(CHUNK_BUFFER);
+
+    tokio::spawn(async move {
+        let mut rows = sqlx::query_as::<_, PriceExportRow>(
+            r#"
+            SELECT * FROM (
+                SELECT
+                    op.id,
+                    op.provider_id,
+                    p.name AS provider_name,
+                    ARRAY(
+                        SELECT z.name
+                        FROM provider_delivery_zones pdz
+                        JOIN delivery_zones z ON pdz.zone_id = z.id
+                        WHERE pdz.provider_id = op.provider_id
+                        ORDER BY z.name
+                    )::TEXT[] AS zones,
+                    op.product_id,
+                    pr.slug AS product,
+                    CASE
+                        WHEN $8 THEN ROUND(op.price * (1 + vat_rate_at(op.created_at)), 4)
+                        ELSE op.price
+                    END AS price,
+                    op.currency,
+                    op.unit,
+                    $8 AS includes_vat,
+                    op.raw_price,
+                    op.created_at
+                FROM
+                    oil_prices op
+                JOIN
+                    providers p ON op.provider_id = p.id
+                JOIN
+                    products pr ON op.product_id = pr.id
+                WHERE
+                    op.deleted_at IS NULL
+                    AND ($1::INT IS NULL OR op.provider_id = $1)
+                    AND ($2::INT IS NULL OR EXISTS (
+                        SELECT 1 FROM provider_delivery_zones pdz
+                        WHERE pdz.provider_id = op.provider_id AND pdz.zone_id = $2
+                    ))
+                    AND ($3::TEXT IS NULL OR pr.slug = $3)
+                    AND ($4::TIMESTAMP IS NULL OR op.created_at > $4)
+                    AND ($5::TIMESTAMP IS NULL OR op.created_at < $5)
+            ) AS export
+            WHERE
+                ($6::NUMERIC IS NULL OR export.price >= $6)
+                AND ($7::NUMERIC IS NULL OR export.price <= $7)
+            ORDER BY
+                export.created_at, export.id
+            "#,
+        )
+        .bind(params.provider_id)
+        .bind(params.zone_id)
+        .bind(&params.product)
+        .bind(params.start)
+        .bind(params.end)
+        .bind(params.min_price)
+        .bind(params.max_price)
+        .bind(params.vat.includes_vat())
+        .fetch(&state.db);
+
+        let mut chunk = format.preamble::<PriceExportRow>();
+        while let Some(row) = rows.next().await {
+            match row {
+                Ok(row) => chunk.push_str(&format.line(&row)),
+                Err(e) => {
+                    tracing::error!("Error while exporting prices: {}", e);
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            }
+
+            if chunk.len() >= CHUNK_SIZE && tx.send(Ok(std::mem::take(&mut chunk))).await.is_err() {
+                // The client went away
+                return;
+            }
+        }
+
+        if !chunk.is_empty() {
+            let _ = tx.send(Ok(chunk)).await;
+        }
+    });
+
+    (
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"prices.{}\"", extension),
+            ),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
@@ -0,0 +1,366 @@
+use crate::app_state::AppState;
+use crate::errors::{ErrorBody, FeedsError};
+use crate::feeds::{AtomEntry, AtomFeed, ATOM_CONTENT_TYPE};
+use crate::models::feeds::{
+    FeedParams, FeedPriceChange, DEFAULT_FEED_ENTRIES, FEED_WINDOW_DAYS, MAX_FEED_ENTRIES,
+};
+use axum::extract::{OriginalUri, Path, Query, State};
+use axum::http::header::{CONTENT_TYPE, HOST};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use rust_decimal::Decimal;
+
+/// The header a reverse proxy passes the scheme of the original request in.
+const FORWARDED_PROTO: &str = "x-forwarded-proto";
+
+/// Fetches an Atom feed of the price changes of all providers.
+///
+/// See `price_feed` for the contents of the feed.
+#[utoipa::path(
+    get,
+    path = "/feeds/prices.atom",
+    tag = "feeds",
+    params(FeedParams),
+    responses(
+        (status = 200, description = "The feed", body = String, content_type = "application/atom+xml"),
+        (status = 400, description = "The limit is invalid", body = ErrorBody),
+        (status = 500, description = "The price changes could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_price_feed(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(params): Query<FeedParams>,
+) -> Result<Response, FeedsError> {
+    let self_link = self_link(&headers, &uri);
+    price_feed(
+        &state,
+        None,
+        None,
+        "feeds/prices".to_string(),
+        "Prisændringer".to_string(),
+        self_link,
+        &params,
+    )
+    .await
+}
+
+/// Fetches an Atom feed of the price changes of a provider.
+///
+/// See `price_feed` for the contents of the feed.
+#[utoipa::path(
+    get,
+    path = "/feeds/providers/{id}/prices.atom",
+    tag = "feeds",
+    params(("id" = i32, Path, description = "The ID of the provider"), FeedParams),
+    responses(
+        (status = 200, description = "The feed", body = String, content_type = "application/atom+xml"),
+        (status = 400, description = "The limit is invalid", body = ErrorBody),
+        (status = 404, description = "The provider does not exist", body = ErrorBody),
+        (status = 500, description = "The price changes could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_provider_price_feed(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(params): Query<FeedParams>,
+) -> Result<Response, FeedsError> {
+    let name: String = sqlx::query_scalar("SELECT name FROM providers WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(FeedsError::fetch_error)?
+        .ok_or_else(FeedsError::not_found)?;
+
+    price_feed(
+        &state,
+        Some(id),
+        None,
+        format!("feeds/providers/{}/prices", id),
+        format!("Prisændringer hos {}", name),
+        self_link(&headers, &uri),
+        &params,
+    )
+    .await
+}
+
+/// Fetches an Atom feed of the price changes of the providers delivering to a zone.
+///
+/// See `price_feed` for the contents of the feed.
+#[utoipa::path(
+    get,
+    path = "/feeds/zones/{id}/prices.atom",
+    tag = "feeds",
+    params(("id" = i32, Path, description = "The ID of the delivery zone"), FeedParams),
+    responses(
+        (status = 200, description = "The feed", body = String, content_type = "application/atom+xml"),
+        (status = 400, description = "The limit is invalid", body = ErrorBody),
+        (status = 404, description = "The delivery zone does not exist", body = ErrorBody),
+        (status = 500, description = "The price changes could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_zone_price_feed(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(params): Query<FeedParams>,
+) -> Result<Response, FeedsError> {
+    let name: String = sqlx::query_scalar("SELECT name FROM delivery_zones WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(FeedsError::fetch_error)?
+        .ok_or_else(FeedsError::not_found)?;
+
+    price_feed(
+        &state,
+        None,
+        Some(id),
+        format!("feeds/zones/{}/prices", id),
+        format!("Prisændringer i {}", name),
+        self_link(&headers, &uri),
+        &params,
+    )
+    .await
+}
+
+/// Builds an Atom feed with an entry for every time a provider's price changed within the last
+/// year, newest first.
+///
+/// Prices are compared rounded to øre, as they are shown, and a provider's first price counts
+/// as a change too. Deleted prices are left out. An entry is identified by the price the change
+/// introduced, and counts as updated when that price was last corrected. Prices include VAT
+/// unless asked otherwise. The ID of the feed is made from its path, product and VAT basis.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `provider_id` - The provider to limit the feed to, if any.
+/// * `zone_id` - The delivery zone to limit the feed to the providers of, if any.
+/// * `path` - The path the ID of the feed is made from.
+/// * `title` - The title of the feed.
+/// * `self_link` - The URL the feed is served from.
+/// * `params` - The query parameters choosing the product, the number of entries and whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Response, FeedsError>` - The result of the operation, either the feed or an error.
+async fn price_feed(
+    state: &AppState,
+    provider_id: Option<i32>,
+    zone_id: Option<i32>,
+    path: String,
+    title: String,
+    self_link: String,
+    params: &FeedParams,
+) -> Result<Response, FeedsError> {
+    let limit = params.limit.unwrap_or(DEFAULT_FEED_ENTRIES);
+    if !(1..=MAX_FEED_ENTRIES).contains(&limit) {
+        return Err(FeedsError::invalid_input(format!(
+            "limit must be between 1 and {}",
+            MAX_FEED_ENTRIES
+        )));
+    }
+
+    // Each provider's last price before the window is included, so the first change in the
+    // window is compared with the price it replaced rather than counted as a first price.
+    let changes = sqlx::query_as::<_, FeedPriceChange>(
+        r#"
+        WITH pairs AS (
+            SELECT
+                p.id AS provider_id, pr.id AS product_id
+            FROM
+                providers p
+            CROSS JOIN
+                products pr
+            WHERE
+                ($1::INT IS NULL OR p.id = $1)
+                AND ($2::INT IS NULL OR p.id IN (
+                    SELECT provider_id FROM provider_delivery_zones WHERE zone_id = $2
+                ))
+                AND ($3::TEXT IS NULL OR pr.slug = $3)
+        ),
+        scoped AS (
+            SELECT
+                op.id, op.provider_id, op.product_id, op.price, op.valid_from
+            FROM
+                oil_prices op
+            JOIN
+                pairs ON op.provider_id = pairs.provider_id AND op.product_id = pairs.product_id
+            WHERE
+                op.deleted_at IS NULL
+                AND op.valid_from >= LOCALTIMESTAMP - MAKE_INTERVAL(days => $6)
+            UNION ALL
+            SELECT
+                before.*
+            FROM
+                pairs
+            CROSS JOIN LATERAL (
+                SELECT
+                    op.id, op.provider_id, op.product_id, op.price, op.valid_from
+                FROM
+                    oil_prices op
+                WHERE
+                    op.provider_id = pairs.provider_id
+                    AND op.product_id = pairs.product_id
+                    AND op.deleted_at IS NULL
+                    AND op.valid_from < LOCALTIMESTAMP - MAKE_INTERVAL(days => $6)
+                ORDER BY
+                    op.valid_from DESC, op.id DESC
+                LIMIT 1
+            ) before
+        ),
+        shown AS (
+            SELECT
+                s.*,
+                ROUND(
+                    CASE WHEN $5 THEN s.price * (1 + vat_rate_at(s.valid_from)) ELSE s.price END,
+                    2
+                ) AS shown_price
+            FROM
+                scoped s
+        ),
+        changes AS (
+            SELECT
+                shown.*,
+                LAG(shown.shown_price) OVER (
+                    PARTITION BY shown.provider_id, shown.product_id
+                    ORDER BY shown.valid_from, shown.id
+                ) AS previous_price
+            FROM
+                shown
+        )
+        SELECT
+            c.id,
+            p.name AS provider_name,
+            p.url AS provider_url,
+            pr.name AS product_name,
+            c.shown_price AS price,
+            c.previous_price,
+            c.valid_from,
+            (SELECT MAX(r.revised_at) FROM oil_price_revisions r WHERE r.price_id = c.id)
+                AS corrected_at
+        FROM
+            changes c
+        JOIN
+            providers p ON c.provider_id = p.id
+        JOIN
+            products pr ON c.product_id = pr.id
+        WHERE
+            c.valid_from >= LOCALTIMESTAMP - MAKE_INTERVAL(days => $6)
+            AND c.previous_price IS DISTINCT FROM c.shown_price
+        ORDER BY
+            c.valid_from DESC, c.id DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(provider_id)
+    .bind(zone_id)
+    .bind(&params.product)
+    .bind(limit)
+    .bind(params.vat.includes_vat())
+    .bind(FEED_WINDOW_DAYS)
+    .fetch_all(&state.db)
+    .await
+    .map_err(FeedsError::fetch_error)?;
+
+    let unit = if params.vat.includes_vat() {
+        "kr. pr. liter inkl. moms"
+    } else {
+        "kr. pr. liter ekskl. moms"
+    };
+    let entries = changes
+        .into_iter()
+        .map(|change| {
+            let (title, summary) = match change.previous_price {
+                Some(previous) => (
+                    format!(
+                        "{}: {} {} ({}{})",
+                        change.provider_name,
+                        change.product_name,
+                        format_price(change.price),
+                        if change.price > previous { "+" } else { "-" },
+                        format_price((change.price - previous).abs())
+                    ),
+                    format!(
+                        "Prisen på {} hos {} er ændret fra {} til {} {}.",
+                        change.product_name.to_lowercase(),
+                        change.provider_name,
+                        format_price(previous),
+                        format_price(change.price),
+                        unit
+                    ),
+                ),
+                None => (
+                    format!(
+                        "{}: {} {}",
+                        change.provider_name,
+                        change.product_name,
+                        format_price(change.price)
+                    ),
+                    format!(
+                        "{} har fået en pris på {}: {} {}.",
+                        change.provider_name,
+                        change.product_name.to_lowercase(),
+                        format_price(change.price),
+                        unit
+                    ),
+                ),
+            };
+
+            AtomEntry {
+                id: format!("price/{}", change.id),
+                title,
+                link: Some(change.provider_url),
+                author: change.provider_name,
+                published: change.valid_from,
+                updated: change
+                    .corrected_at
+                    .map_or(change.valid_from, |at| at.max(change.valid_from)),
+                summary,
+            }
+        })
+        .collect();
+
+    let mut id = path;
+    if let Some(product) = &params.product {
+        id.push_str(&format!("/{}", product));
+    }
+    id.push_str(if params.vat.includes_vat() {
+        "/incl"
+    } else {
+        "/excl"
+    });
+
+    let feed = AtomFeed {
+        id,
+        title,
+        self_link,
+        entries,
+    };
+
+    Ok(([(CONTENT_TYPE, ATOM_CONTENT_TYPE)], feed.render()).into_response())
+}
+
+/// Rebuilds the absolute URL a request was made to, from its `Host` header and the scheme a
+/// reverse proxy passed on. HTTPS is assumed if no scheme was passed on.
+fn self_link(headers: &HeaderMap, uri: &axum::http::Uri) -> String {
+    let host = headers
+        .get(HOST)
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or("localhost");
+    let scheme = headers
+        .get(FORWARDED_PROTO)
+        .and_then(|scheme| scheme.to_str().ok())
+        .unwrap_or("https");
+    format!("{}://{}{}", scheme, host, uri)
+}
+
+/// Formats a price with two decimals and a decimal comma.
+fn format_price(price: Decimal) -> String {
+    format!("{:.2}", price).replace('.', ",")
+}
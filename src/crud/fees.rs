@@ -0,0 +1,168 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::errors::{ErrorBody, FeesError, FeesSuccess, MessageBody};
+use crate::helpers::is_foreign_key_violation;
+use crate::models::fees::{ProviderFees, ZoneFee};
+use axum::extract::{Path, State};
+use axum::Json;
+use std::collections::HashSet;
+
+/// Fetches the delivery fee rules of a provider from the database.
+///
+/// Providers without any rules get an empty rule set, meaning delivery is free and there is no
+/// minimum order.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the provider.
+///
+/// # Returns
+///
+/// * `Result<Json<ProviderFees>, FeesError>` - The result of the operation, either the fee rules or an error.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/fees",
+    tag = "fees",
+    params(("id" = i32, Path, description = "The ID of the provider")),
+    responses(
+        (status = 200, description = "The delivery fee rules of the provider", body = ProviderFees),
+        (status = 500, description = "The fee rules could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_provider_fees(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<ProviderFees>, FeesError> {
+    let mut fees = sqlx::query_as::<_, ProviderFees>(
+        r#"
+        SELECT delivery_fee, free_above_liters, minimum_order_liters
+        FROM provider_fees
+        WHERE provider_id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(FeesError::fetch_error)?
+    .unwrap_or_default();
+
+    fees.zone_fees = sqlx::query_as::<_, ZoneFee>(
+        "SELECT zone_id, delivery_fee FROM provider_zone_fees WHERE provider_id = $1 ORDER BY zone_id",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(FeesError::fetch_error)?;
+
+    Ok(Json(fees))
+}
+
+/// Replaces the delivery fee rules of a provider in the database.
+///
+/// An unknown provider is answered with `404 Not Found`, and an unknown zone or a zone listed more
+/// than once with `400 Bad Request`.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the provider.
+/// * `json` - The JSON payload containing the complete set of fee rules.
+///
+/// # Returns
+///
+/// * `Result<FeesSuccess, FeesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    put,
+    path = "/providers/{id}/fees",
+    tag = "fees",
+    params(("id" = i32, Path, description = "The ID of the provider")),
+    request_body = ProviderFees,
+    responses(
+        (status = 200, description = "The fee rules were replaced", body = MessageBody),
+        (status = 400, description = "A fee is negative, or a zone is unknown or listed more than once", body = ErrorBody),
+        (status = 404, description = "The provider does not exist", body = ErrorBody),
+        (status = 500, description = "The fee rules could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn update_provider_fees(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(json): Json<ProviderFees>,
+) -> Result<FeesSuccess, FeesError> {
+    let negative_fee = json.delivery_fee.is_some_and(|fee| fee.is_sign_negative())
+        || json
+            .zone_fees
+            .iter()
+            .any(|zone| zone.delivery_fee.is_sign_negative());
+    if negative_fee {
+        return Err(FeesError::invalid_input("delivery fees cannot be negative"));
+    }
+    let mut zone_ids = HashSet::new();
+    if !json
+        .zone_fees
+        .iter()
+        .all(|zone| zone_ids.insert(zone.zone_id))
+    {
+        return Err(FeesError::invalid_input(
+            "each zone may only be listed once",
+        ));
+    }
+
+    let mut tx = state.db.begin().await.map_err(FeesError::update_error)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO provider_fees (provider_id, delivery_fee, free_above_liters, minimum_order_liters)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (provider_id) DO UPDATE SET
+            delivery_fee = EXCLUDED.delivery_fee,
+            free_above_liters = EXCLUDED.free_above_liters,
+            minimum_order_liters = EXCLUDED.minimum_order_liters
+        "#,
+    )
+    .bind(id)
+    .bind(json.delivery_fee)
+    .bind(json.free_above_liters)
+    .bind(json.minimum_order_liters)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        if is_foreign_key_violation(&e) {
+            FeesError::unknown("provider")
+        } else {
+            FeesError::update_error(e)
+        }
+    })?;
+
+    sqlx::query("DELETE FROM provider_zone_fees WHERE provider_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(FeesError::update_error)?;
+
+    for zone in &json.zone_fees {
+        sqlx::query(
+            "INSERT INTO provider_zone_fees (provider_id, zone_id, delivery_fee) VALUES ($1, $2, $3)",
+        )
+        .bind(id)
+        .bind(zone.zone_id)
+        .bind(zone.delivery_fee)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            if is_foreign_key_violation(&e) {
+                FeesError::invalid_input(format!("unknown zone {}", zone.zone_id))
+            } else {
+                FeesError::update_error(e)
+            }
+        })?;
+    }
+
+    tx.commit().await.map_err(FeesError::update_error)?;
+
+    Ok(FeesSuccess::updated(id))
+}
@@ -0,0 +1,308 @@
+use crate::app_state::AppState;
+use crate::errors::{ErrorBody, ForecastError};
+use crate::forecast::{Backtest, Model, MIN_OBSERVATIONS};
+use crate::helpers::fetch_product_by_slug;
+use crate::models::forecast::{
+    Forecast, ForecastBacktest, ForecastModel, ForecastParams, ForecastPoint, CONFIDENCE_LEVEL,
+    CONFIDENCE_Z, DEFAULT_FORECAST_DAYS, DEFAULT_HISTORY_DAYS, MAX_FORECAST_DAYS, MAX_HISTORY_DAYS,
+};
+use crate::models::providers::STALE_AFTER_HOURS;
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Forecasts a provider's price of a product for the coming days.
+///
+/// The model is fitted to the price the provider had in effect at the end of each day of the
+/// history. The number of days defaults to 14, the history to the last 180 days and the product
+/// to the default product. An unknown provider is answered with `404 Not Found`. A provider whose
+/// price was not seen within the last 48 hours is not forecast, as its old price would be fitted
+/// as if it were still current.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the provider.
+/// * `params` - The query parameters choosing the product, the number of days, the history and whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Json<Forecast>, ForecastError>` - The result of the operation, either the forecast or an error.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/forecast",
+    tag = "forecasts",
+    params(("id" = i32, Path, description = "The ID of the provider"), ForecastParams),
+    responses(
+        (status = 200, description = "The forecast", body = Forecast),
+        (status = 400, description = "The parameters are invalid, the history is too short or the provider's price is stale", body = ErrorBody),
+        (status = 404, description = "The provider does not exist", body = ErrorBody),
+        (status = 500, description = "The history could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_provider_forecast(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<ForecastParams>,
+) -> Result<Json<Forecast>, ForecastError> {
+    let (days, history_days) = validate(&params)?;
+
+    let provider_exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM providers WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(ForecastError::fetch_error)?;
+    if !provider_exists {
+        return Err(ForecastError::unknown("provider"));
+    }
+
+    let product_id = fetch_product_id(&state, &params).await?;
+
+    let last_seen_at: Option<NaiveDateTime> = sqlx::query_scalar(
+        r#"
+        SELECT MAX(last_seen_at)
+        FROM oil_prices
+        WHERE provider_id = $1 AND product_id = $2 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(id)
+    .bind(product_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(ForecastError::fetch_error)?;
+    let stale_before = Utc::now().naive_utc() - Duration::hours(STALE_AFTER_HOURS);
+    if last_seen_at.is_none_or(|last_seen_at| last_seen_at < stale_before) {
+        return Err(ForecastError::invalid_input(format!(
+            "the provider's price was not seen within the last {} hours",
+            STALE_AFTER_HOURS
+        )));
+    }
+
+    let series = sqlx::query_as::<_, (NaiveDate, Option<f64>)>(
+        r#"
+        SELECT
+            d.day::DATE,
+            CAST(
+                CASE
+                    WHEN $4 THEN p.price * (1 + vat_rate_at(d.day))
+                    ELSE p.price
+                END AS DOUBLE PRECISION
+            )
+        FROM
+            generate_series(
+                CAST(CURRENT_DATE - $3 AS TIMESTAMP), CURRENT_DATE::TIMESTAMP, INTERVAL '1 day'
+            ) AS d(day)
+        LEFT JOIN LATERAL (
+            SELECT op.price
+            FROM oil_prices op
+            WHERE op.provider_id = $1
+              AND op.product_id = $2
+              AND op.valid_from < d.day + INTERVAL '1 day'
+              AND op.deleted_at IS NULL
+            ORDER BY op.valid_from DESC, op.id DESC
+            LIMIT 1
+        ) p ON TRUE
+        ORDER BY
+            d.day
+        "#,
+    )
+    .bind(id)
+    .bind(product_id)
+    .bind(history_days)
+    .bind(params.vat.includes_vat())
+    .fetch_all(&state.db)
+    .await
+    .map_err(ForecastError::fetch_error)?;
+
+    build_forecast(
+        series,
+        days,
+        product_id,
+        Some(id),
+        params.vat.includes_vat(),
+    )
+    .map(Json)
+}
+
+/// Forecasts the market price of a product for the coming days.
+///
+/// The model is fitted to the median of the market index. Days the index was not computed for
+/// carry the median of the day before. The number of days defaults to 14, the history to the
+/// last 180 days and the product to the default product.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `params` - The query parameters choosing the product, the number of days, the history and whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Json<Forecast>, ForecastError>` - The result of the operation, either the forecast or an error.
+#[utoipa::path(
+    get,
+    path = "/index/forecast",
+    tag = "forecasts",
+    params(ForecastParams),
+    responses(
+        (status = 200, description = "The forecast", body = Forecast),
+        (status = 400, description = "The parameters are invalid or the history is too short", body = ErrorBody),
+        (status = 500, description = "The history could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_market_forecast(
+    State(state): State<AppState>,
+    Query(params): Query<ForecastParams>,
+) -> Result<Json<Forecast>, ForecastError> {
+    let (days, history_days) = validate(&params)?;
+    let product_id = fetch_product_id(&state, &params).await?;
+
+    let series = sqlx::query_as::<_, (NaiveDate, Option<f64>)>(
+        r#"
+        SELECT
+            d.day::DATE,
+            CAST(
+                CASE
+                    WHEN $3 THEN m.median * (1 + vat_rate_at(d.day))
+                    ELSE m.median
+                END AS DOUBLE PRECISION
+            )
+        FROM
+            generate_series(
+                CAST(CURRENT_DATE - $2 AS TIMESTAMP), CURRENT_DATE::TIMESTAMP, INTERVAL '1 day'
+            ) AS d(day)
+        LEFT JOIN
+            market_index m ON m.product_id = $1 AND m.day = d.day
+        ORDER BY
+            d.day
+        "#,
+    )
+    .bind(product_id)
+    .bind(history_days)
+    .bind(params.vat.includes_vat())
+    .fetch_all(&state.db)
+    .await
+    .map_err(ForecastError::fetch_error)?;
+
+    build_forecast(series, days, product_id, None, params.vat.includes_vat()).map(Json)
+}
+
+/// Checks the number of days to forecast and of history, falling back to the defaults.
+fn validate(params: &ForecastParams) -> Result<(usize, i32), ForecastError> {
+    let days = params.days.unwrap_or(DEFAULT_FORECAST_DAYS);
+    if days == 0 || days > MAX_FORECAST_DAYS {
+        return Err(ForecastError::invalid_input(format!(
+            "days must be between 1 and {}",
+            MAX_FORECAST_DAYS
+        )));
+    }
+
+    let history_days = params.history_days.unwrap_or(DEFAULT_HISTORY_DAYS);
+    if history_days < MIN_OBSERVATIONS as i32 || history_days > MAX_HISTORY_DAYS {
+        return Err(ForecastError::invalid_input(format!(
+            "history_days must be between {} and {}",
+            MIN_OBSERVATIONS, MAX_HISTORY_DAYS
+        )));
+    }
+
+    Ok((days, history_days))
+}
+
+/// Looks up the ID of the product to forecast, falling back to the default product.
+async fn fetch_product_id(state: &AppState, params: &ForecastParams) -> Result<i32, ForecastError> {
+    fetch_product_by_slug(&state.db, params.product.as_deref())
+        .await
+        .map_err(ForecastError::fetch_error)?
+        .map(|(id, _)| id)
+        .ok_or_else(|| ForecastError::invalid_input("unknown product"))
+}
+
+/// Fits a model to a daily series and forecasts the days after it.
+///
+/// Days before the first known value are left out, and days without a value after it carry the
+/// value of the day before. The forecast is backtested over as many days as it covers.
+///
+/// # Arguments
+///
+/// * `series` - The value of every day of the history, oldest first.
+/// * `days` - The number of days to forecast.
+/// * `product_id` - The ID of the product.
+/// * `provider_id` - The ID of the provider, or `None` for the market.
+/// * `includes_vat` - Whether the values include VAT.
+///
+/// # Returns
+///
+/// * `Result<Forecast, ForecastError>` - The forecast, or an error if the history is too short.
+fn build_forecast(
+    series: Vec<(NaiveDate, Option<f64>)>,
+    days: usize,
+    product_id: i32,
+    provider_id: Option<i32>,
+    includes_vat: bool,
+) -> Result<Forecast, ForecastError> {
+    let mut values = Vec::with_capacity(series.len());
+    let mut last_date = None;
+    for (date, value) in series {
+        if let Some(value) = value.or(values.last().copied()) {
+            values.push(value);
+            last_date = Some(date);
+        }
+    }
+
+    let not_enough_history = || {
+        ForecastError::invalid_input(format!(
+            "at least {} days of price history are needed to forecast",
+            MIN_OBSERVATIONS
+        ))
+    };
+    let last_date = last_date.ok_or_else(not_enough_history)?;
+    let model = Model::fit(&values).ok_or_else(not_enough_history)?;
+    let backtest = Backtest::run(&values, days);
+
+    let points = (1..=days)
+        .map(|horizon| {
+            let price = model.forecast(horizon);
+            let margin = CONFIDENCE_Z * model.standard_error(horizon);
+            ForecastPoint {
+                date: last_date + chrono::Duration::days(horizon as i64),
+                price: to_decimal(price),
+                lower: to_decimal((price - margin).max(0.0)),
+                upper: to_decimal(price + margin),
+            }
+        })
+        .collect();
+
+    Ok(Forecast {
+        product_id,
+        provider_id,
+        includes_vat,
+        last_date,
+        last_price: to_decimal(values[values.len() - 1]),
+        confidence: CONFIDENCE_LEVEL,
+        model: ForecastModel {
+            method: "damped_holt",
+            alpha: model.alpha,
+            beta: model.beta,
+            phi: model.phi,
+            level: to_decimal(model.level),
+            trend: to_decimal(model.trend),
+            residual_std_dev: to_decimal(model.sigma),
+            observations: model.observations,
+        },
+        backtest: backtest.map(|backtest| ForecastBacktest {
+            days: backtest.days,
+            mean_absolute_error: to_decimal(backtest.mean_absolute_error),
+            mean_absolute_percentage_error: to_decimal(backtest.mean_absolute_percentage_error)
+                .round_dp(2),
+        }),
+        points,
+    })
+}
+
+/// Converts a computed value to a price with four decimals.
+fn to_decimal(value: f64) -> Decimal {
+    Decimal::from_f64_retain(value)
+        .unwrap_or_default()
+        .round_dp(4)
+}
@@ -0,0 +1,417 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::crud::prices::{fetch_price_basis, insert_price};
+use crate::crud::quarantine::{check_plausibility, quarantine_price};
+use crate::errors::{ErrorBody, PricesError};
+use crate::helpers::is_unique_violation;
+use crate::models::imports::{PriceImportParams, PriceImportReport};
+use axum::extract::{Query, State};
+use axum::Json;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sqlx::{Connection, PgConnection};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The timestamp formats tried when no format is given.
+const TIMESTAMP_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%d-%m-%Y %H:%M:%S",
+    "%d-%m-%Y %H:%M",
+    "%d.%m.%Y %H:%M",
+];
+
+/// The date formats tried when no format is given. Dates are imported at midnight.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d-%m-%Y", "%d.%m.%Y", "%d/%m/%Y"];
+
+/// What happened to a valid row of an import.
+enum RowOutcome {
+    Imported,
+    /// A price of the provider and product at the same time was already recorded.
+    Duplicate,
+    /// The price failed the plausibility checks and was held for review.
+    Quarantined,
+}
+
+/// Parses a timestamp, either with the given format or by trying the common formats.
+///
+/// # Arguments
+///
+/// * `value` - The value to parse.
+/// * `format` - The `chrono` format of the value, if known.
+///
+/// # Returns
+///
+/// * `Option<NaiveDateTime>` - The timestamp, or `None` if the value could not be parsed.
+fn parse_timestamp(value: &str, format: Option<&str>) -> Option<NaiveDateTime> {
+    if let Some(format) = format {
+        return NaiveDateTime::parse_from_str(value, format)
+            .ok()
+            .or_else(|| {
+                NaiveDate::parse_from_str(value, format)
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+            });
+    }
+
+    TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// Parses a price, optionally written with a decimal comma and thousands separators.
+///
+/// The thousands separator is whichever of `.` and `,` is not the decimal separator, and must
+/// separate groups of three digits, so `8,95` is rejected rather than read as `895` when
+/// decimal points are expected.
+///
+/// # Arguments
+///
+/// * `value` - The value to parse.
+/// * `decimal_comma` - Whether the value uses a decimal comma.
+///
+/// # Returns
+///
+/// * `Option<Decimal>` - The price, or `None` if the value could not be parsed.
+fn parse_price(value: &str, decimal_comma: bool) -> Option<Decimal> {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let (decimal, thousands) = if decimal_comma {
+        (',', '.')
+    } else {
+        ('.', ',')
+    };
+
+    let (integer, fraction) = match value.split_once(decimal) {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (value.as_str(), None),
+    };
+    if fraction.is_some_and(|fraction| fraction.contains([decimal, thousands])) {
+        return None;
+    }
+
+    let groups: Vec<&str> = integer.split(thousands).collect();
+    if groups.len() > 1
+        && (groups[0].is_empty()
+            || groups[0].len() > 3
+            || groups[1..].iter().any(|group| group.len() != 3))
+    {
+        return None;
+    }
+
+    let integer = groups.concat();
+    let value = match fraction {
+        Some(fraction) => format!("{}.{}", integer, fraction),
+        None => integer,
+    };
+    Decimal::from_str(&value).ok()
+}
+
+/// Imports a single row of historical prices.
+///
+/// Rows already imported, meaning a price of the same provider and product at the same time
+/// exists and is not deleted, are skipped so files can be imported again safely. Imported prices
+/// are marked as such under a savepoint, so a row another import stores concurrently is caught by
+/// the unique index on imported prices and skipped without failing the import. Prices failing the plausibility checks
+/// of their product, compared with the prices in effect before them, are held for review.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, within the import's transaction.
+/// * `params` - How the file is read.
+/// * `provider_id` - The ID of the provider.
+/// * `product` - The slug of the product, if given.
+/// * `price` - The price as published.
+/// * `timestamp` - The time the price was published.
+///
+/// # Returns
+///
+/// * `Result<Result<RowOutcome, String>, sqlx::Error>` - What happened to the row, the reason it was rejected, or a database error.
+async fn import_row(
+    conn: &mut PgConnection,
+    params: &PriceImportParams,
+    provider_id: i32,
+    product: Option<&str>,
+    price: Decimal,
+    timestamp: NaiveDateTime,
+) -> Result<Result<RowOutcome, String>, sqlx::Error> {
+    let Some(mut basis) =
+        fetch_price_basis(conn, provider_id, None, product, Some(timestamp)).await?
+    else {
+        return Ok(Err(format!(
+            "unknown product '{}'",
+            product.unwrap_or_default()
+        )));
+    };
+    if let Some(includes_vat) = params.includes_vat {
+        basis.includes_vat = includes_vat;
+    }
+    if let Some(per_liters) = params.per_liters {
+        basis.per_liters = per_liters;
+    }
+
+    let duplicate = sqlx::query_as::<_, (i32,)>(
+        r#"
+        SELECT id FROM oil_prices
+        WHERE provider_id = $1 AND product_id = $2 AND created_at = $3 AND deleted_at IS NULL
+        LIMIT 1
+        "#,
+    )
+    .bind(provider_id)
+    .bind(basis.product_id)
+    .bind(timestamp)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if duplicate.is_some() {
+        return Ok(Ok(RowOutcome::Duplicate));
+    }
+
+    let reasons = check_plausibility(
+        conn,
+        provider_id,
+        basis.product_id,
+        basis.normalise(price),
+        Some(timestamp),
+    )
+    .await?;
+    if !reasons.is_empty() {
+        quarantine_price(
+            conn,
+            provider_id,
+            &basis,
+            price,
+            &[],
+            Some(timestamp),
+            &reasons,
+        )
+        .await?;
+        return Ok(Ok(RowOutcome::Quarantined));
+    }
+
+    let mut savepoint = conn.begin().await?;
+    let stored = match insert_price(
+        &mut savepoint,
+        provider_id,
+        &basis,
+        price,
+        &[],
+        Some(timestamp),
+        None,
+    )
+    .await
+    {
+        Ok(price_id) => {
+            sqlx::query("UPDATE oil_prices SET imported = TRUE WHERE id = $1")
+                .bind(price_id)
+                .execute(&mut *savepoint)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match stored {
+        Ok(_) => {
+            savepoint.commit().await?;
+            Ok(Ok(RowOutcome::Imported))
+        }
+        Err(e) if is_unique_violation(&e) => {
+            savepoint.rollback().await?;
+            Ok(Ok(RowOutcome::Duplicate))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Imports historical prices from a CSV file.
+///
+/// The columns holding the provider name, price, timestamp and optionally the product slug are
+/// configurable, as are the delimiter, the timestamp format and whether prices use a decimal
+/// comma. Provider names are matched case-insensitively. Prices keep their original timestamps
+/// and are normalised on the basis in effect at the time. Invalid rows are skipped and listed in
+/// the report, rows imported before are skipped as duplicates, and rows failing the plausibility
+/// checks are held for review. A dry run validates the file and reports what would be imported
+/// without storing anything. Imported prices do not trigger webhooks, live updates or price
+/// alerts. Files of up to 64 MiB are accepted.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `params` - The query parameters describing how the file is read.
+/// * `body` - The CSV file.
+///
+/// # Returns
+///
+/// * `Result<Json<PriceImportReport>, PricesError>` - The result of the operation, either the import report or an error.
+#[utoipa::path(
+    post,
+    path = "/imports/prices",
+    tag = "imports",
+    params(PriceImportParams),
+    request_body(content = String, content_type = "text/csv"),
+    responses(
+        (status = 200, description = "What was, or in a dry run would be, imported", body = PriceImportReport),
+        (status = 400, description = "The file or the parameters are invalid", body = ErrorBody),
+        (status = 500, description = "The prices could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn import_prices(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Query(params): Query<PriceImportParams>,
+    body: String,
+) -> Result<Json<PriceImportReport>, PricesError> {
+    if params.per_liters.is_some_and(|per_liters| per_liters <= 0) {
+        return Err(PricesError::invalid_input(
+            "per_liters must be greater than zero",
+        ));
+    }
+
+    let delimiter = params.delimiter.unwrap_or(',');
+    if !delimiter.is_ascii() {
+        return Err(PricesError::invalid_input(
+            "delimiter must be an ASCII character",
+        ));
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(body.trim_start_matches('\u{feff}').as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| PricesError::invalid_input(format!("invalid CSV header: {}", e)))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header == name)
+            .ok_or_else(|| PricesError::invalid_input(format!("missing column '{}'", name)))
+    };
+    let provider_column = column(&params.provider_column)?;
+    let price_column = column(&params.price_column)?;
+    let timestamp_column = column(&params.timestamp_column)?;
+    let product_column = params.product_column.as_deref().map(column).transpose()?;
+
+    let providers: HashMap<String, i32> =
+        sqlx::query_as::<_, (i32, String)>("SELECT id, name FROM providers")
+            .fetch_all(&state.db)
+            .await
+            .map_err(PricesError::fetch_error)?
+            .into_iter()
+            .map(|(id, name)| (name.to_lowercase(), id))
+            .collect();
+
+    let mut report = PriceImportReport {
+        dry_run: params.dry_run,
+        ..Default::default()
+    };
+    let mut tx = state.db.begin().await.map_err(PricesError::insert_error)?;
+
+    for record in reader.records() {
+        report.rows += 1;
+
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |position| position.line());
+                report.reject(line, format!("invalid CSV row: {}", e));
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |position| position.line());
+        let field = |index: usize| record.get(index).unwrap_or_default();
+
+        let Some(&provider_id) = providers.get(&field(provider_column).to_lowercase()) else {
+            report.reject(
+                line,
+                format!("unknown provider '{}'", field(provider_column)),
+            );
+            continue;
+        };
+        let Some(price) = parse_price(field(price_column), params.decimal_comma)
+            .filter(|price| *price > Decimal::ZERO)
+        else {
+            report.reject(line, format!("invalid price '{}'", field(price_column)));
+            continue;
+        };
+        let Some(timestamp) =
+            parse_timestamp(field(timestamp_column), params.timestamp_format.as_deref())
+        else {
+            report.reject(
+                line,
+                format!("invalid timestamp '{}'", field(timestamp_column)),
+            );
+            continue;
+        };
+        let product = product_column
+            .map(field)
+            .filter(|product| !product.is_empty())
+            .or(params.product.as_deref());
+
+        match import_row(&mut tx, &params, provider_id, product, price, timestamp)
+            .await
+            .map_err(PricesError::insert_error)?
+        {
+            Ok(RowOutcome::Imported) => report.imported += 1,
+            Ok(RowOutcome::Duplicate) => report.duplicates += 1,
+            Ok(RowOutcome::Quarantined) => report.quarantined += 1,
+            Err(error) => report.reject(line, error),
+        }
+    }
+
+    if params.dry_run {
+        tx.rollback().await.map_err(PricesError::insert_error)?;
+    } else {
+        tx.commit().await.map_err(PricesError::insert_error)?;
+    }
+
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_price;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn price(value: &str) -> Option<Decimal> {
+        Some(Decimal::from_str(value).unwrap())
+    }
+
+    #[test]
+    fn parses_decimal_points() {
+        assert_eq!(parse_price("8.95", false), price("8.95"));
+        assert_eq!(parse_price("12", false), price("12"));
+        assert_eq!(parse_price("1,234.50", false), price("1234.50"));
+        assert_eq!(parse_price(" 1,234,567.5 ", false), price("1234567.5"));
+    }
+
+    #[test]
+    fn parses_decimal_commas() {
+        assert_eq!(parse_price("8,95", true), price("8.95"));
+        assert_eq!(parse_price("1.234,50", true), price("1234.50"));
+        assert_eq!(parse_price("1 234,50", true), price("1234.50"));
+    }
+
+    #[test]
+    fn rejects_misplaced_separators() {
+        assert_eq!(parse_price("8,95", false), None);
+        assert_eq!(parse_price("8.95", true), None);
+        assert_eq!(parse_price("1,2345.6", false), None);
+        assert_eq!(parse_price(",234", false), None);
+        assert_eq!(parse_price("1.5.5", false), None);
+        assert_eq!(parse_price("1,5,5", true), None);
+        assert_eq!(parse_price("1.234,5", false), None);
+        assert_eq!(parse_price("", false), None);
+    }
+}
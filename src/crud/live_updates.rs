@@ -0,0 +1,134 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::{AuthError, Claims};
+use crate::errors::ErrorBody;
+use crate::models::live_events::{
+    ClientMessage, LiveEvent, LiveUpdatesParams, ServerMessage, Topic,
+};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::response::Response;
+use std::collections::HashMap;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Opens a WebSocket for subscribing to live updates.
+///
+/// Clients authenticate with the same JWT as the rest of the API, either as a bearer token or,
+/// for browsers that cannot set headers on WebSocket requests, as the `token` query parameter.
+/// Once connected they send `{"action": "subscribe", "topic": "..."}` or
+/// `{"action": "unsubscribe", "topic": "..."}` for the topics `prices`, `prices:<provider_id>`,
+/// `scraping_runs` and `provider_health`.
+///
+/// # Arguments
+///
+/// * `claims` - The JWT claims from the `Authorization` header, if present.
+/// * `state` - The application state containing the live event bus.
+/// * `params` - The query parameters containing the token, if not sent as a header.
+/// * `ws` - The WebSocket upgrade request.
+///
+/// # Returns
+///
+/// * `Result<Response, AuthError>` - The upgrade response or an authentication error.
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "live updates",
+    params(LiveUpdatesParams),
+    responses(
+        (status = 101, description = "The connection was upgraded to a WebSocket"),
+        (status = 400, description = "No valid token was given", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn live_updates(
+    claims: Option<Claims>,
+    State(state): State<AppState>,
+    Query(params): Query<LiveUpdatesParams>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, AuthError> {
+    if claims.is_none() {
+        let token = params.token.ok_or(AuthError::MissingCredentials)?;
+        Claims::from_token(&token)?;
+    }
+
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state)))
+}
+
+/// Sends a message to a WebSocket client.
+///
+/// # Arguments
+///
+/// * `socket` - The WebSocket.
+/// * `message` - The message to send.
+///
+/// # Returns
+///
+/// * `bool` - `false` if the client has disconnected.
+async fn send(socket: &mut WebSocket, message: &ServerMessage<'_>) -> bool {
+    match serde_json::to_string(message) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+/// Relays live events matching the client's subscriptions until the client disconnects.
+///
+/// # Arguments
+///
+/// * `socket` - The WebSocket.
+/// * `state` - The application state containing the live event bus.
+async fn handle_socket(mut socket: WebSocket, state: AppState) {
+    let mut events = state.events.subscribe();
+    let mut topics: HashMap<Topic, String> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                };
+
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Subscribe { topic }) => match Topic::parse(&topic) {
+                        Some(parsed) => {
+                            let topic = topics.entry(parsed).or_insert(topic);
+                            send(&mut socket, &ServerMessage::Subscribed { topic }).await
+                        }
+                        None => {
+                            let message = format!("Unknown topic: {}", topic);
+                            send(&mut socket, &ServerMessage::Error { message }).await
+                        }
+                    },
+                    Ok(ClientMessage::Unsubscribe { topic }) => {
+                        if let Some(parsed) = Topic::parse(&topic) {
+                            topics.remove(&parsed);
+                        }
+                        send(&mut socket, &ServerMessage::Unsubscribed { topic: &topic }).await
+                    }
+                    Err(e) => {
+                        let message = format!("Invalid message: {}", e);
+                        send(&mut socket, &ServerMessage::Error { message }).await
+                    }
+                };
+
+                if !reply {
+                    return;
+                }
+            }
+            event = events.recv() => {
+                let event: LiveEvent = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                };
+
+                if topics.keys().any(|topic| topic.matches(&event))
+                    && !send(&mut socket, &ServerMessage::Event { event: &event }).await
+                {
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::app_state::AppState;
+use crate::errors::{ErrorBody, MarketIndexError};
+use crate::helpers::fetch_product_by_slug;
+use crate::models::market_index::{MarketIndexParams, MarketIndexPoint, DEFAULT_INDEX_DAYS};
+use axum::extract::{Query, State};
+use axum::Json;
+
+/// Fetches the history of the market index of a product.
+///
+/// The index of a day aggregates the prices active providers had in effect at the end of it: the
+/// median, the mean without the cheapest and most expensive tenth, and the mean weighted by the
+/// number of delivery zones each provider covers. The period defaults to the last 30 days and the
+/// product to the default product.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `params` - The query parameters choosing the product, the period and whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<MarketIndexPoint>>, MarketIndexError>` - The result of the operation, either the index of every day in the period or an error.
+#[utoipa::path(
+    get,
+    path = "/index",
+    tag = "market index",
+    params(MarketIndexParams),
+    responses(
+        (status = 200, description = "The index of every day in the period", body = Vec<MarketIndexPoint>),
+        (status = 400, description = "The product is unknown", body = ErrorBody),
+        (status = 500, description = "The index could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_market_index(
+    State(state): State<AppState>,
+    Query(params): Query<MarketIndexParams>,
+) -> Result<Json<Vec<MarketIndexPoint>>, MarketIndexError> {
+    let (product_id, _) = fetch_product_by_slug(&state.db, params.product.as_deref())
+        .await
+        .map_err(MarketIndexError::fetch_error)?
+        .ok_or_else(|| MarketIndexError::invalid_input("unknown product"))?;
+
+    let rows = sqlx::query_as::<_, MarketIndexPoint>(
+        r#"
+        WITH shown AS (
+            SELECT
+                m.day,
+                m.providers,
+                CASE
+                    WHEN $4 THEN ROUND(m.median * (1 + vat_rate_at(m.day)), 4)
+                    ELSE m.median
+                END AS median,
+                CASE
+                    WHEN $4 THEN ROUND(m.trimmed_mean * (1 + vat_rate_at(m.day)), 4)
+                    ELSE m.trimmed_mean
+                END AS trimmed_mean,
+                CASE
+                    WHEN $4 THEN ROUND(m.weighted_mean * (1 + vat_rate_at(m.day)), 4)
+                    ELSE m.weighted_mean
+                END AS weighted_mean
+            FROM
+                market_index m
+            WHERE
+                m.product_id = $1
+        )
+        SELECT
+            s.day,
+            s.providers,
+            s.median,
+            s.trimmed_mean,
+            s.weighted_mean,
+            $4 AS includes_vat,
+            s.median - d.median AS day_change,
+            ROUND((s.median - d.median) / NULLIF(d.median, 0) * 100, 2) AS day_change_percent,
+            s.median - y.median AS year_change,
+            ROUND((s.median - y.median) / NULLIF(y.median, 0) * 100, 2) AS year_change_percent
+        FROM
+            shown s
+        LEFT JOIN
+            shown d ON d.day = s.day - 1
+        LEFT JOIN
+            shown y ON y.day = CAST(s.day - INTERVAL '1 year' AS DATE)
+        WHERE
+            s.day >= COALESCE($2, COALESCE($3, CURRENT_DATE) - $5)
+            AND s.day <= COALESCE($3, CURRENT_DATE)
+        ORDER BY
+            s.day
+        "#,
+    )
+    .bind(product_id)
+    .bind(params.start)
+    .bind(params.end)
+    .bind(params.vat.includes_vat())
+    .bind(DEFAULT_INDEX_DAYS)
+    .fetch_all(&state.db)
+    .await
+    .map_err(MarketIndexError::fetch_error)?;
+
+    Ok(Json(rows))
+}
@@ -0,0 +1,198 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::errors::{ErrorBody, MessageBody, PriceAlertsError, PriceAlertsSuccess};
+use crate::helpers::is_foreign_key_violation;
+use crate::models::price_alerts::{AlertChannel, PriceAlertAdd, PriceAlertCreated, PriceAlerts};
+use crate::models::products::DEFAULT_PRODUCT;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use lettre::Address;
+use rand::distributions::{Alphanumeric, DistString};
+use rust_decimal::Decimal;
+
+/// Subscribes to a price alert.
+///
+/// The alert's reference price for percent drops is the cheapest current price in the area at
+/// the time of subscribing. The returned token is needed to unsubscribe again. An unknown product
+/// or zone is answered with `400 Bad Request`.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `json` - The JSON payload containing the subscription details.
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PriceAlertCreated>), PriceAlertsError>` - The result of the operation, either the ID and token of the alert or an error.
+#[utoipa::path(
+    post,
+    path = "/alerts",
+    tag = "alerts",
+    request_body = PriceAlertAdd,
+    responses(
+        (status = 201, description = "The alert was created, with the token needed to unsubscribe", body = PriceAlertCreated),
+        (status = 400, description = "The alert is invalid or its product or zone unknown", body = ErrorBody),
+        (status = 500, description = "The alert could not be stored", body = ErrorBody),
+    )
+)]
+pub(crate) async fn create_price_alert(
+    State(state): State<AppState>,
+    Json(json): Json<PriceAlertAdd>,
+) -> Result<(StatusCode, Json<PriceAlertCreated>), PriceAlertsError> {
+    if json.zone_id.is_none() && json.postcode.is_none() {
+        return Err(PriceAlertsError::invalid_input(
+            "either zone_id or postcode is required",
+        ));
+    }
+    if json.target_price.is_none() && json.percent_drop.is_none() {
+        return Err(PriceAlertsError::invalid_input(
+            "either target_price or percent_drop is required",
+        ));
+    }
+    if json
+        .percent_drop
+        .is_some_and(|percent| percent <= Decimal::ZERO || percent >= Decimal::ONE_HUNDRED)
+    {
+        return Err(PriceAlertsError::invalid_input(
+            "percent_drop must be between 0 and 100",
+        ));
+    }
+    if json.channel == AlertChannel::Email && json.recipient.parse::<Address>().is_err() {
+        return Err(PriceAlertsError::invalid_input(
+            "recipient must be an email address",
+        ));
+    }
+
+    let token = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+
+    let row = sqlx::query_as::<_, PriceAlertCreated>(
+        r#"
+        WITH alert_product AS (
+            SELECT COALESCE($4, (SELECT id FROM products WHERE slug = $9)) AS id
+        ),
+        latest_prices AS (
+            SELECT DISTINCT ON (op.provider_id)
+                op.price, op.created_at
+            FROM
+                oil_prices op
+            WHERE
+                op.product_id = (SELECT id FROM alert_product)
+                AND op.deleted_at IS NULL
+                AND op.provider_id IN (
+                    SELECT pz.provider_id
+                    FROM provider_delivery_zones pz
+                    LEFT JOIN delivery_zone_postcodes zp ON pz.zone_id = zp.zone_id
+                    WHERE pz.zone_id = $5 OR $6 BETWEEN zp.postcode_from AND zp.postcode_to
+                )
+            ORDER BY
+                op.provider_id, op.created_at DESC, op.id DESC
+        )
+        INSERT INTO price_alerts
+            (token, recipient, channel, product_id, zone_id, postcode, target_price, percent_drop,
+             reference_price)
+        SELECT
+            $1, $2, $3, (SELECT id FROM alert_product), $5, $6, $7, $8,
+            (SELECT MIN(ROUND(price * (1 + vat_rate_at(created_at)), 4)) FROM latest_prices)
+        RETURNING id, token
+        "#,
+    )
+    .bind(&token)
+    .bind(json.recipient)
+    .bind(json.channel.as_str())
+    .bind(json.product_id)
+    .bind(json.zone_id)
+    .bind(json.postcode)
+    .bind(json.target_price)
+    .bind(json.percent_drop)
+    .bind(DEFAULT_PRODUCT)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| {
+        if is_foreign_key_violation(&e) {
+            PriceAlertsError::invalid_input("unknown product or zone")
+        } else {
+            PriceAlertsError::insert_error(e)
+        }
+    })?;
+
+    Ok((StatusCode::CREATED, Json(row)))
+}
+
+/// Fetches all price alerts from the database.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<PriceAlerts>>, PriceAlertsError>` - The result of the operation, either a list of price alerts or an error.
+#[utoipa::path(
+    get,
+    path = "/alerts",
+    tag = "alerts",
+    responses(
+        (status = 200, description = "All price alerts", body = Vec<PriceAlerts>),
+        (status = 500, description = "The price alerts could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn fetch_price_alerts(
+    _claims: Claims,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<PriceAlerts>>, PriceAlertsError> {
+    let res = sqlx::query_as::<_, PriceAlerts>(
+        r#"
+        SELECT
+            id, recipient, channel, product_id, zone_id, postcode, target_price, percent_drop,
+            reference_price, last_notified_price, created_at
+        FROM
+            price_alerts
+        ORDER BY
+            id
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(PriceAlertsError::fetch_error)?;
+
+    Ok(Json(res))
+}
+
+/// Unsubscribes from a price alert.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `token` - The token returned when subscribing.
+///
+/// # Returns
+///
+/// * `Result<PriceAlertsSuccess, PriceAlertsError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/alerts/{token}",
+    tag = "alerts",
+    params(("token" = String, Path, description = "The token returned when subscribing")),
+    responses(
+        (status = 200, description = "The alert was deleted", body = MessageBody),
+        (status = 404, description = "No alert has the token", body = ErrorBody),
+        (status = 500, description = "The alert could not be deleted", body = ErrorBody),
+    )
+)]
+pub(crate) async fn delete_price_alert(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<PriceAlertsSuccess, PriceAlertsError> {
+    let (id,) =
+        sqlx::query_as::<_, (i32,)>("DELETE FROM price_alerts WHERE token = $1 RETURNING id")
+            .bind(token)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(PriceAlertsError::delete_error)?
+            .ok_or_else(PriceAlertsError::not_found)?;
+
+    Ok(PriceAlertsSuccess::deleted(id))
+}
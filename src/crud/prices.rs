@@ -1,152 +1,2103 @@
 use crate::app_state::AppState;
 use crate::auth::jwt::Claims;
-use crate::errors::{PricesError, PricesSuccess};
+use crate::crud::quarantine::{check_plausibility, quarantine_correction, quarantine_price};
+use crate::errors::{ErrorBody, MessageBody, PricesError, PricesSuccess};
+use crate::export::{ExportFormat, Negotiated};
+use crate::helpers::{fetch_product_by_slug, is_foreign_key_violation};
+use crate::market_index::job::mark_dirty;
+use crate::models::live_events::LiveEvent;
 use crate::models::prices::{
-    PriceDetails, PriceInsertResponse, PriceQueryParams, Prices, ProviderPriceAdd,
+    BulkItemStatus, BulkMode, BulkPriceItem, BulkPriceParams, BulkPriceResponse, BulkPriceResult,
+    ComparedProvider, ComparisonPoint, PriceBasis, PriceChange, PriceChangeParams,
+    PriceCompareParams, PriceComparison, PriceCorrection, PriceDeleteParams, PriceDetails,
+    PriceEvent, PriceIngestParams, PriceInsertResponse, PriceListParams, PriceRevision,
+    PriceSeriesParams, PriceSeriesPoint, PriceSort, PriceStreamParams, PriceTier, Prices,
+    ProviderPriceAdd, StoredPrice, VatQueryParams, MAX_BULK_PRICES, MAX_COMPARED_PROVIDERS,
+    MAX_SERIES_POINTS,
 };
-use axum::extract::{Path, Query, State};
+use crate::models::products::DEFAULT_PRODUCT;
+use crate::models::quarantine::QuarantinedPriceResponse;
+use crate::models::webhooks::WebhookEvent;
+use crate::notifications::evaluator::evaluate_price_alerts;
+use crate::pagination::{PageParams, DEFAULT_LIMIT, MAX_LIMIT};
+use crate::webhooks::events::enqueue_event;
+use axum::body::Bytes;
+use axum::extract::{OriginalUri, Path, Query, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde_json::json;
+use sqlx::{Acquire, PgConnection, Postgres, QueryBuilder};
+use std::convert::Infallible;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Looks up the basis a provider publishes a product's price on.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection.
+/// * `provider_id` - The ID of the provider.
+/// * `product_id` - The ID of the product, if known.
+/// * `product` - The slug of the product, used when the ID is not known. Defaults to the default product.
+/// * `at` - The time the price applies to, which decides the VAT rate. Defaults to now.
+///
+/// # Returns
+///
+/// * `Result<Option<PriceBasis>, sqlx::Error>` - The basis, `None` if the product does not exist, or a database error.
+pub(crate) async fn fetch_price_basis(
+    conn: &mut PgConnection,
+    provider_id: i32,
+    product_id: Option<i32>,
+    product: Option<&str>,
+    at: Option<NaiveDateTime>,
+) -> Result<Option<PriceBasis>, sqlx::Error> {
+    sqlx::query_as::<_, PriceBasis>(
+        r#"
+        SELECT
+            pr.id AS product_id,
+            COALESCE(pp.includes_vat, TRUE) AS includes_vat,
+            COALESCE(pp.per_liters, 1) AS per_liters,
+            vat_rate_at(COALESCE($4, LOCALTIMESTAMP)) AS vat_rate
+        FROM
+            products pr
+        LEFT JOIN
+            provider_products pp ON pp.product_id = pr.id AND pp.provider_id = $1
+        WHERE
+            pr.id = COALESCE($2, (SELECT id FROM products WHERE slug = $3))
+        "#,
+    )
+    .bind(provider_id)
+    .bind(product_id)
+    .bind(product.unwrap_or(DEFAULT_PRODUCT))
+    .bind(at)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Stores a published price normalised on its basis, with its volume tiers.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, usually within a transaction.
+/// * `provider_id` - The ID of the provider.
+/// * `basis` - The basis the price is published on.
+/// * `raw_price` - The price as published by the provider.
+/// * `tiers` - The volume tiers as published by the provider.
+/// * `observed_at` - The time the price was observed. Defaults to now.
+/// * `scraping_run_id` - The ID of the scraping run that observed the price, if any.
+///
+/// # Returns
+///
+/// * `Result<i32, sqlx::Error>` - The ID of the new price or a database error.
+pub(crate) async fn insert_price(
+    conn: &mut PgConnection,
+    provider_id: i32,
+    basis: &PriceBasis,
+    raw_price: Decimal,
+    tiers: &[PriceTier],
+    observed_at: Option<NaiveDateTime>,
+    scraping_run_id: Option<i32>,
+) -> Result<i32, sqlx::Error> {
+    let row: PriceInsertResponse = sqlx::query_as::<_, PriceInsertResponse>(
+        r#"
+        INSERT INTO oil_prices
+            (provider_id, price, product_id, raw_price, raw_includes_vat, raw_per_liters,
+             created_at, valid_from, last_seen_at, scraping_run_id)
+        VALUES (
+            $1, $2, $3, $4, $5, $6,
+            COALESCE($7, LOCALTIMESTAMP), COALESCE($7, LOCALTIMESTAMP), COALESCE($7, LOCALTIMESTAMP),
+            $8
+        )
+        RETURNING id
+        "#,
+    )
+    .bind(provider_id)
+    .bind(basis.normalise(raw_price))
+    .bind(basis.product_id)
+    .bind(raw_price)
+    .bind(basis.includes_vat)
+    .bind(basis.per_liters)
+    .bind(observed_at)
+    .bind(scraping_run_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    for tier in tiers {
+        sqlx::query(
+            "INSERT INTO oil_price_tiers (price_id, min_liters, price) VALUES ($1, $2, $3)",
+        )
+        .bind(row.id)
+        .bind(tier.min_liters)
+        .bind(basis.normalise(tier.price))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    link_price_interval(conn, row.id).await?;
+
+    if let Some(observed_at) = observed_at {
+        mark_dirty(conn, observed_at.date()).await?;
+    }
+
+    Ok(row.id)
+}
+
+/// Fits a price into the intervals of its provider and product.
+///
+/// The price ends where the next one of the product starts, and ends the previous one. Both are
+/// looked up, since imported prices may arrive out of order. Deleted prices are left out.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, usually within a transaction.
+/// * `price_id` - The ID of the price.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` if the intervals were linked, or a database error.
+async fn link_price_interval(conn: &mut PgConnection, price_id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        WITH new_price AS (
+            SELECT id, provider_id, product_id, valid_from FROM oil_prices WHERE id = $1
+        ),
+        closed AS (
+            UPDATE oil_prices op
+            SET valid_to = (
+                SELECT MIN(n.valid_from)
+                FROM oil_prices n
+                WHERE n.provider_id = np.provider_id
+                  AND n.product_id = np.product_id
+                  AND n.valid_from > np.valid_from
+                  AND n.deleted_at IS NULL
+            )
+            FROM new_price np
+            WHERE op.id = np.id
+        )
+        UPDATE oil_prices op
+        SET valid_to = np.valid_from
+        FROM new_price np
+        WHERE op.id = (
+            SELECT p.id
+            FROM oil_prices p
+            WHERE p.provider_id = np.provider_id
+              AND p.product_id = np.product_id
+              AND p.valid_from <= np.valid_from
+              AND p.id <> np.id
+              AND p.deleted_at IS NULL
+            ORDER BY p.valid_from DESC, p.id DESC
+            LIMIT 1
+        )
+        "#,
+    )
+    .bind(price_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Marks the price in effect as seen again if a newly observed price is unchanged.
+///
+/// A price is unchanged if the price of the product in effect at the time it was observed has
+/// the same normalised price and volume tiers. Only its `last_seen_at` is then moved forward.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, usually within a transaction.
+/// * `provider_id` - The ID of the provider.
+/// * `basis` - The basis the price is published on.
+/// * `raw_price` - The price as published by the provider.
+/// * `tiers` - The volume tiers as published by the provider.
+/// * `observed_at` - The time the price was observed. Defaults to now.
+///
+/// # Returns
+///
+/// * `Result<Option<i32>, sqlx::Error>` - The ID of the unchanged price, `None` if the price changed, or a database error.
+pub(crate) async fn touch_unchanged_price(
+    conn: &mut PgConnection,
+    provider_id: i32,
+    basis: &PriceBasis,
+    raw_price: Decimal,
+    tiers: &[PriceTier],
+    observed_at: Option<NaiveDateTime>,
+) -> Result<Option<i32>, sqlx::Error> {
+    let current = sqlx::query_as::<_, (i32, Decimal)>(
+        r#"
+        SELECT id, price
+        FROM oil_prices
+        WHERE provider_id = $1
+          AND product_id = $2
+          AND valid_from <= COALESCE($3, LOCALTIMESTAMP)
+          AND deleted_at IS NULL
+        ORDER BY valid_from DESC, id DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(provider_id)
+    .bind(basis.product_id)
+    .bind(observed_at)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some((price_id, price)) = current else {
+        return Ok(None);
+    };
+    if price != basis.normalise(raw_price) {
+        return Ok(None);
+    }
+
+    let current_tiers = sqlx::query_as::<_, (i32, Decimal)>(
+        "SELECT min_liters, price FROM oil_price_tiers WHERE price_id = $1 ORDER BY min_liters",
+    )
+    .bind(price_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut new_tiers: Vec<(i32, Decimal)> = tiers
+        .iter()
+        .map(|tier| (tier.min_liters, basis.normalise(tier.price)))
+        .collect();
+    new_tiers.sort_by_key(|tier| tier.0);
+    if current_tiers != new_tiers {
+        return Ok(None);
+    }
+
+    let last_seen_at: NaiveDateTime = sqlx::query_scalar(
+        r#"
+        UPDATE oil_prices op
+        SET last_seen_at = GREATEST(op.last_seen_at, COALESCE($2, LOCALTIMESTAMP))
+        FROM oil_prices before
+        WHERE op.id = $1 AND before.id = op.id
+        RETURNING before.last_seen_at
+        "#,
+    )
+    .bind(price_id)
+    .bind(observed_at)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    // Seeing the price again in the past may keep its provider active on days already computed
+    if observed_at.is_some_and(|observed_at| observed_at > last_seen_at) {
+        mark_dirty(conn, last_seen_at.date()).await?;
+    }
+
+    Ok(Some(price_id))
+}
+
+/// Queues the `price.created` webhook for a newly stored price.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, within the transaction that stored the price.
+/// * `price_id` - The ID of the price.
+/// * `provider_id` - The ID of the provider.
+/// * `basis` - The basis the price was published on.
+/// * `raw_price` - The price as published by the provider.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` if the webhook was queued, or a database error.
+pub(crate) async fn enqueue_price_created(
+    conn: &mut PgConnection,
+    price_id: i32,
+    provider_id: i32,
+    basis: &PriceBasis,
+    raw_price: Decimal,
+) -> Result<(), sqlx::Error> {
+    enqueue_event(
+        conn,
+        WebhookEvent::PriceCreated,
+        json!({
+            "id": price_id,
+            "provider_id": provider_id,
+            "product_id": basis.product_id,
+            "price": basis.normalise(raw_price),
+            "includes_vat": false,
+            "raw_price": raw_price,
+            "raw_includes_vat": basis.includes_vat,
+            "raw_per_liters": basis.per_liters,
+        }),
+    )
+    .await
+}
+
+/// Pushes newly stored prices to live subscribers and evaluates price alerts in the background.
+///
+/// Only prices that are now in effect are followed up on, so back-dated prices stored behind a
+/// newer one neither reach subscribers nor trigger alerts.
+///
+/// # Arguments
+///
+/// * `state` - The application state.
+/// * `price_ids` - The IDs of the new prices.
+pub(crate) fn spawn_price_followups(state: AppState, price_ids: Vec<i32>) {
+    tokio::spawn(async move {
+        let current: Vec<i32> = match sqlx::query_scalar(
+            r#"
+            SELECT id FROM oil_prices
+            WHERE id = ANY($1) AND valid_to IS NULL AND deleted_at IS NULL
+            ORDER BY id
+            "#,
+        )
+        .bind(&price_ids)
+        .fetch_all(&state.db)
+        .await
+        {
+            Ok(current) => current,
+            Err(e) => {
+                tracing::error!("Error while fetching prices in effect: {}", e);
+                return;
+            }
+        };
+
+        for price_id in current {
+            if let Err(e) = state.events.publish_price(&state.db, price_id).await {
+                tracing::error!("Error while publishing price {}: {}", price_id, e);
+            }
+            if let Err(e) = evaluate_price_alerts(state.clone(), price_id).await {
+                tracing::error!(
+                    "Error while evaluating price alerts for price {}: {}",
+                    price_id,
+                    e
+                );
+            }
+        }
+    });
+}
 
 /// Creates a new price for a provider in the database.
 ///
+/// The published price is normalised to DKK per liter excluding VAT using the basis declared by
+/// the provider's extraction rule for the product, which the payload may override. The published
+/// value is kept alongside the normalised price. Once the price is stored it is pushed to live
+/// subscribers and price alerts are evaluated in the background.
+///
+/// With `dedupe=true` a price equal to the one in effect, tiers included, is not stored again;
+/// the price in effect is only marked as seen and its ID returned.
+///
+/// Prices failing the plausibility checks of their product are not stored but held for review,
+/// answered with `202 Accepted` and the reasons the price is suspicious. An unknown provider or
+/// product is answered with `404 Not Found`.
+///
 /// # Arguments
 ///
 /// * `_claims` - The JWT claims of the authenticated user.
 /// * `state` - The application state containing the database connection pool.
 /// * `id` - The ID of the provider.
-/// * `json` - The JSON payload containing the price details.
+/// * `params` - The query parameters choosing whether unchanged prices are deduplicated.
+/// * `json` - The JSON payload containing the price details and optional volume tiers. Prices without a product are recorded as the default product.
 ///
 /// # Returns
 ///
-/// * `Result<PricesSuccess, PricesError>` - The result of the operation, either a success or an error.
+/// * `Result<Response, PricesError>` - The result of the operation, either a success, the quarantined price or an error.
+#[utoipa::path(
+    post,
+    path = "/providers/{id}/prices",
+    tag = "prices",
+    params(("id" = i32, Path, description = "The ID of the provider"), PriceIngestParams),
+    request_body = ProviderPriceAdd,
+    responses(
+        (status = 201, description = "The price was stored", body = MessageBody),
+        (status = 200, description = "The price was unchanged and the price in effect marked as seen", body = MessageBody),
+        (status = 202, description = "The price failed the plausibility checks and was held for review", body = QuarantinedPriceResponse),
+        (status = 400, description = "The price or its tiers are invalid", body = ErrorBody),
+        (status = 404, description = "The provider or the product does not exist", body = ErrorBody),
+        (status = 500, description = "The price could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn create_price_for_provider(
     _claims: Claims,
     State(state): State<AppState>,
     Path(id): Path<i32>,
+    Query(params): Query<PriceIngestParams>,
     Json(json): Json<ProviderPriceAdd>,
-) -> Result<PricesSuccess, PricesError> {
-    let row: PriceInsertResponse = sqlx::query_as::<_, PriceInsertResponse>(
-        "INSERT INTO oil_prices (provider_id, price) VALUES ($1, $2) RETURNING id",
+) -> Result<Response, PricesError> {
+    if json.price <= Decimal::ZERO {
+        return Err(PricesError::invalid_input(
+            "price must be greater than zero",
+        ));
+    }
+    if json.tiers.iter().any(|tier| tier.min_liters <= 0) {
+        return Err(PricesError::invalid_input(
+            "tier min_liters must be greater than zero",
+        ));
+    }
+    if json.per_liters.is_some_and(|per_liters| per_liters <= 0) {
+        return Err(PricesError::invalid_input(
+            "per_liters must be greater than zero",
+        ));
+    }
+
+    let mut tx = state.db.begin().await.map_err(PricesError::insert_error)?;
+
+    let mut basis = fetch_price_basis(&mut tx, id, json.product_id, None, None)
+        .await
+        .map_err(PricesError::fetch_error)?
+        .ok_or_else(|| PricesError::unknown("product"))?;
+
+    if let Some(includes_vat) = json.includes_vat {
+        basis.includes_vat = includes_vat;
+    }
+    if let Some(per_liters) = json.per_liters {
+        basis.per_liters = per_liters;
+    }
+
+    if params.dedupe {
+        let unchanged = touch_unchanged_price(&mut tx, id, &basis, json.price, &json.tiers, None)
+            .await
+            .map_err(PricesError::insert_error)?;
+        if let Some(price_id) = unchanged {
+            tx.commit().await.map_err(PricesError::insert_error)?;
+            return Ok(PricesSuccess::updated(price_id).into_response());
+        }
+    }
+
+    let reasons = check_plausibility(
+        &mut tx,
+        id,
+        basis.product_id,
+        basis.normalise(json.price),
+        None,
     )
-    .bind(id)
-    .bind(json.price)
-    .fetch_one(&state.db)
     .await
-    .map_err(PricesError::insert_error)?;
+    .map_err(PricesError::fetch_error)?;
+    if !reasons.is_empty() {
+        let quarantine_id =
+            quarantine_price(&mut tx, id, &basis, json.price, &json.tiers, None, &reasons)
+                .await
+                .map_err(provider_insert_error)?;
+        tx.commit().await.map_err(PricesError::insert_error)?;
+
+        tracing::warn!(
+            "Quarantined price {} of provider {}: {}",
+            json.price,
+            id,
+            reasons.join("; ")
+        );
+
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(QuarantinedPriceResponse {
+                quarantine_id,
+                reasons,
+            }),
+        )
+            .into_response());
+    }
+
+    let price_id = insert_price(&mut tx, id, &basis, json.price, &json.tiers, None, None)
+        .await
+        .map_err(provider_insert_error)?;
+    enqueue_price_created(&mut tx, price_id, id, &basis, json.price)
+        .await
+        .map_err(PricesError::insert_error)?;
+
+    tx.commit().await.map_err(PricesError::insert_error)?;
+
+    spawn_price_followups(state, vec![price_id]);
+
+    Ok(PricesSuccess::created(price_id).into_response())
+}
+
+/// Maps an error storing a price for a provider, reporting an unknown provider as not found.
+///
+/// # Arguments
+///
+/// * `error` - The database error.
+///
+/// # Returns
+///
+/// * `PricesError` - A not found error if the provider does not exist, or an insert error.
+fn provider_insert_error(error: sqlx::Error) -> PricesError {
+    if is_foreign_key_violation(&error) {
+        PricesError::unknown("provider")
+    } else {
+        PricesError::insert_error(error)
+    }
+}
+
+/// Parses the items of a bulk request, sent either as a JSON array or as NDJSON.
+///
+/// # Arguments
+///
+/// * `headers` - The request headers, whose content type tells the two apart.
+/// * `body` - The request body.
+///
+/// # Returns
+///
+/// * `Result<Vec<Result<BulkPriceItem, String>>, PricesError>` - The items, each either parsed or with the reason it could not be, or an error if the body is malformed as a whole.
+fn parse_bulk_items(
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<Vec<Result<BulkPriceItem, String>>, PricesError> {
+    let is_ndjson = headers
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| {
+            content_type.starts_with("application/x-ndjson")
+                || content_type.starts_with("application/ndjson")
+        });
 
-    Ok(PricesSuccess::created(row.id))
+    if is_ndjson {
+        let body = std::str::from_utf8(body)
+            .map_err(|_| PricesError::invalid_input("body must be UTF-8"))?;
+        return Ok(body
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|e| e.to_string()))
+            .collect());
+    }
+
+    let items: Vec<serde_json::Value> = serde_json::from_slice(body).map_err(|e| {
+        PricesError::invalid_input(format!("body must be a JSON array of prices: {}", e))
+    })?;
+    Ok(items
+        .into_iter()
+        .map(|item| serde_json::from_value(item).map_err(|e| e.to_string()))
+        .collect())
 }
 
-/// Fetches all prices from the database.
+/// Validates and stores a single item of a bulk request.
+///
+/// Prices failing the plausibility checks of their product are held for review instead.
 ///
 /// # Arguments
 ///
+/// * `conn` - The database connection, within the request's transaction.
+/// * `item` - The item to store.
+/// * `known_providers` - The sorted IDs of the providers that exist.
+/// * `scraping_run_id` - The ID of the scraping run the prices were observed in, if any.
+/// * `dedupe` - Whether an unchanged price only marks the price in effect as seen again.
+///
+/// # Returns
+///
+/// * `Result<Result<(BulkItemStatus, i32, Vec<String>), String>, sqlx::Error>` - Whether the price was created, unchanged or quarantined with its ID and the reasons it was quarantined, the reason the item was rejected, or a database error.
+async fn store_bulk_item(
+    conn: &mut PgConnection,
+    item: &BulkPriceItem,
+    known_providers: &[i32],
+    scraping_run_id: Option<i32>,
+    dedupe: bool,
+) -> Result<Result<(BulkItemStatus, i32, Vec<String>), String>, sqlx::Error> {
+    if known_providers.binary_search(&item.provider_id).is_err() {
+        return Ok(Err("unknown provider".to_string()));
+    }
+    if item.price <= Decimal::ZERO {
+        return Ok(Err("price must be greater than zero".to_string()));
+    }
+
+    let Some(basis) = fetch_price_basis(
+        conn,
+        item.provider_id,
+        None,
+        item.product.as_deref(),
+        item.observed_at,
+    )
+    .await?
+    else {
+        return Ok(Err("unknown product".to_string()));
+    };
+
+    if dedupe {
+        let unchanged = touch_unchanged_price(
+            conn,
+            item.provider_id,
+            &basis,
+            item.price,
+            &[],
+            item.observed_at,
+        )
+        .await?;
+        if let Some(price_id) = unchanged {
+            return Ok(Ok((BulkItemStatus::Unchanged, price_id, vec![])));
+        }
+    }
+
+    let reasons = check_plausibility(
+        conn,
+        item.provider_id,
+        basis.product_id,
+        basis.normalise(item.price),
+        item.observed_at,
+    )
+    .await?;
+    if !reasons.is_empty() {
+        let quarantine_id = quarantine_price(
+            conn,
+            item.provider_id,
+            &basis,
+            item.price,
+            &[],
+            item.observed_at,
+            &reasons,
+        )
+        .await?;
+        return Ok(Ok((BulkItemStatus::Quarantined, quarantine_id, reasons)));
+    }
+
+    let price_id = insert_price(
+        conn,
+        item.provider_id,
+        &basis,
+        item.price,
+        &[],
+        item.observed_at,
+        scraping_run_id,
+    )
+    .await?;
+    enqueue_price_created(conn, price_id, item.provider_id, &basis, item.price).await?;
+
+    Ok(Ok((BulkItemStatus::Created, price_id, vec![])))
+}
+
+/// Maps a database error storing an item of a partial bulk request to the reason reported for
+/// the item. The error itself is logged rather than reported, to keep the reasons stable.
+///
+/// # Arguments
+///
+/// * `error` - The database error.
+///
+/// # Returns
+///
+/// * `String` - The reason the item was rejected.
+fn bulk_item_error(error: sqlx::Error) -> String {
+    if is_foreign_key_violation(&error) {
+        return "unknown provider or product".to_string();
+    }
+
+    tracing::error!("Error while storing bulk price: {}", error);
+    "the price could not be stored".to_string()
+}
+
+/// Creates many prices, for any number of providers, in one request.
+///
+/// The body is a JSON array or, with the `application/x-ndjson` content type, one JSON object per
+/// line. Each item holds a `provider_id`, a `price`, and optionally the time it was `observed_at`
+/// and the `product` slug. Prices are normalised like single prices are. In the default
+/// `atomic` mode nothing is stored unless every item is valid; in `partial` mode the valid items
+/// are stored and the rest rejected. Either way the outcome of every item is reported. With
+/// `dedupe=true` unchanged prices are reported as `unchanged` instead of being stored again.
+/// Prices failing the plausibility checks of their product are held for review and reported as
+/// `quarantined` with the reasons they are suspicious.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
 /// * `state` - The application state containing the database connection pool.
+/// * `params` - The query parameters holding the scraping run the prices were observed in, the mode and whether unchanged prices are deduplicated.
+/// * `headers` - The request headers.
+/// * `body` - The request body holding the prices.
 ///
 /// # Returns
 ///
-/// * `Result<Json<Vec<Prices>>, PricesError>` - The result of the operation, either a list of prices or an error.
+/// * `Result<(StatusCode, Json<BulkPriceResponse>), PricesError>` - The result of the operation, either the outcome of every item or an error.
+#[utoipa::path(
+    post,
+    path = "/prices/bulk",
+    tag = "prices",
+    params(BulkPriceParams),
+    request_body(content(
+        (Vec<BulkPriceItem> = "application/json"),
+        (String = "application/x-ndjson"),
+    )),
+    responses(
+        (status = 201, description = "Every item was stored, found unchanged or held for review", body = BulkPriceResponse),
+        (status = 200, description = "Some items were rejected and the rest stored", body = BulkPriceResponse),
+        (status = 400, description = "The body or the scraping run is invalid", body = ErrorBody),
+        (status = 422, description = "Some items were rejected, so nothing was stored, in atomic mode", body = BulkPriceResponse),
+        (status = 500, description = "The prices could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn create_prices_bulk(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Query(params): Query<BulkPriceParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<BulkPriceResponse>), PricesError> {
+    let items = parse_bulk_items(&headers, &body)?;
+    if items.is_empty() {
+        return Err(PricesError::invalid_input("no prices given"));
+    }
+    if items.len() > MAX_BULK_PRICES {
+        return Err(PricesError::invalid_input(format!(
+            "at most {} prices may be sent at once",
+            MAX_BULK_PRICES
+        )));
+    }
+
+    let mut tx = state.db.begin().await.map_err(PricesError::insert_error)?;
+
+    if let Some(scraping_run_id) = params.scraping_run_id {
+        sqlx::query_as::<_, (i32,)>("SELECT id FROM scraping_runs WHERE id = $1")
+            .bind(scraping_run_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(PricesError::fetch_error)?
+            .ok_or_else(|| PricesError::invalid_input("unknown scraping run"))?;
+    }
+
+    let provider_ids: Vec<i32> = items
+        .iter()
+        .filter_map(|item| item.as_ref().ok().map(|item| item.provider_id))
+        .collect();
+    let known_providers: Vec<i32> =
+        sqlx::query_scalar("SELECT id FROM providers WHERE id = ANY($1) ORDER BY id")
+            .bind(&provider_ids)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(PricesError::fetch_error)?;
+
+    let mut results = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        let outcome = match item {
+            Err(error) => Err(error),
+            // Partial requests store each item under a savepoint, so a failing item leaves the
+            // others intact
+            Ok(item) if params.mode == BulkMode::Partial => {
+                let mut savepoint = (&mut *tx)
+                    .begin()
+                    .await
+                    .map_err(PricesError::insert_error)?;
+                let outcome = store_bulk_item(
+                    &mut savepoint,
+                    &item,
+                    &known_providers,
+                    params.scraping_run_id,
+                    params.dedupe,
+                )
+                .await
+                .unwrap_or_else(|e| Err(bulk_item_error(e)));
+                if outcome.is_ok() {
+                    savepoint
+                        .commit()
+                        .await
+                        .map_err(PricesError::insert_error)?;
+                }
+                outcome
+            }
+            Ok(item) => store_bulk_item(
+                &mut tx,
+                &item,
+                &known_providers,
+                params.scraping_run_id,
+                params.dedupe,
+            )
+            .await
+            .map_err(PricesError::insert_error)?,
+        };
+
+        results.push(match outcome {
+            Ok((status, id, reasons)) => BulkPriceResult {
+                index,
+                status,
+                id: Some(id),
+                error: None,
+                reasons,
+            },
+            Err(error) => BulkPriceResult {
+                index,
+                status: BulkItemStatus::Rejected,
+                id: None,
+                error: Some(error),
+                reasons: vec![],
+            },
+        });
+    }
+
+    let rejected = results
+        .iter()
+        .filter(|result| result.status == BulkItemStatus::Rejected)
+        .count();
+
+    if params.mode == BulkMode::Atomic && rejected > 0 {
+        tx.rollback().await.map_err(PricesError::insert_error)?;
+
+        for result in results.iter_mut() {
+            if matches!(
+                result.status,
+                BulkItemStatus::Created | BulkItemStatus::Unchanged | BulkItemStatus::Quarantined
+            ) {
+                result.status = BulkItemStatus::Skipped;
+                result.id = None;
+                result.reasons.clear();
+            }
+        }
+
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(BulkPriceResponse {
+                scraping_run_id: params.scraping_run_id,
+                created: 0,
+                unchanged: 0,
+                quarantined: 0,
+                rejected,
+                results,
+            }),
+        ));
+    }
+
+    tx.commit().await.map_err(PricesError::insert_error)?;
+
+    let price_ids: Vec<i32> = results
+        .iter()
+        .filter(|result| result.status == BulkItemStatus::Created)
+        .filter_map(|result| result.id)
+        .collect();
+    let created = price_ids.len();
+    let unchanged = results
+        .iter()
+        .filter(|result| result.status == BulkItemStatus::Unchanged)
+        .count();
+    let quarantined = results
+        .iter()
+        .filter(|result| result.status == BulkItemStatus::Quarantined)
+        .count();
+    spawn_price_followups(state, price_ids);
+
+    let status = if rejected == 0 {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((
+        status,
+        Json(BulkPriceResponse {
+            scraping_run_id: params.scraping_run_id,
+            created,
+            unchanged,
+            quarantined,
+            rejected,
+            results,
+        }),
+    ))
+}
+
+/// Builds the query listing prices that match a set of filters, one page at a time.
+///
+/// # Arguments
+///
+/// * `params` - The filters and whether prices include VAT.
+/// * `page` - The pagination and sorting parameters.
+///
+/// # Returns
+///
+/// * `Result<QueryBuilder<Postgres>, PricesError>` - The query, or an error if the cursor is malformed.
+fn price_list_query<'a>(
+    params: &'a PriceListParams,
+    page: &PageParams<PriceSort>,
+) -> Result<QueryBuilder<'a, Postgres>, PricesError> {
+    let includes_vat = params.vat.includes_vat();
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT * FROM (
+            SELECT
+                oil_prices.id,
+                oil_prices.provider_id,
+                oil_prices.product_id,
+                CASE
+                    WHEN "#,
+    );
+    query
+        .push_bind(includes_vat)
+        .push(
+            r#" THEN ROUND(oil_prices.price * (1 + vat_rate_at(oil_prices.created_at)), 4)
+                    ELSE oil_prices.price
+                END AS price,
+                oil_prices.currency,
+                oil_prices.unit,
+                "#,
+        )
+        .push_bind(includes_vat)
+        .push(
+            r#" AS includes_vat,
+                oil_prices.raw_price,
+                oil_prices.created_at,
+                oil_prices.deleted_at
+            FROM
+                oil_prices
+            JOIN
+                products
+            ON
+                oil_prices.product_id = products.id
+            WHERE
+                TRUE"#,
+        );
+
+    if !params.include_deleted {
+        query.push(" AND oil_prices.deleted_at IS NULL");
+    }
+    if let Some(provider_id) = params.provider_id {
+        query
+            .push(" AND oil_prices.provider_id = ")
+            .push_bind(provider_id);
+    }
+    if let Some(zone_id) = params.zone_id {
+        query
+            .push(
+                " AND EXISTS (SELECT 1 FROM provider_delivery_zones pdz \
+                 WHERE pdz.provider_id = oil_prices.provider_id AND pdz.zone_id = ",
+            )
+            .push_bind(zone_id)
+            .push(")");
+    }
+    if let Some(product) = &params.product {
+        query.push(" AND products.slug = ").push_bind(product);
+    }
+    if let Some(start) = params.start {
+        query.push(" AND oil_prices.created_at > ").push_bind(start);
+    }
+    if let Some(end) = params.end {
+        query.push(" AND oil_prices.created_at < ").push_bind(end);
+    }
+
+    query.push(") AS page WHERE TRUE");
+
+    if let Some(min_price) = params.min_price {
+        query.push(" AND page.price >= ").push_bind(min_price);
+    }
+    if let Some(max_price) = params.max_price {
+        query.push(" AND page.price <= ").push_bind(max_price);
+    }
+
+    page.push_page(&mut query)
+        .map_err(PricesError::invalid_input)?;
+    // The link to the next page keeps the offset, so it only applies to the first page
+    if let (Some(offset), None) = (params.offset, &page.cursor) {
+        query.push(" OFFSET ").push_bind(offset.max(0));
+    }
+
+    Ok(query)
+}
+
+/// Fetches a page of prices from the database.
+///
+/// The page is sent as JSON, CSV or NDJSON depending on the `Accept` header. The next page is
+/// linked in the `Link` and `X-Next-Cursor` response headers. Deleted prices are only listed
+/// with `include_deleted=true`, which requires authentication. The deprecated `offset` parameter
+/// is still honoured for clients that have not moved to cursors.
+///
+/// # Arguments
+///
+/// * `claims` - The JWT claims of the authenticated user, if any.
+/// * `state` - The application state containing the database connection pool.
+/// * `uri` - The URI of the request, used to link to the next page.
+/// * `format` - The response format negotiated from the `Accept` header.
+/// * `page` - The pagination and sorting parameters.
+/// * `params` - The query parameters for filtering by provider, zone, product, date and price, and choosing whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Negotiated<Prices>, PricesError>` - The result of the operation, either a page of prices or an error.
+#[utoipa::path(
+    get,
+    path = "/prices",
+    tag = "prices",
+    params(
+        PageParams<PriceSort>,
+        ("sort" = Option<PriceSort>, Query, description = "The column to sort by"),
+        PriceListParams,
+    ),
+    responses(
+        (status = 200, description = "A page of prices", content(
+            (Vec<Prices> = "application/json"),
+            (String = "text/csv"),
+            (String = "application/x-ndjson"),
+        )),
+        (status = 400, description = "The filters or the cursor are invalid", body = ErrorBody),
+        (status = 401, description = "Deleted prices were asked for without authentication", body = ErrorBody),
+        (status = 500, description = "The prices could not be fetched", body = ErrorBody),
+    ),
+    security((), ("bearer" = []))
+)]
 pub(crate) async fn fetch_prices(
+    claims: Option<Claims>,
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    format: ExportFormat,
+    Query(page): Query<PageParams<PriceSort>>,
+    Query(params): Query<PriceListParams>,
+) -> Result<Negotiated<Prices>, PricesError> {
+    if params.include_deleted && claims.is_none() {
+        return Err(PricesError::unauthorized(
+            "include_deleted requires authentication",
+        ));
+    }
+
+    let rows = price_list_query(&params, &page)?
+        .build_query_as::<Prices>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(PricesError::fetch_error)?;
+
+    Ok(page.paginate(rows, uri).negotiated(format))
+}
+
+/// Fetches a page of prices for a specific provider from the database.
+///
+/// The page is sent as JSON, CSV or NDJSON depending on the `Accept` header. The next page is
+/// linked in the `Link` and `X-Next-Cursor` response headers. Deleted prices are only listed
+/// with `include_deleted=true`, which requires authentication. The deprecated `offset` parameter
+/// is still honoured for clients that have not moved to cursors.
+///
+/// # Arguments
+///
+/// * `claims` - The JWT claims of the authenticated user, if any.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the provider.
+/// * `uri` - The URI of the request, used to link to the next page.
+/// * `format` - The response format negotiated from the `Accept` header.
+/// * `page` - The pagination and sorting parameters.
+/// * `params` - The query parameters for filtering and choosing whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Negotiated<PriceDetails>, PricesError>` - The result of the operation, either a page of price details or an error.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/prices",
+    tag = "prices",
+    params(
+        ("id" = i32, Path, description = "The ID of the provider"),
+        PageParams<PriceSort>,
+        ("sort" = Option<PriceSort>, Query, description = "The column to sort by"),
+        PriceListParams,
+    ),
+    responses(
+        (status = 200, description = "A page of the provider's prices", content(
+            (Vec<PriceDetails> = "application/json"),
+            (String = "text/csv"),
+            (String = "application/x-ndjson"),
+        )),
+        (status = 400, description = "The filters or the cursor are invalid", body = ErrorBody),
+        (status = 401, description = "Deleted prices were asked for without authentication", body = ErrorBody),
+        (status = 500, description = "The prices could not be fetched", body = ErrorBody),
+    ),
+    security((), ("bearer" = []))
+)]
+pub(crate) async fn fetch_prices_by_provider(
+    claims: Option<Claims>,
     State(state): State<AppState>,
-) -> Result<Json<Vec<Prices>>, PricesError> {
-    let rows = sqlx::query_as::<_, Prices>(
+    Path(id): Path<i32>,
+    OriginalUri(uri): OriginalUri,
+    format: ExportFormat,
+    Query(page): Query<PageParams<PriceSort>>,
+    Query(mut params): Query<PriceListParams>,
+) -> Result<Negotiated<PriceDetails>, PricesError> {
+    if params.include_deleted && claims.is_none() {
+        return Err(PricesError::unauthorized(
+            "include_deleted requires authentication",
+        ));
+    }
+    params.provider_id = Some(id);
+
+    let results = price_list_query(&params, &page)?
+        .build_query_as::<PriceDetails>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(PricesError::fetch_error)?;
+
+    Ok(page.paginate(results, uri).negotiated(format))
+}
+
+/// Locks a price for a change by an administrator.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, within the change's transaction.
+/// * `id` - The ID of the price.
+///
+/// # Returns
+///
+/// * `Result<StoredPrice, PricesError>` - The price as stored, or an error if it does not exist.
+async fn lock_price(conn: &mut PgConnection, id: i32) -> Result<StoredPrice, PricesError> {
+    fetch_stored_price(conn, id)
+        .await
+        .map_err(PricesError::fetch_error)?
+        .ok_or_else(PricesError::not_found)
+}
+
+/// Fetches a price as stored, deleted or not, locking it for a change.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, within the change's transaction.
+/// * `id` - The ID of the price.
+///
+/// # Returns
+///
+/// * `Result<Option<StoredPrice>, sqlx::Error>` - The price as stored, `None` if it does not exist, or a database error.
+pub(crate) async fn fetch_stored_price(
+    conn: &mut PgConnection,
+    id: i32,
+) -> Result<Option<StoredPrice>, sqlx::Error> {
+    sqlx::query_as::<_, StoredPrice>(
         r#"
         SELECT
-            oil_prices.id AS id,
-            oil_prices.price,
-            oil_prices.created_at,
-            providers.id AS provider_id
+            provider_id, product_id, price, raw_price, raw_includes_vat, raw_per_liters, created_at,
+            valid_from, deleted_at
         FROM
             oil_prices
-        JOIN
-            providers
-        ON
-            oil_prices.provider_id = providers.id
-        ORDER BY
-            oil_prices.created_at ASC
+        WHERE
+            id = $1
+        FOR UPDATE
         "#,
     )
-    .fetch_all(&state.db)
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Corrects the value of a price.
+///
+/// The price keeps its ID and timestamp. The value it had before, volume tiers included, is kept
+/// as a revision along with who corrected it and why. The basis of the price defaults to the one
+/// it was published on, and its tiers are only replaced if given.
+///
+/// A corrected price failing the plausibility checks of its product is not applied but held for
+/// review, answered with `202 Accepted` and the reasons the price is suspicious.
+///
+/// # Arguments
+///
+/// * `claims` - The JWT claims of the authenticated user, recorded as the author of the correction.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the price.
+/// * `json` - The JSON payload containing the corrected price and the reason for the correction.
+///
+/// # Returns
+///
+/// * `Result<Response, PricesError>` - The result of the operation, either a success, the quarantined correction or an error.
+#[utoipa::path(
+    patch,
+    path = "/prices/{id}",
+    tag = "prices",
+    params(("id" = i32, Path, description = "The ID of the price")),
+    request_body = PriceCorrection,
+    responses(
+        (status = 200, description = "The price was corrected", body = MessageBody),
+        (status = 202, description = "The correction failed the plausibility checks and was held for review", body = QuarantinedPriceResponse),
+        (status = 400, description = "The correction is invalid", body = ErrorBody),
+        (status = 404, description = "The price does not exist", body = ErrorBody),
+        (status = 500, description = "The price could not be corrected", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn correct_price(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(json): Json<PriceCorrection>,
+) -> Result<Response, PricesError> {
+    if json.price <= Decimal::ZERO {
+        return Err(PricesError::invalid_input(
+            "price must be greater than zero",
+        ));
+    }
+    if json.tiers.iter().flatten().any(|tier| tier.min_liters <= 0) {
+        return Err(PricesError::invalid_input(
+            "tier min_liters must be greater than zero",
+        ));
+    }
+    if json.per_liters.is_some_and(|per_liters| per_liters <= 0) {
+        return Err(PricesError::invalid_input(
+            "per_liters must be greater than zero",
+        ));
+    }
+
+    let mut tx = state.db.begin().await.map_err(PricesError::update_error)?;
+
+    let stored = lock_price(&mut tx, id).await?;
+    if stored.deleted_at.is_some() {
+        return Err(PricesError::invalid_input(
+            "deleted prices cannot be corrected",
+        ));
+    }
+
+    let mut basis = fetch_price_basis(
+        &mut tx,
+        stored.provider_id,
+        Some(stored.product_id),
+        None,
+        stored.created_at,
+    )
+    .await
+    .map_err(PricesError::fetch_error)?
+    .ok_or_else(|| PricesError::invalid_input("unknown product"))?;
+    basis.includes_vat = json.includes_vat.unwrap_or(stored.raw_includes_vat);
+    basis.per_liters = json.per_liters.unwrap_or(stored.raw_per_liters);
+
+    let reasons = check_plausibility(
+        &mut tx,
+        stored.provider_id,
+        stored.product_id,
+        basis.normalise(json.price),
+        stored.created_at,
+    )
     .await
     .map_err(PricesError::fetch_error)?;
+    if !reasons.is_empty() {
+        let quarantine_id = quarantine_correction(
+            &mut tx,
+            id,
+            stored.provider_id,
+            &basis,
+            &json,
+            &claims.username,
+            &reasons,
+        )
+        .await
+        .map_err(PricesError::update_error)?;
+        tx.commit().await.map_err(PricesError::update_error)?;
 
-    Ok(Json(rows))
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(QuarantinedPriceResponse {
+                quarantine_id,
+                reasons,
+            }),
+        )
+            .into_response());
+    }
+
+    apply_correction(&mut tx, id, &stored, &basis, &json, &claims.username)
+        .await
+        .map_err(PricesError::update_error)?;
+
+    tx.commit().await.map_err(PricesError::update_error)?;
+
+    Ok(PricesSuccess::updated(id).into_response())
 }
 
-/// Fetches prices for a specific provider from the database.
+/// Applies a correction to a price, keeping the value it had before as a revision and notifying
+/// webhooks of the change.
 ///
 /// # Arguments
 ///
+/// * `conn` - The database connection, within the correction's transaction.
+/// * `id` - The ID of the price.
+/// * `stored` - The price as stored, locked for the correction.
+/// * `basis` - The basis the corrected price is published on.
+/// * `correction` - The correction.
+/// * `author` - The user the correction is recorded as made by.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` if the correction was applied, or a database error.
+pub(crate) async fn apply_correction(
+    conn: &mut PgConnection,
+    id: i32,
+    stored: &StoredPrice,
+    basis: &PriceBasis,
+    correction: &PriceCorrection,
+    author: &str,
+) -> Result<(), sqlx::Error> {
+    let tiers = sqlx::query_as::<_, PriceTier>(
+        "SELECT min_liters, price FROM oil_price_tiers WHERE price_id = $1 ORDER BY min_liters",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO oil_price_revisions
+            (price_id, price, raw_price, raw_includes_vat, raw_per_liters, tiers, reason, revised_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(id)
+    .bind(stored.price)
+    .bind(stored.raw_price)
+    .bind(stored.raw_includes_vat)
+    .bind(stored.raw_per_liters)
+    .bind(serde_json::to_value(&tiers).unwrap_or_default())
+    .bind(&correction.reason)
+    .bind(author)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE oil_prices
+        SET price = $2, raw_price = $3, raw_includes_vat = $4, raw_per_liters = $5
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(basis.normalise(correction.price))
+    .bind(correction.price)
+    .bind(basis.includes_vat)
+    .bind(basis.per_liters)
+    .execute(&mut *conn)
+    .await?;
+
+    if let Some(tiers) = &correction.tiers {
+        sqlx::query("DELETE FROM oil_price_tiers WHERE price_id = $1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+
+        for tier in tiers {
+            sqlx::query(
+                "INSERT INTO oil_price_tiers (price_id, min_liters, price) VALUES ($1, $2, $3)",
+            )
+            .bind(id)
+            .bind(tier.min_liters)
+            .bind(basis.normalise(tier.price))
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+
+    mark_dirty(conn, stored.valid_from.date()).await?;
+
+    enqueue_event(
+        &mut *conn,
+        WebhookEvent::PriceCorrected,
+        json!({
+            "id": id,
+            "provider_id": stored.provider_id,
+            "product_id": stored.product_id,
+            "price": basis.normalise(correction.price),
+            "previous_price": stored.price,
+            "includes_vat": false,
+            "raw_price": correction.price,
+            "raw_includes_vat": basis.includes_vat,
+            "raw_per_liters": basis.per_liters,
+            "reason": correction.reason,
+        }),
+    )
+    .await
+}
+
+/// Fetches the revisions of a price, oldest first. Each revision holds the value the price had
+/// before a correction.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
 /// * `state` - The application state containing the database connection pool.
-/// * `id` - The ID of the provider.
-/// * `params` - The query parameters for filtering and pagination.
+/// * `id` - The ID of the price.
 ///
 /// # Returns
 ///
-/// * `Result<Json<Vec<PriceDetails>>, PricesError>` - The result of the operation, either a list of price details or an error.
-pub(crate) async fn fetch_prices_by_provider(
+/// * `Result<Json<Vec<PriceRevision>>, PricesError>` - The result of the operation, either a list of revisions or an error.
+#[utoipa::path(
+    get,
+    path = "/prices/{id}/revisions",
+    tag = "prices",
+    params(("id" = i32, Path, description = "The ID of the price")),
+    responses(
+        (status = 200, description = "The revisions of the price, oldest first", body = Vec<PriceRevision>),
+        (status = 500, description = "The revisions could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn fetch_price_revisions(
+    _claims: Claims,
     State(state): State<AppState>,
     Path(id): Path<i32>,
-    Query(params): Query<PriceQueryParams>,
-) -> Result<Json<Vec<PriceDetails>>, PricesError> {
-    let query = r#"
+) -> Result<Json<Vec<PriceRevision>>, PricesError> {
+    let rows = sqlx::query_as::<_, PriceRevision>(
+        r#"
         SELECT
-            oil_prices.price,
-            oil_prices.created_at
+            id, price, raw_price, raw_includes_vat, raw_per_liters, tiers, reason, revised_by,
+            revised_at
         FROM
-            oil_prices
+            oil_price_revisions
         WHERE
-            oil_prices.provider_id = $1
-            AND oil_prices.price IS NOT NULL
-            AND ($4 IS NULL OR oil_prices.created_at > $4)
-            AND ($5 IS NULL OR oil_prices.created_at < $5)
+            price_id = $1
         ORDER BY
-            oil_prices.created_at ASC
-        LIMIT $2
-        OFFSET $3;
-    "#;
-
-    let results = sqlx::query_as::<_, PriceDetails>(query)
-        .bind(id)
-        .bind(params.limit.unwrap_or(1000))
-        .bind(params.offset.unwrap_or(0))
-        .bind(params.start)
-        .bind(params.end)
-        .fetch_all(&state.db)
-        .await
-        .map_err(PricesError::fetch_error)?;
+            revised_at, id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(PricesError::fetch_error)?;
 
-    Ok(Json(results))
+    Ok(Json(rows))
 }
 
-/// Deletes a price from the database.
+/// Deletes a price.
+///
+/// The price is kept, marked as deleted by the authenticated user with an optional reason, and
+/// left out of prices, quotes and alerts until it is restored. The price before it stays in
+/// effect for as long as the deleted one was.
 ///
 /// # Arguments
 ///
-/// * `_claims` - The JWT claims of the authenticated user.
+/// * `claims` - The JWT claims of the authenticated user, recorded as the one who deleted the price.
 /// * `state` - The application state containing the database connection pool.
 /// * `id` - The ID of the price to delete.
+/// * `params` - The query parameters holding the reason for the deletion.
 ///
 /// # Returns
 ///
 /// * `Result<PricesSuccess, PricesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/prices/{id}",
+    tag = "prices",
+    params(("id" = i32, Path, description = "The ID of the price"), PriceDeleteParams),
+    responses(
+        (status = 200, description = "The price was deleted", body = MessageBody),
+        (status = 400, description = "The price is already deleted", body = ErrorBody),
+        (status = 404, description = "The price does not exist", body = ErrorBody),
+        (status = 500, description = "The price could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn delete_price(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<PriceDeleteParams>,
+) -> Result<PricesSuccess, PricesError> {
+    let mut tx = state.db.begin().await.map_err(PricesError::delete_error)?;
+
+    let stored = lock_price(&mut tx, id).await?;
+    if stored.deleted_at.is_some() {
+        return Err(PricesError::invalid_input("price is already deleted"));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE oil_prices op
+        SET valid_to = d.valid_to
+        FROM oil_prices d
+        WHERE d.id = $1
+          AND op.id = (
+            SELECT p.id
+            FROM oil_prices p
+            WHERE p.provider_id = d.provider_id
+              AND p.product_id = d.product_id
+              AND (p.valid_from, p.id) < (d.valid_from, d.id)
+              AND p.deleted_at IS NULL
+            ORDER BY p.valid_from DESC, p.id DESC
+            LIMIT 1
+          )
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(PricesError::delete_error)?;
+
+    sqlx::query(
+        r#"
+        UPDATE oil_prices
+        SET deleted_at = LOCALTIMESTAMP, deleted_by = $2, deletion_reason = $3
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(&claims.username)
+    .bind(&params.reason)
+    .execute(&mut *tx)
+    .await
+    .map_err(PricesError::delete_error)?;
+
+    mark_dirty(&mut tx, stored.valid_from.date())
+        .await
+        .map_err(PricesError::delete_error)?;
+
+    enqueue_event(
+        &mut *tx,
+        WebhookEvent::PriceDeleted,
+        json!({ "id": id, "reason": params.reason }),
+    )
+    .await
+    .map_err(PricesError::delete_error)?;
+
+    tx.commit().await.map_err(PricesError::delete_error)?;
+
+    Ok(PricesSuccess::deleted(id))
+}
+
+/// Restores a deleted price, putting it back into the intervals of its provider and product.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the price to restore.
+///
+/// # Returns
+///
+/// * `Result<PricesSuccess, PricesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/prices/{id}/restore",
+    tag = "prices",
+    params(("id" = i32, Path, description = "The ID of the price")),
+    responses(
+        (status = 200, description = "The price was restored", body = MessageBody),
+        (status = 400, description = "The price is not deleted", body = ErrorBody),
+        (status = 404, description = "The price does not exist", body = ErrorBody),
+        (status = 500, description = "The price could not be restored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn restore_price(
     _claims: Claims,
     State(state): State<AppState>,
     Path(id): Path<i32>,
 ) -> Result<PricesSuccess, PricesError> {
-    sqlx::query_as::<_, Prices>("SELECT * FROM oil_prices WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.db)
+    let mut tx = state.db.begin().await.map_err(PricesError::update_error)?;
+
+    let stored = lock_price(&mut tx, id).await?;
+    if stored.deleted_at.is_none() {
+        return Err(PricesError::invalid_input("price is not deleted"));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE oil_prices
+        SET deleted_at = NULL, deleted_by = NULL, deletion_reason = NULL
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(PricesError::update_error)?;
+
+    link_price_interval(&mut tx, id)
+        .await
+        .map_err(PricesError::update_error)?;
+    mark_dirty(&mut tx, stored.valid_from.date())
+        .await
+        .map_err(PricesError::update_error)?;
+
+    enqueue_event(&mut *tx, WebhookEvent::PriceRestored, json!({ "id": id }))
+        .await
+        .map_err(PricesError::update_error)?;
+
+    tx.commit().await.map_err(PricesError::update_error)?;
+
+    Ok(PricesSuccess::updated(id))
+}
+
+/// Fetches the changes of a provider's prices, most recent first.
+///
+/// Consecutive prices of a product that are equal are merged into one change, which lasts from
+/// the first of them until the next change and was last seen when the last of them was.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the provider.
+/// * `params` - The query parameters for filtering by product and date, limiting the number of changes and choosing whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<PriceChange>>, PricesError>` - The result of the operation, either a list of price changes or an error.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/prices/changes",
+    tag = "prices",
+    params(("id" = i32, Path, description = "The ID of the provider"), PriceChangeParams),
+    responses(
+        (status = 200, description = "The changes of the provider's prices, most recent first", body = Vec<PriceChange>),
+        (status = 500, description = "The changes could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_price_changes(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<PriceChangeParams>,
+) -> Result<Json<Vec<PriceChange>>, PricesError> {
+    let rows = sqlx::query_as::<_, PriceChange>(
+        r#"
+        WITH observed AS (
+            SELECT
+                op.*,
+                pr.slug AS product,
+                LAG(op.price) OVER (
+                    PARTITION BY op.product_id ORDER BY op.valid_from, op.id
+                ) AS previous_excl_vat
+            FROM
+                oil_prices op
+            JOIN
+                products pr ON op.product_id = pr.id
+            WHERE
+                op.provider_id = $1
+                AND op.deleted_at IS NULL
+                AND ($2::TEXT IS NULL OR pr.slug = $2)
+        ),
+        runs AS (
+            SELECT
+                observed.*,
+                COUNT(*) FILTER (WHERE previous_excl_vat IS DISTINCT FROM price) OVER (
+                    PARTITION BY product_id ORDER BY valid_from, id
+                ) AS run
+            FROM
+                observed
+        ),
+        changes AS (
+            SELECT
+                MIN(id) FILTER (WHERE previous_excl_vat IS DISTINCT FROM price) AS id,
+                product_id,
+                MIN(product) AS product,
+                MIN(price) AS price,
+                MIN(currency) AS currency,
+                MIN(unit) AS unit,
+                MIN(valid_from) AS valid_from,
+                (ARRAY_AGG(valid_to ORDER BY valid_from DESC, id DESC))[1] AS valid_to,
+                MAX(last_seen_at) AS last_seen_at
+            FROM
+                runs
+            GROUP BY
+                product_id, run
+        ),
+        priced AS (
+            SELECT
+                changes.*,
+                CASE
+                    WHEN $5 THEN ROUND(price * (1 + vat_rate_at(valid_from)), 4)
+                    ELSE price
+                END AS shown_price
+            FROM
+                changes
+        ),
+        linked AS (
+            SELECT
+                priced.*,
+                LAG(shown_price) OVER (
+                    PARTITION BY product_id ORDER BY valid_from, id
+                ) AS previous_price
+            FROM
+                priced
+        )
+        SELECT
+            id,
+            product_id,
+            product,
+            shown_price AS price,
+            previous_price,
+            shown_price - previous_price AS change,
+            currency,
+            unit,
+            $5 AS includes_vat,
+            valid_from,
+            valid_to,
+            last_seen_at
+        FROM
+            linked
+        WHERE
+            ($3::TIMESTAMP IS NULL OR valid_from > $3)
+            AND ($4::TIMESTAMP IS NULL OR valid_from < $4)
+        ORDER BY
+            valid_from DESC, id DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(id)
+    .bind(&params.product)
+    .bind(params.start)
+    .bind(params.end)
+    .bind(params.vat.includes_vat())
+    .bind(params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT))
+    .fetch_all(&state.db)
+    .await
+    .map_err(PricesError::fetch_error)?;
+
+    Ok(Json(rows))
+}
+
+/// Reconstructs a provider's price of a product as a time series with evenly spaced points.
+///
+/// Each point holds the price in effect at that time, or `null` before the first known price.
+/// The series defaults to the last 30 intervals and the default product.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the provider.
+/// * `params` - The query parameters choosing the product, the period, the interval between points and whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<PriceSeriesPoint>>, PricesError>` - The result of the operation, either the points of the series or an error.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/prices/series",
+    tag = "prices",
+    params(("id" = i32, Path, description = "The ID of the provider"), PriceSeriesParams),
+    responses(
+        (status = 200, description = "The price in effect at every point of the series", body = Vec<PriceSeriesPoint>),
+        (status = 400, description = "The period or the product is invalid, or the series too long", body = ErrorBody),
+        (status = 500, description = "The series could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_price_series(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<PriceSeriesParams>,
+) -> Result<Json<Vec<PriceSeriesPoint>>, PricesError> {
+    let end = params.end.unwrap_or_else(|| chrono::Utc::now().naive_utc());
+    let start = params
+        .start
+        .unwrap_or(end - params.interval.duration() * 30);
+    if start > end {
+        return Err(PricesError::invalid_input("start must be before end"));
+    }
+    let points = (end - start).num_seconds() / params.interval.duration().num_seconds() + 1;
+    if points > MAX_SERIES_POINTS {
+        return Err(PricesError::invalid_input(format!(
+            "a series may hold at most {} points",
+            MAX_SERIES_POINTS
+        )));
+    }
+
+    let (product_id, _) = fetch_product_by_slug(&state.db, params.product.as_deref())
         .await
         .map_err(PricesError::fetch_error)?
-        .ok_or_else(|| PricesError::fetch_error(sqlx::Error::RowNotFound))?;
+        .ok_or_else(|| PricesError::invalid_input("unknown product"))?;
+
+    let rows = sqlx::query_as::<_, PriceSeriesPoint>(
+        r#"
+        SELECT
+            s.at,
+            CASE
+                WHEN $6 THEN ROUND(p.price * (1 + vat_rate_at(s.at)), 4)
+                ELSE p.price
+            END AS price
+        FROM
+            generate_series($3::TIMESTAMP, $4::TIMESTAMP, CAST($5 AS INTERVAL)) AS s(at)
+        LEFT JOIN LATERAL (
+            SELECT op.price
+            FROM oil_prices op
+            WHERE op.provider_id = $1
+              AND op.product_id = $2
+              AND op.valid_from <= s.at
+              AND op.deleted_at IS NULL
+            ORDER BY op.valid_from DESC, op.id DESC
+            LIMIT 1
+        ) p ON TRUE
+        ORDER BY
+            s.at
+        "#,
+    )
+    .bind(id)
+    .bind(product_id)
+    .bind(start)
+    .bind(end)
+    .bind(params.interval.as_sql())
+    .bind(params.vat.includes_vat())
+    .fetch_all(&state.db)
+    .await
+    .map_err(PricesError::fetch_error)?;
+
+    Ok(Json(rows))
+}
 
-    sqlx::query("DELETE FROM oil_prices WHERE id = $1")
-        .bind(id)
-        .execute(&state.db)
+/// Compares the prices of several providers over a period.
+///
+/// The price of each provider is the one in effect at the start of each bucket, like in a price
+/// series, so gaps between prices are filled with the price before them. Providers without a
+/// price yet at a point are marked with `null` there and left out of its statistics. Buckets
+/// start on whole hours, days or weeks (Mondays), the first being the one holding the start of
+/// the period. The period defaults to the 30 buckets before now and the product to the default
+/// product.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `params` - The query parameters choosing the providers, the product, the period, the bucket size and whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Json<PriceComparison>, PricesError>` - The result of the operation, either the aligned prices with their statistics or an error.
+#[utoipa::path(
+    get,
+    path = "/prices/compare",
+    tag = "prices",
+    params(PriceCompareParams),
+    responses(
+        (status = 200, description = "The aligned prices of the providers with their statistics", body = PriceComparison),
+        (status = 400, description = "The providers, the period or the product are invalid, or the comparison too long", body = ErrorBody),
+        (status = 500, description = "The prices could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn compare_prices(
+    State(state): State<AppState>,
+    Query(params): Query<PriceCompareParams>,
+) -> Result<Json<PriceComparison>, PricesError> {
+    let provider_ids = params
+        .provider_ids()
+        .ok_or_else(|| PricesError::invalid_input("providers must be a list of provider IDs"))?;
+    if provider_ids.is_empty() || provider_ids.len() > MAX_COMPARED_PROVIDERS {
+        return Err(PricesError::invalid_input(format!(
+            "between 1 and {} providers may be compared",
+            MAX_COMPARED_PROVIDERS
+        )));
+    }
+
+    let end = params.end.unwrap_or_else(|| chrono::Utc::now().naive_utc());
+    let start = params.start.unwrap_or(end - params.bucket.duration() * 30);
+    if start > end {
+        return Err(PricesError::invalid_input("start must be before end"));
+    }
+    // The first bucket starts at the start of the bucket holding `start`, which may add a point.
+    let points = (end - start).num_seconds() / params.bucket.duration().num_seconds() + 2;
+    if points * provider_ids.len() as i64 > MAX_SERIES_POINTS {
+        return Err(PricesError::invalid_input(format!(
+            "a comparison may hold at most {} prices",
+            MAX_SERIES_POINTS
+        )));
+    }
+
+    let (product_id, _) = fetch_product_by_slug(&state.db, params.product.as_deref())
         .await
-        .map_err(PricesError::delete_error)?;
+        .map_err(PricesError::fetch_error)?
+        .ok_or_else(|| PricesError::invalid_input("unknown product"))?;
 
-    Ok(PricesSuccess::deleted(id))
+    let names =
+        sqlx::query_as::<_, (i32, String)>("SELECT id, name FROM providers WHERE id = ANY($1)")
+            .bind(&provider_ids)
+            .fetch_all(&state.db)
+            .await
+            .map_err(PricesError::fetch_error)?;
+    let mut providers = Vec::with_capacity(provider_ids.len());
+    for id in &provider_ids {
+        let name = names
+            .iter()
+            .find(|(provider_id, _)| provider_id == id)
+            .map(|(_, name)| name.clone())
+            .ok_or_else(|| PricesError::invalid_input(format!("unknown provider {}", id)))?;
+        providers.push(ComparedProvider {
+            provider_id: *id,
+            name,
+            priced: 0,
+            cheapest: 0,
+            average_price: None,
+            min_price: None,
+            max_price: None,
+            average_premium: None,
+        });
+    }
+
+    let rows = sqlx::query_as::<_, (NaiveDateTime, i32, Option<Decimal>)>(
+        r#"
+        SELECT
+            s.at,
+            pr.id,
+            CASE
+                WHEN $6 THEN ROUND(p.price * (1 + vat_rate_at(s.at)), 4)
+                ELSE p.price
+            END AS price
+        FROM
+            generate_series(
+                date_trunc($7, $3::TIMESTAMP), $4::TIMESTAMP, CAST($5 AS INTERVAL)
+            ) AS s(at)
+        CROSS JOIN
+            UNNEST($1::INT[]) AS pr(id)
+        LEFT JOIN LATERAL (
+            SELECT op.price
+            FROM oil_prices op
+            WHERE op.provider_id = pr.id
+              AND op.product_id = $2
+              AND op.valid_from <= s.at
+              AND op.deleted_at IS NULL
+            ORDER BY op.valid_from DESC, op.id DESC
+            LIMIT 1
+        ) p ON TRUE
+        ORDER BY
+            s.at
+        "#,
+    )
+    .bind(&provider_ids)
+    .bind(product_id)
+    .bind(start)
+    .bind(end)
+    .bind(params.bucket.as_sql())
+    .bind(params.vat.includes_vat())
+    .bind(params.bucket.as_sql_field())
+    .fetch_all(&state.db)
+    .await
+    .map_err(PricesError::fetch_error)?;
+
+    let mut points: Vec<ComparisonPoint> = Vec::new();
+    for (at, provider_id, price) in rows {
+        if points.last().is_none_or(|point| point.at != at) {
+            points.push(ComparisonPoint {
+                at,
+                prices: vec![None; provider_ids.len()],
+                spread: None,
+            });
+        }
+        if let (Some(point), Some(index)) = (
+            points.last_mut(),
+            provider_ids.iter().position(|id| *id == provider_id),
+        ) {
+            point.prices[index] = price;
+        }
+    }
+
+    let mut totals = vec![Decimal::ZERO; providers.len()];
+    let mut premiums = vec![(Decimal::ZERO, 0usize); providers.len()];
+    let mut spreads = (Decimal::ZERO, 0usize);
+    for point in &mut points {
+        let priced: Vec<(usize, Decimal)> = point
+            .prices
+            .iter()
+            .enumerate()
+            .filter_map(|(index, price)| price.map(|price| (index, price)))
+            .collect();
+        let (Some(cheapest), Some(dearest)) = (
+            priced.iter().map(|(_, price)| *price).min(),
+            priced.iter().map(|(_, price)| *price).max(),
+        ) else {
+            continue;
+        };
+
+        for (index, price) in &priced {
+            let provider = &mut providers[*index];
+            provider.priced += 1;
+            totals[*index] += price;
+            provider.min_price = Some(provider.min_price.map_or(*price, |min| min.min(*price)));
+            provider.max_price = Some(provider.max_price.map_or(*price, |max| max.max(*price)));
+            if *price == cheapest {
+                provider.cheapest += 1;
+            }
+            if priced.len() > 1 {
+                premiums[*index].0 += price - cheapest;
+                premiums[*index].1 += 1;
+            }
+        }
+
+        if priced.len() > 1 {
+            point.spread = Some(dearest - cheapest);
+            spreads.0 += dearest - cheapest;
+            spreads.1 += 1;
+        }
+    }
+
+    for (index, provider) in providers.iter_mut().enumerate() {
+        if provider.priced > 0 {
+            provider.average_price =
+                Some((totals[index] / Decimal::from(provider.priced)).round_dp(4));
+        }
+        let (premium, count) = premiums[index];
+        if count > 0 {
+            provider.average_premium = Some((premium / Decimal::from(count)).round_dp(4));
+        }
+    }
+
+    Ok(Json(PriceComparison {
+        product_id,
+        includes_vat: params.vat.includes_vat(),
+        providers,
+        average_spread: (spreads.1 > 0).then(|| (spreads.0 / Decimal::from(spreads.1)).round_dp(4)),
+        points,
+    }))
+}
+
+/// Fetches the volume tiers of a price from the database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the price.
+/// * `params` - The query parameters choosing whether prices include VAT.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<PriceTier>>, PricesError>` - The result of the operation, either a list of volume tiers or an error.
+#[utoipa::path(
+    get,
+    path = "/prices/{id}/tiers",
+    tag = "prices",
+    params(("id" = i32, Path, description = "The ID of the price"), VatQueryParams),
+    responses(
+        (status = 200, description = "The volume tiers of the price", body = Vec<PriceTier>),
+        (status = 500, description = "The tiers could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_price_tiers(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<VatQueryParams>,
+) -> Result<Json<Vec<PriceTier>>, PricesError> {
+    let rows = sqlx::query_as::<_, PriceTier>(
+        r#"
+        SELECT
+            t.min_liters,
+            CASE
+                WHEN $2 THEN ROUND(t.price * (1 + vat_rate_at(op.created_at)), 4)
+                ELSE t.price
+            END AS price
+        FROM
+            oil_price_tiers t
+        JOIN
+            oil_prices op ON t.price_id = op.id
+        WHERE
+            t.price_id = $1
+        ORDER BY
+            t.min_liters
+        "#,
+    )
+    .bind(id)
+    .bind(params.vat.includes_vat())
+    .fetch_all(&state.db)
+    .await
+    .map_err(PricesError::fetch_error)?;
+
+    Ok(Json(rows))
+}
+
+/// Streams newly inserted prices as Server-Sent Events.
+///
+/// Each price is sent as a `price` event with the price's ID as event ID. Subscribers that fall
+/// too far behind skip the events they missed.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the live event bus.
+/// * `params` - The query parameters for filtering by provider, zone and product.
+///
+/// # Returns
+///
+/// * `Sse<impl Stream<Item = Result<Event, Infallible>>>` - The event stream.
+#[utoipa::path(
+    get,
+    path = "/prices/stream",
+    tag = "prices",
+    params(PriceStreamParams),
+    responses(
+        (status = 200, description = "A stream of `price` events, each holding a newly inserted price as JSON", content(
+            (PriceEvent = "text/event-stream"),
+        )),
+    )
+)]
+pub(crate) async fn stream_prices(
+    State(state): State<AppState>,
+    Query(params): Query<PriceStreamParams>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(state.events.subscribe()).filter_map(move |event| {
+        let LiveEvent::Price(event) = event.ok()? else {
+            return None;
+        };
+        if !params.matches(&event) {
+            return None;
+        }
+        Event::default()
+            .event("price")
+            .id(event.id.to_string())
+            .json_data(&event)
+            .ok()
+            .map(Ok)
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
 }
@@ -0,0 +1,305 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::errors::{ErrorBody, MessageBody, ProductsError, ProductsSuccess};
+use crate::helpers::{is_foreign_key_violation, is_unique_violation};
+use crate::models::products::{
+    ProductAdd, Products, ProductsInsertResponse, ProviderProductAdd, ProviderProducts,
+};
+use axum::extract::{Path, State};
+use axum::Json;
+
+/// Creates a new product in the database.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `json` - The JSON payload containing the product details.
+///
+/// # Returns
+///
+/// * `Result<ProductsSuccess, ProductsError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/products",
+    tag = "products",
+    request_body = ProductAdd,
+    responses(
+        (status = 201, description = "The product was created", body = MessageBody),
+        (status = 409, description = "The slug is already taken", body = ErrorBody),
+        (status = 500, description = "The product could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn create_product(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<ProductAdd>,
+) -> Result<ProductsSuccess, ProductsError> {
+    let row: ProductsInsertResponse = sqlx::query_as::<_, ProductsInsertResponse>(
+        "INSERT INTO products (slug, name, description) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(json.slug)
+    .bind(json.name)
+    .bind(json.description)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| {
+        if is_unique_violation(&e) {
+            ProductsError::conflict("a product with the slug already exists")
+        } else {
+            ProductsError::insert_error(e)
+        }
+    })?;
+
+    Ok(ProductsSuccess::created(row.id))
+}
+
+/// Fetches all products from the database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<Products>>, ProductsError>` - The result of the operation, either a list of products or an error.
+#[utoipa::path(
+    get,
+    path = "/products",
+    tag = "products",
+    responses(
+        (status = 200, description = "All products", body = Vec<Products>),
+        (status = 500, description = "The products could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_products(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Products>>, ProductsError> {
+    let res = sqlx::query_as::<_, Products>(
+        "SELECT id, slug, name, description FROM products ORDER BY id",
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(ProductsError::fetch_error)?;
+
+    Ok(Json(res))
+}
+
+/// Deletes a product from the database.
+///
+/// Products that still have prices recorded against them cannot be deleted and are answered with
+/// `409 Conflict`.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the product to delete.
+///
+/// # Returns
+///
+/// * `Result<ProductsSuccess, ProductsError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/products/{id}",
+    tag = "products",
+    params(("id" = i32, Path, description = "The ID of the product")),
+    responses(
+        (status = 200, description = "The product was deleted", body = MessageBody),
+        (status = 404, description = "The product does not exist", body = ErrorBody),
+        (status = 409, description = "Prices are still recorded against the product", body = ErrorBody),
+        (status = 500, description = "The product could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn delete_product(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<ProductsSuccess, ProductsError> {
+    let res = sqlx::query("DELETE FROM products WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(|e| {
+            if is_foreign_key_violation(&e) {
+                ProductsError::conflict("prices are still recorded against the product")
+            } else {
+                ProductsError::delete_error(e)
+            }
+        })?;
+
+    if res.rows_affected() == 0 {
+        return Err(ProductsError::not_found());
+    }
+
+    Ok(ProductsSuccess::deleted(id))
+}
+
+/// Fetches the per-product extraction rules of a provider from the database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the provider.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<ProviderProducts>>, ProductsError>` - The result of the operation, either a list of extraction rules or an error.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/products",
+    tag = "products",
+    params(("id" = i32, Path, description = "The ID of the provider")),
+    responses(
+        (status = 200, description = "The extraction rules of the provider's products", body = Vec<ProviderProducts>),
+        (status = 500, description = "The extraction rules could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_provider_products(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<ProviderProducts>>, ProductsError> {
+    let res = sqlx::query_as::<_, ProviderProducts>(
+        r#"
+        SELECT
+            pp.provider_id, pp.product_id, p.slug, p.name, pp.html_element, pp.includes_vat,
+            pp.per_liters
+        FROM
+            provider_products pp
+        JOIN
+            products p ON pp.product_id = p.id
+        WHERE
+            pp.provider_id = $1
+        ORDER BY
+            pp.product_id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(ProductsError::fetch_error)?;
+
+    Ok(Json(res))
+}
+
+/// Creates or replaces the extraction rule of a product for a provider.
+///
+/// The rule also declares the basis the provider publishes the price on. Unless stated otherwise
+/// prices are assumed to be per liter including VAT.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `provider_id` - The ID of the provider.
+/// * `product_id` - The ID of the product.
+/// * `json` - The JSON payload containing the extraction rule.
+///
+/// # Returns
+///
+/// * `Result<ProductsSuccess, ProductsError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    put,
+    path = "/providers/{id}/products/{product_id}",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "The ID of the provider"),
+        ("product_id" = i32, Path, description = "The ID of the product"),
+    ),
+    request_body = ProviderProductAdd,
+    responses(
+        (status = 200, description = "The extraction rule was stored", body = MessageBody),
+        (status = 400, description = "The extraction rule is invalid", body = ErrorBody),
+        (status = 404, description = "The provider or the product does not exist", body = ErrorBody),
+        (status = 500, description = "The extraction rule could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn upsert_provider_product(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path((provider_id, product_id)): Path<(i32, i32)>,
+    Json(json): Json<ProviderProductAdd>,
+) -> Result<ProductsSuccess, ProductsError> {
+    if json.per_liters.is_some_and(|per_liters| per_liters <= 0) {
+        return Err(ProductsError::invalid_input(
+            "per_liters must be greater than zero",
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO provider_products (provider_id, product_id, html_element, includes_vat, per_liters)
+        VALUES ($1, $2, $3, COALESCE($4, TRUE), COALESCE($5, 1))
+        ON CONFLICT (provider_id, product_id) DO UPDATE SET
+            html_element = EXCLUDED.html_element,
+            includes_vat = EXCLUDED.includes_vat,
+            per_liters = EXCLUDED.per_liters
+        "#,
+    )
+    .bind(provider_id)
+    .bind(product_id)
+    .bind(json.html_element)
+    .bind(json.includes_vat)
+    .bind(json.per_liters)
+    .execute(&state.db)
+    .await
+    .map_err(|e| {
+        if is_foreign_key_violation(&e) {
+            ProductsError::unknown("provider or product")
+        } else {
+            ProductsError::update_error(e)
+        }
+    })?;
+
+    Ok(ProductsSuccess::updated(product_id))
+}
+
+/// Deletes the extraction rule of a product for a provider.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `provider_id` - The ID of the provider.
+/// * `product_id` - The ID of the product.
+///
+/// # Returns
+///
+/// * `Result<ProductsSuccess, ProductsError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/providers/{id}/products/{product_id}",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "The ID of the provider"),
+        ("product_id" = i32, Path, description = "The ID of the product"),
+    ),
+    responses(
+        (status = 200, description = "The extraction rule was deleted", body = MessageBody),
+        (status = 404, description = "The provider has no extraction rule for the product", body = ErrorBody),
+        (status = 500, description = "The extraction rule could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn delete_provider_product(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path((provider_id, product_id)): Path<(i32, i32)>,
+) -> Result<ProductsSuccess, ProductsError> {
+    let res =
+        sqlx::query("DELETE FROM provider_products WHERE provider_id = $1 AND product_id = $2")
+            .bind(provider_id)
+            .bind(product_id)
+            .execute(&state.db)
+            .await
+            .map_err(ProductsError::delete_error)?;
+
+    if res.rows_affected() == 0 {
+        return Err(ProductsError::not_found());
+    }
+
+    Ok(ProductsSuccess::deleted(product_id))
+}
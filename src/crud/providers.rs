@@ -1,15 +1,24 @@
 use crate::app_state::AppState;
 use crate::auth::jwt::Claims;
-use crate::errors::{ProvidersError, ProvidersSuccess};
-use crate::helpers::{provider_exists, zone_exists};
-use crate::models::delivery_zones::{DeliveryZoneProviderAdd, DeliveryZones};
+use crate::errors::{ErrorBody, MessageBody, ProvidersError, ProvidersSuccess};
+use crate::helpers::fetch_product_by_slug;
+use crate::models::delivery_zones::{DeliveryZoneProviderAdd, DeliveryZoneProviderAddResponse};
+use crate::models::live_events::{LiveEvent, ScrapingRunEvent};
+use crate::models::products::ProviderProductSummary;
 use crate::models::providers::{
-    ProviderAdd, ProviderIds, ProviderWithZones, ProviderZoneRow, Providers,
-    ProvidersInsertResponse,
+    ProviderAdd, ProviderHealth, ProviderIds, ProviderInclude, ProviderIncludeParams,
+    ProviderLatestPrice, ProviderListParams, ProviderListing, ProviderSort, ProviderZoneRow,
+    Providers, ProvidersInsertResponse,
 };
-use axum::extract::{Path, State};
+use crate::models::webhooks::WebhookEvent;
+use crate::pagination::{Envelope, Enveloped, PageParams, Paginated};
+use crate::webhooks::events::enqueue_event;
+use axum::extract::{OriginalUri, Path, Query, State};
 use axum::http::StatusCode;
 use axum::Json;
+use chrono::{NaiveDateTime, Utc};
+use serde_json::json;
+use sqlx::{Postgres, QueryBuilder};
 use std::collections::HashMap;
 
 /// Creates a new provider in the database.
@@ -23,6 +32,17 @@ use std::collections::HashMap;
 /// # Returns
 ///
 /// * `Result<ProvidersSuccess, ProvidersError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/providers",
+    tag = "providers",
+    request_body = ProviderAdd,
+    responses(
+        (status = 201, description = "The provider was created", body = MessageBody),
+        (status = 500, description = "The provider could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn create_provider(
     _claims: Claims,
     State(state): State<AppState>,
@@ -43,6 +63,10 @@ pub(crate) async fn create_provider(
 
 /// Adds delivery zones to a provider in the database.
 ///
+/// All zones are validated with a single query and linked inside one transaction, so either
+/// every known zone is linked or nothing is. Zones that are already linked are left untouched
+/// and unknown zone IDs are reported back instead of failing the whole request.
+///
 /// # Arguments
 ///
 /// * `_claims` - The JWT claims of the authenticated user.
@@ -52,53 +76,154 @@ pub(crate) async fn create_provider(
 ///
 /// # Returns
 ///
-/// * `Result<ProvidersSuccess, ProvidersError>` - The result of the operation, either a success or an error.
+/// * `Result<Json<DeliveryZoneProviderAddResponse>, ProvidersError>` - The result of the operation, either the zones that were added, already present or unknown, or an error.
+#[utoipa::path(
+    post,
+    path = "/providers/{id}/zones",
+    tag = "providers",
+    params(("id" = i32, Path, description = "The ID of the provider")),
+    request_body = DeliveryZoneProviderAdd,
+    responses(
+        (status = 200, description = "The zones that were added, already present or unknown", body = DeliveryZoneProviderAddResponse),
+        (status = 404, description = "The provider does not exist", body = ErrorBody),
+        (status = 500, description = "The zones could not be added", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn add_delivery_zones_to_provider(
     _claims: Claims,
     State(state): State<AppState>,
     Path(id): Path<i32>,
     Json(json): Json<DeliveryZoneProviderAdd>,
-) -> Result<ProvidersSuccess, ProvidersError> {
-    if !provider_exists(id, &state.db).await? {
-        return Err(ProvidersError::fetch_error(sqlx::Error::RowNotFound));
-    }
+) -> Result<Json<DeliveryZoneProviderAddResponse>, ProvidersError> {
+    let mut zone_ids = json.zone_ids;
+    zone_ids.sort_unstable();
+    zone_ids.dedup();
 
-    for zone_id in &json.zone_ids {
-        if !zone_exists(*zone_id, &state.db).await? {
-            return Err(ProvidersError::fetch_error(sqlx::Error::RowNotFound));
-        }
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(ProvidersError::insert_error)?;
 
-        sqlx::query("INSERT INTO delivery_zone (provider_id, zone_id) VALUES ($1, $2)")
-            .bind(id)
-            .bind(zone_id)
-            .execute(&state.db)
-            .await
-            .map_err(ProvidersError::insert_error)?;
-    }
+    // Lock the provider so it cannot be deleted while the zones are being linked
+    sqlx::query_as::<_, (i32,)>("SELECT id FROM providers WHERE id = $1 FOR SHARE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(ProvidersError::fetch_error)?
+        .ok_or_else(ProvidersError::not_found)?;
 
-    Ok(ProvidersSuccess::updated(id))
+    let known: Vec<i32> = sqlx::query_scalar::<_, i32>(
+        "SELECT id FROM delivery_zones WHERE id = ANY($1) ORDER BY id",
+    )
+    .bind(&zone_ids)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(ProvidersError::fetch_error)?;
+
+    let mut added: Vec<i32> = sqlx::query_scalar::<_, i32>(
+        r#"
+        INSERT INTO provider_delivery_zones (provider_id, zone_id)
+        SELECT $1, zone_id FROM UNNEST($2::INT[]) AS zone_id
+        ON CONFLICT (provider_id, zone_id) DO NOTHING
+        RETURNING zone_id
+        "#,
+    )
+    .bind(id)
+    .bind(&known)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(ProvidersError::insert_error)?;
+
+    tx.commit().await.map_err(ProvidersError::insert_error)?;
+
+    added.sort_unstable();
+
+    let already_present = known
+        .iter()
+        .filter(|zone_id| added.binary_search(zone_id).is_err())
+        .copied()
+        .collect();
+    let unknown = zone_ids
+        .into_iter()
+        .filter(|zone_id| known.binary_search(zone_id).is_err())
+        .collect();
+
+    Ok(Json(DeliveryZoneProviderAddResponse {
+        provider_id: id,
+        added,
+        already_present,
+        unknown,
+    }))
 }
 
-/// Fetches the IDs of all providers from the database.
+/// Fetches the IDs of providers from the database.
+///
+/// The scraper reads the whole list in one request, so every provider is returned unless a page
+/// is asked for with `limit` or `cursor`. The next page is then linked in the `Link` and
+/// `X-Next-Cursor` response headers.
 ///
 /// # Arguments
 ///
 /// * `_claims` - The JWT claims of the authenticated user.
 /// * `state` - The application state containing the database connection pool.
+/// * `uri` - The URI of the request, used to link to the next page.
+/// * `page` - The pagination and sorting parameters.
+/// * `params` - The query parameters for filtering by zone.
 ///
 /// # Returns
 ///
-/// * `Result<Json<Vec<ProviderIds>>, ProvidersError>` - The result of the operation, either a list of provider IDs or an error.
+/// * `Result<Paginated<ProviderIds>, ProvidersError>` - The result of the operation, either the provider IDs or an error.
+#[utoipa::path(
+    get,
+    path = "/scraping_runs/providers",
+    tag = "scraping runs",
+    params(
+        PageParams<ProviderSort>,
+        ("sort" = Option<ProviderSort>, Query, description = "The column to sort by"),
+        ProviderListParams,
+    ),
+    responses(
+        (status = 200, description = "All providers to scrape, or a page of them with the next page linked in the `Link` and `X-Next-Cursor` headers", body = Vec<ProviderIds>),
+        (status = 400, description = "The cursor is malformed", body = ErrorBody),
+        (status = 500, description = "The providers could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn fetch_providers_ids(
     _claims: Claims,
     State(state): State<AppState>,
-) -> Result<Json<Vec<ProviderIds>>, ProvidersError> {
-    let res = sqlx::query_as::<_, ProviderIds>("SELECT id,last_accessed FROM providers")
+    OriginalUri(uri): OriginalUri,
+    Query(page): Query<PageParams<ProviderSort>>,
+    Query(params): Query<ProviderListParams>,
+) -> Result<Paginated<ProviderIds>, ProvidersError> {
+    let mut query = provider_list_query(
+        "SELECT id, name, created_at, last_accessed FROM providers",
+        &params,
+    );
+    if page.is_requested() {
+        page.push_page(&mut query)
+            .map_err(ProvidersError::invalid_input)?;
+    } else {
+        page.push_order(&mut query);
+    }
+
+    let res = query
+        .build_query_as::<ProviderIds>()
         .fetch_all(&state.db)
         .await
         .map_err(ProvidersError::fetch_error)?;
 
-    Ok(Json(res))
+    if !page.is_requested() {
+        return Ok(Paginated {
+            items: res,
+            next_cursor: None,
+            uri,
+        });
+    }
+
+    Ok(page.paginate(res, uri))
 }
 
 /// Fetches a provider by ID from the database.
@@ -112,6 +237,17 @@ pub(crate) async fn fetch_providers_ids(
 /// # Returns
 ///
 /// * `Result<Json<Providers>>, ProvidersError>` - The result of the operation, either the provider details or an error.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}",
+    tag = "providers",
+    params(("id" = i32, Path, description = "The ID of the provider")),
+    responses(
+        (status = 200, description = "The provider", body = Providers),
+        (status = 500, description = "The provider does not exist or could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn fetch_provider(
     _claims: Claims,
     State(state): State<AppState>,
@@ -137,80 +273,286 @@ pub(crate) async fn fetch_provider(
 /// # Returns
 ///
 /// * `Result<StatusCode, ProvidersError>` - The result of the operation, either a success status code or an error.
+#[utoipa::path(
+    put,
+    path = "/providers/{id}",
+    tag = "providers",
+    params(("id" = i32, Path, description = "The ID of the provider. The ID in the body is the one updated")),
+    request_body = Providers,
+    responses(
+        (status = 200, description = "The provider was updated"),
+        (status = 500, description = "The provider could not be updated", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn update_provider(
     _claims: Claims,
     State(state): State<AppState>,
     Json(json): Json<Providers>,
 ) -> Result<StatusCode, ProvidersError> {
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(ProvidersError::update_error)?;
+
     sqlx::query("UPDATE providers SET name = $1, url = $2, html_element = $3 WHERE id = $4")
-        .bind(json.name)
-        .bind(json.url)
-        .bind(json.html_element)
+        .bind(&json.name)
+        .bind(&json.url)
+        .bind(&json.html_element)
         .bind(json.id)
-        .execute(&state.db)
+        .execute(&mut *tx)
         .await
         .map_err(ProvidersError::update_error)?;
 
+    enqueue_event(
+        &mut *tx,
+        WebhookEvent::ProviderUpdated,
+        json!({
+            "id": json.id,
+            "name": json.name,
+            "url": json.url,
+            "html_element": json.html_element,
+        }),
+    )
+    .await
+    .map_err(ProvidersError::update_error)?;
+
+    tx.commit().await.map_err(ProvidersError::update_error)?;
+
     Ok(StatusCode::OK)
 }
 
-/// Fetches all providers with their associated delivery zones from the database.
+/// Builds the query listing providers that match a set of filters.
+///
+/// # Arguments
+///
+/// * `select` - The query selecting the provider columns to list.
+/// * `params` - The filters.
+///
+/// # Returns
+///
+/// * `QueryBuilder<Postgres>` - The query, ready for the page to be appended.
+fn provider_list_query<'a>(
+    select: &str,
+    params: &ProviderListParams,
+) -> QueryBuilder<'a, Postgres> {
+    let mut query = QueryBuilder::new(format!("SELECT * FROM ({}) AS page WHERE TRUE", select));
+
+    if let Some(zone_id) = params.zone_id {
+        query
+            .push(
+                " AND EXISTS (SELECT 1 FROM provider_delivery_zones pdz \
+                 WHERE pdz.provider_id = page.id AND pdz.zone_id = ",
+            )
+            .push_bind(zone_id)
+            .push(")");
+    }
+
+    query
+}
+
+/// Fetches a page of providers with their latest price and health from the database.
+///
+/// Delivery zones are included unless `include` says otherwise, and the products a provider is
+/// scraped for are included on request, e.g. `?include=zones,products`. The latest price is the
+/// one in effect for the `product` slug, heating oil by default, while the health is derived from
+/// prices of any product. The page is wrapped in an envelope holding the cursor and link of the
+/// next page.
 ///
 /// # Arguments
 ///
 /// * `state` - The application state containing the database connection pool.
+/// * `uri` - The URI of the request, used to link to the next page.
+/// * `page` - The pagination and sorting parameters.
+/// * `params` - The query parameters for filtering by zone.
+/// * `expand` - The query parameters choosing the related data to include, the product of the latest prices and whether they include VAT.
 ///
 /// # Returns
 ///
-/// * `Result<Json<Vec<ProviderWithZones>>, ProvidersError>` - The result of the operation, either a list of providers with their zones or an error.
+/// * `Result<Enveloped<ProviderListing>, ProvidersError>` - The result of the operation, either a page of providers or an error.
+#[utoipa::path(
+    get,
+    path = "/providers",
+    tag = "providers",
+    params(
+        PageParams<ProviderSort>,
+        ("sort" = Option<ProviderSort>, Query, description = "The column to sort by"),
+        ProviderListParams,
+        ProviderIncludeParams,
+    ),
+    responses(
+        (status = 200, description = "A page of providers with their latest price and health", body = Envelope<ProviderListing>),
+        (status = 400, description = "The cursor, the included data or the product is invalid", body = ErrorBody),
+        (status = 500, description = "The providers could not be fetched", body = ErrorBody),
+    )
+)]
 pub(crate) async fn fetch_providers_with_zones(
     State(state): State<AppState>,
-) -> Result<Json<Vec<ProviderWithZones>>, ProvidersError> {
-    let rows = sqlx::query_as::<_, ProviderZoneRow>(
+    OriginalUri(uri): OriginalUri,
+    Query(page): Query<PageParams<ProviderSort>>,
+    Query(params): Query<ProviderListParams>,
+    Query(expand): Query<ProviderIncludeParams>,
+) -> Result<Enveloped<ProviderListing>, ProvidersError> {
+    let include = match &expand.include {
+        Some(include) => ProviderInclude::parse_list(include).ok_or_else(|| {
+            ProvidersError::invalid_input("include may only list zones and products")
+        })?,
+        None => vec![ProviderInclude::Zones],
+    };
+    let (product_id, _) = fetch_product_by_slug(&state.db, expand.product.as_deref())
+        .await
+        .map_err(ProvidersError::fetch_error)?
+        .ok_or_else(|| ProvidersError::invalid_input("unknown product"))?;
+
+    let mut query = provider_list_query(
+        "SELECT id, name, url, created_at, last_updated FROM providers",
+        &params,
+    );
+    page.push_page(&mut query)
+        .map_err(ProvidersError::invalid_input)?;
+
+    let mut providers = query
+        .build_query_as::<ProviderListing>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(ProvidersError::fetch_error)?;
+
+    let provider_ids: Vec<i32> = providers.iter().map(|provider| provider.id).collect();
+    let positions: HashMap<i32, usize> = provider_ids
+        .iter()
+        .enumerate()
+        .map(|(position, &id)| (id, position))
+        .collect();
+
+    let latest_prices = sqlx::query_as::<_, ProviderLatestPrice>(
         r#"
-        SELECT
-            p.id as provider_id, p.name as provider_name, p.url, p.html_element,
-            p.created_at, p.last_updated, p.last_accessed,
-            z.id as zone_id, z.name as zone_name, z.description
+        SELECT DISTINCT ON (op.provider_id)
+            op.provider_id,
+            op.id,
+            op.product_id,
+            pr.slug AS product,
+            CASE
+                WHEN $2 THEN ROUND(op.price * (1 + vat_rate_at(op.created_at)), 4)
+                ELSE op.price
+            END AS price,
+            op.currency,
+            op.unit,
+            $2 AS includes_vat,
+            op.created_at
         FROM
-            providers p
-        LEFT JOIN
-            provider_delivery_zones pz ON p.id = pz.provider_id
-        LEFT JOIN
-            delivery_zones z ON pz.zone_id = z.id
+            oil_prices op
+        JOIN
+            products pr ON op.product_id = pr.id
+        WHERE
+            op.provider_id = ANY($1)
+            AND op.product_id = $3
+            AND op.deleted_at IS NULL
         ORDER BY
-            p.id
+            op.provider_id, op.valid_from DESC, op.id DESC
         "#,
     )
+    .bind(&provider_ids)
+    .bind(expand.vat.includes_vat())
+    .bind(product_id)
     .fetch_all(&state.db)
     .await
     .map_err(ProvidersError::fetch_error)?;
 
-    let mut providers_map: HashMap<i32, ProviderWithZones> = HashMap::new();
-
-    for row in rows {
-        let provider_entry =
-            providers_map
-                .entry(row.provider_id)
-                .or_insert_with(|| ProviderWithZones {
-                    id: row.provider_id,
-                    name: row.provider_name,
-                    url: row.url,
-                    created_at: row.created_at,
-                    last_updated: row.last_updated,
-                    zones: vec![],
-                });
-
-        if let Some(zone_id) = row.zone_id {
-            provider_entry.zones.push(DeliveryZones {
-                id: zone_id,
-                name: row.zone_name.unwrap_or_default(),
-                description: row.description.unwrap(),
-            });
+    let last_seen: HashMap<i32, NaiveDateTime> = sqlx::query_as::<_, (i32, NaiveDateTime)>(
+        r#"
+        SELECT provider_id, MAX(last_seen_at)
+        FROM oil_prices
+        WHERE provider_id = ANY($1) AND deleted_at IS NULL
+        GROUP BY provider_id
+        "#,
+    )
+    .bind(&provider_ids)
+    .fetch_all(&state.db)
+    .await
+    .map_err(ProvidersError::fetch_error)?
+    .into_iter()
+    .collect();
+
+    for price in latest_prices {
+        if let Some(&position) = positions.get(&price.provider_id) {
+            providers[position].latest_price = Some(price);
+        }
+    }
+
+    let now = Utc::now().naive_utc();
+    for provider in providers.iter_mut() {
+        let last_price_at = last_seen.get(&provider.id).copied();
+        provider.health = ProviderHealth::from_last_price(last_price_at, now);
+    }
+
+    if include.contains(&ProviderInclude::Zones) {
+        let rows = sqlx::query_as::<_, ProviderZoneRow>(
+            r#"
+            SELECT
+                pz.provider_id, z.id, z.name, z.description, z.created_at
+            FROM
+                provider_delivery_zones pz
+            JOIN
+                delivery_zones z ON pz.zone_id = z.id
+            WHERE
+                pz.provider_id = ANY($1)
+            ORDER BY
+                z.id
+            "#,
+        )
+        .bind(&provider_ids)
+        .fetch_all(&state.db)
+        .await
+        .map_err(ProvidersError::fetch_error)?;
+
+        for provider in providers.iter_mut() {
+            provider.zones = Some(vec![]);
+        }
+        for row in rows {
+            if let Some(&position) = positions.get(&row.provider_id) {
+                providers[position]
+                    .zones
+                    .get_or_insert_with(Vec::new)
+                    .push(row.zone);
+            }
         }
     }
 
-    Ok(Json(providers_map.into_values().collect()))
+    if include.contains(&ProviderInclude::Products) {
+        let rows = sqlx::query_as::<_, ProviderProductSummary>(
+            r#"
+            SELECT
+                pp.provider_id, pp.product_id, p.slug, p.name
+            FROM
+                provider_products pp
+            JOIN
+                products p ON pp.product_id = p.id
+            WHERE
+                pp.provider_id = ANY($1)
+            ORDER BY
+                pp.product_id
+            "#,
+        )
+        .bind(&provider_ids)
+        .fetch_all(&state.db)
+        .await
+        .map_err(ProvidersError::fetch_error)?;
+
+        for provider in providers.iter_mut() {
+            provider.products = Some(vec![]);
+        }
+        for row in rows {
+            if let Some(&position) = positions.get(&row.provider_id) {
+                providers[position]
+                    .products
+                    .get_or_insert_with(Vec::new)
+                    .push(row);
+            }
+        }
+    }
+
+    Ok(page.paginate(providers, uri).enveloped())
 }
 
 /// Updates the last accessed timestamp of a provider in the database.
@@ -223,15 +565,38 @@ pub(crate) async fn fetch_providers_with_zones(
 /// # Returns
 ///
 /// * `Result<ProvidersSuccess, ProvidersError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    put,
+    path = "/providers/{id}/last_access",
+    tag = "providers",
+    params(("id" = i32, Path, description = "The ID of the provider")),
+    responses(
+        (status = 200, description = "The provider was marked as accessed now", body = MessageBody),
+        (status = 500, description = "The provider could not be updated", body = ErrorBody),
+    )
+)]
 pub(crate) async fn update_last_accessed(
     State(state): State<AppState>,
     Path(id): Path<i32>,
 ) -> Result<ProvidersSuccess, ProvidersError> {
-    sqlx::query("UPDATE providers SET last_accessed = NOW() WHERE id = $1")
-        .bind(id)
-        .execute(&state.db)
-        .await
-        .map_err(ProvidersError::update_error)?;
+    let last_accessed = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+        "UPDATE providers SET last_accessed = NOW() WHERE id = $1 RETURNING last_accessed",
+    )
+    .bind(id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(ProvidersError::update_error)?;
+
+    // The scraper touches each provider as it goes, which is reported as scraping run progress
+    if let Some(last_accessed) = last_accessed {
+        let event = LiveEvent::ScrapingRun(ScrapingRunEvent::ProviderScraped {
+            provider_id: id,
+            last_accessed,
+        });
+        if let Err(e) = state.events.publish(&state.db, event).await {
+            tracing::error!("Error while publishing scrape of provider {}: {}", id, e);
+        }
+    }
 
     Ok(ProvidersSuccess::updated(id))
 }
@@ -247,6 +612,17 @@ pub(crate) async fn update_last_accessed(
 /// # Returns
 ///
 /// * `Result<ProvidersSuccess, ProvidersError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/providers/{id}",
+    tag = "providers",
+    params(("id" = i32, Path, description = "The ID of the provider")),
+    responses(
+        (status = 200, description = "The provider was deleted", body = MessageBody),
+        (status = 500, description = "The provider could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn delete_provider(
     _claims: Claims,
     State(state): State<AppState>,
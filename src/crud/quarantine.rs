@@ -0,0 +1,704 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::crud::prices::{
+    apply_correction, enqueue_price_created, fetch_price_basis, fetch_stored_price, insert_price,
+    spawn_price_followups,
+};
+use crate::errors::{
+    ErrorBody, MessageBody, PricesSuccess, ProductsError, ProductsSuccess, QuarantineError,
+    QuarantineSuccess,
+};
+use crate::helpers::is_foreign_key_violation;
+use crate::models::prices::{PriceBasis, PriceCorrection, PriceTier};
+use crate::models::quarantine::{
+    PlausibilityRules, QuarantineQueryParams, QuarantineSort, QuarantineStatus, QuarantinedPrice,
+    MEDIAN_WINDOW_DAYS, MIN_MEDIAN_PROVIDERS,
+};
+use crate::pagination::{PageParams, Paginated};
+use axum::extract::{OriginalUri, Path, Query, State};
+use axum::Json;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, Postgres, QueryBuilder};
+
+/// Fetches the plausibility rules of a product from the database.
+///
+/// Products without rules get an empty rule set, meaning every price is accepted.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the product.
+///
+/// # Returns
+///
+/// * `Result<Json<PlausibilityRules>, ProductsError>` - The result of the operation, either the plausibility rules or an error.
+#[utoipa::path(
+    get,
+    path = "/products/{id}/plausibility",
+    tag = "quarantine",
+    params(("id" = i32, Path, description = "The ID of the product")),
+    responses(
+        (status = 200, description = "The plausibility rules of the product", body = PlausibilityRules),
+        (status = 500, description = "The rules could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_plausibility_rules(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<PlausibilityRules>, ProductsError> {
+    let rules = sqlx::query_as::<_, PlausibilityRules>(
+        r#"
+        SELECT min_price, max_price, max_change_percent, max_median_deviation_percent
+        FROM price_plausibility_rules
+        WHERE product_id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(ProductsError::fetch_error)?
+    .unwrap_or_default();
+
+    Ok(Json(rules))
+}
+
+/// Replaces the plausibility rules of a product in the database.
+///
+/// An unknown product is answered with `404 Not Found`.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the product.
+/// * `json` - The JSON payload containing the complete set of plausibility rules.
+///
+/// # Returns
+///
+/// * `Result<ProductsSuccess, ProductsError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    put,
+    path = "/products/{id}/plausibility",
+    tag = "quarantine",
+    params(("id" = i32, Path, description = "The ID of the product")),
+    request_body = PlausibilityRules,
+    responses(
+        (status = 200, description = "The rules were replaced", body = MessageBody),
+        (status = 400, description = "The rules are invalid", body = ErrorBody),
+        (status = 404, description = "The product does not exist", body = ErrorBody),
+        (status = 500, description = "The rules could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn update_plausibility_rules(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(json): Json<PlausibilityRules>,
+) -> Result<ProductsSuccess, ProductsError> {
+    let negative = [json.min_price, json.max_price]
+        .into_iter()
+        .flatten()
+        .any(|price| price.is_sign_negative());
+    if negative {
+        return Err(ProductsError::invalid_input(
+            "price bounds cannot be negative",
+        ));
+    }
+    if let (Some(min_price), Some(max_price)) = (json.min_price, json.max_price) {
+        if min_price > max_price {
+            return Err(ProductsError::invalid_input(
+                "min_price cannot be above max_price",
+            ));
+        }
+    }
+    let non_positive = [json.max_change_percent, json.max_median_deviation_percent]
+        .into_iter()
+        .flatten()
+        .any(|percent| percent <= Decimal::ZERO);
+    if non_positive {
+        return Err(ProductsError::invalid_input(
+            "percentages must be greater than zero",
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO price_plausibility_rules
+            (product_id, min_price, max_price, max_change_percent, max_median_deviation_percent)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (product_id) DO UPDATE SET
+            min_price = EXCLUDED.min_price,
+            max_price = EXCLUDED.max_price,
+            max_change_percent = EXCLUDED.max_change_percent,
+            max_median_deviation_percent = EXCLUDED.max_median_deviation_percent
+        "#,
+    )
+    .bind(id)
+    .bind(json.min_price)
+    .bind(json.max_price)
+    .bind(json.max_change_percent)
+    .bind(json.max_median_deviation_percent)
+    .execute(&state.db)
+    .await
+    .map_err(|e| {
+        if is_foreign_key_violation(&e) {
+            ProductsError::not_found()
+        } else {
+            ProductsError::update_error(e)
+        }
+    })?;
+
+    Ok(ProductsSuccess::updated(id))
+}
+
+/// Checks a normalised price against the plausibility rules of its product.
+///
+/// The price is compared with the bounds of the product, with the provider's price in effect and
+/// with the median of the other providers' current prices. The median is only used once enough
+/// providers have recent prices. A price taking effect in the past is compared with the prices
+/// in effect before it instead.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection.
+/// * `provider_id` - The ID of the provider.
+/// * `product_id` - The ID of the product.
+/// * `price` - The price per liter excluding VAT.
+/// * `at` - The time the price takes effect, if not now.
+///
+/// # Returns
+///
+/// * `Result<Vec<String>, sqlx::Error>` - The reasons the price is suspicious, empty if it is plausible, or a database error.
+pub(crate) async fn check_plausibility(
+    conn: &mut PgConnection,
+    provider_id: i32,
+    product_id: i32,
+    price: Decimal,
+    at: Option<NaiveDateTime>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rules = sqlx::query_as::<_, PlausibilityRules>(
+        r#"
+        SELECT min_price, max_price, max_change_percent, max_median_deviation_percent
+        FROM price_plausibility_rules
+        WHERE product_id = $1
+        "#,
+    )
+    .bind(product_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .unwrap_or_default();
+
+    let mut reasons = Vec::new();
+
+    if let Some(min_price) = rules.min_price.filter(|min_price| price < *min_price) {
+        reasons.push(format!(
+            "price {} is below the minimum of {}",
+            price, min_price
+        ));
+    }
+    if let Some(max_price) = rules.max_price.filter(|max_price| price > *max_price) {
+        reasons.push(format!(
+            "price {} is above the maximum of {}",
+            price, max_price
+        ));
+    }
+
+    if let Some(max_change_percent) = rules.max_change_percent {
+        let current: Option<Decimal> = sqlx::query_scalar(
+            r#"
+            SELECT price
+            FROM oil_prices
+            WHERE provider_id = $1
+              AND product_id = $2
+              AND deleted_at IS NULL
+              AND ($3::TIMESTAMP IS NULL OR valid_from < $3)
+            ORDER BY valid_from DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(provider_id)
+        .bind(product_id)
+        .bind(at)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        if let Some(current) = current.filter(|current| !current.is_zero()) {
+            let change = ((price - current) / current * Decimal::ONE_HUNDRED).abs();
+            if change > max_change_percent {
+                reasons.push(format!(
+                    "price changed {}% from {}, more than the allowed {}%",
+                    change.round_dp(2),
+                    current,
+                    max_change_percent
+                ));
+            }
+        }
+    }
+
+    if let Some(max_deviation_percent) = rules.max_median_deviation_percent {
+        let (median, providers) = sqlx::query_as::<_, (Option<Decimal>, i64)>(
+            r#"
+            SELECT
+                CAST(PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY price) AS NUMERIC(12, 4)),
+                COUNT(*)
+            FROM (
+                SELECT DISTINCT ON (provider_id) price
+                FROM oil_prices
+                WHERE product_id = $1
+                  AND provider_id <> $2
+                  AND deleted_at IS NULL
+                  AND ($4::TIMESTAMP IS NULL OR valid_from < $4)
+                  AND last_seen_at > COALESCE($4, LOCALTIMESTAMP) - MAKE_INTERVAL(days => $3)
+                ORDER BY provider_id, valid_from DESC, id DESC
+            ) latest
+            "#,
+        )
+        .bind(product_id)
+        .bind(provider_id)
+        .bind(MEDIAN_WINDOW_DAYS)
+        .bind(at)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if let Some(median) =
+            median.filter(|median| providers >= MIN_MEDIAN_PROVIDERS && !median.is_zero())
+        {
+            let deviation = ((price - median) / median * Decimal::ONE_HUNDRED).abs();
+            if deviation > max_deviation_percent {
+                reasons.push(format!(
+                    "price deviates {}% from the market median of {}, more than the allowed {}%",
+                    deviation.round_dp(2),
+                    median,
+                    max_deviation_percent
+                ));
+            }
+        }
+    }
+
+    Ok(reasons)
+}
+
+/// Holds a suspicious price for review instead of storing it.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection.
+/// * `provider_id` - The ID of the provider.
+/// * `basis` - The basis the price is published on.
+/// * `raw_price` - The price as published by the provider.
+/// * `tiers` - The volume tiers as published by the provider.
+/// * `observed_at` - The time the price was observed at, if not now.
+/// * `reasons` - The reasons the price is suspicious.
+///
+/// # Returns
+///
+/// * `Result<i32, sqlx::Error>` - The ID of the quarantined price or a database error.
+pub(crate) async fn quarantine_price(
+    conn: &mut PgConnection,
+    provider_id: i32,
+    basis: &PriceBasis,
+    raw_price: Decimal,
+    tiers: &[PriceTier],
+    observed_at: Option<NaiveDateTime>,
+    reasons: &[String],
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO price_quarantine
+            (provider_id, product_id, price, raw_price, raw_includes_vat, raw_per_liters, tiers,
+             reasons, observed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id
+        "#,
+    )
+    .bind(provider_id)
+    .bind(basis.product_id)
+    .bind(basis.normalise(raw_price))
+    .bind(raw_price)
+    .bind(basis.includes_vat)
+    .bind(basis.per_liters)
+    .bind(serde_json::to_value(tiers).unwrap_or_default())
+    .bind(reasons)
+    .bind(observed_at)
+    .fetch_one(conn)
+    .await
+}
+
+/// Holds a suspicious correction of a price for review instead of applying it.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection.
+/// * `price_id` - The ID of the price to correct.
+/// * `provider_id` - The ID of the provider of the price.
+/// * `basis` - The basis the corrected price is published on.
+/// * `correction` - The correction.
+/// * `requested_by` - The user who asked for the correction.
+/// * `reasons` - The reasons the corrected price is suspicious.
+///
+/// # Returns
+///
+/// * `Result<i32, sqlx::Error>` - The ID of the quarantined correction or a database error.
+pub(crate) async fn quarantine_correction(
+    conn: &mut PgConnection,
+    price_id: i32,
+    provider_id: i32,
+    basis: &PriceBasis,
+    correction: &PriceCorrection,
+    requested_by: &str,
+    reasons: &[String],
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO price_quarantine
+            (provider_id, product_id, price, raw_price, raw_includes_vat, raw_per_liters, tiers,
+             reasons, corrects_price_id, correction_reason, requested_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING id
+        "#,
+    )
+    .bind(provider_id)
+    .bind(basis.product_id)
+    .bind(basis.normalise(correction.price))
+    .bind(correction.price)
+    .bind(basis.includes_vat)
+    .bind(basis.per_liters)
+    .bind(serde_json::to_value(&correction.tiers).unwrap_or_default())
+    .bind(reasons)
+    .bind(price_id)
+    .bind(&correction.reason)
+    .bind(requested_by)
+    .fetch_one(conn)
+    .await
+}
+
+/// Fetches a page of quarantined prices from the database, oldest first by default.
+///
+/// The next page is linked in the `Link` and `X-Next-Cursor` response headers.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `uri` - The URI of the request, used to link to the next page.
+/// * `page` - The pagination and sorting parameters.
+/// * `params` - The query parameters for filtering by status, pending by default, and provider.
+///
+/// # Returns
+///
+/// * `Result<Paginated<QuarantinedPrice>, QuarantineError>` - The result of the operation, either a page of quarantined prices or an error.
+#[utoipa::path(
+    get,
+    path = "/prices/quarantine",
+    tag = "quarantine",
+    params(
+        PageParams<QuarantineSort>,
+        ("sort" = Option<QuarantineSort>, Query, description = "The column to sort by"),
+        QuarantineQueryParams,
+    ),
+    responses(
+        (status = 200, description = "A page of quarantined prices, with the next page linked in the `Link` and `X-Next-Cursor` headers", body = Vec<QuarantinedPrice>),
+        (status = 400, description = "The cursor is malformed", body = ErrorBody),
+        (status = 500, description = "The quarantined prices could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn fetch_quarantined_prices(
+    _claims: Claims,
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    Query(page): Query<PageParams<QuarantineSort>>,
+    Query(params): Query<QuarantineQueryParams>,
+) -> Result<Paginated<QuarantinedPrice>, QuarantineError> {
+    let mut query = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT * FROM (
+            SELECT
+                id, provider_id, product_id, price, raw_price, raw_includes_vat, raw_per_liters,
+                tiers, reasons, status, received_at, observed_at, corrects_price_id,
+                correction_reason, requested_by, reviewed_by, reviewed_at, price_id
+            FROM
+                price_quarantine
+        ) AS page
+        WHERE
+            page.status = "#,
+    );
+    query.push_bind(params.status.as_str());
+
+    if let Some(provider_id) = params.provider_id {
+        query
+            .push(" AND page.provider_id = ")
+            .push_bind(provider_id);
+    }
+
+    page.push_page(&mut query)
+        .map_err(QuarantineError::invalid_input)?;
+
+    let rows = query
+        .build_query_as::<QuarantinedPrice>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(QuarantineError::fetch_error)?;
+
+    Ok(page.paginate(rows, uri))
+}
+
+/// Locks a quarantined price that is still waiting for review.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, within the review's transaction.
+/// * `id` - The ID of the quarantined price.
+///
+/// # Returns
+///
+/// * `Result<QuarantinedPrice, QuarantineError>` - The quarantined price, or an error if it does not exist or was already reviewed.
+async fn lock_pending(
+    conn: &mut PgConnection,
+    id: i32,
+) -> Result<QuarantinedPrice, QuarantineError> {
+    let row = sqlx::query_as::<_, QuarantinedPrice>(
+        r#"
+        SELECT
+            id, provider_id, product_id, price, raw_price, raw_includes_vat, raw_per_liters, tiers,
+            reasons, status, received_at, observed_at, corrects_price_id, correction_reason,
+            requested_by, reviewed_by, reviewed_at, price_id
+        FROM
+            price_quarantine
+        WHERE
+            id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .map_err(QuarantineError::fetch_error)?
+    .ok_or_else(QuarantineError::not_found)?;
+
+    if row.status != QuarantineStatus::Pending.as_str() {
+        return Err(QuarantineError::invalid_input(format!(
+            "price was already {}",
+            row.status
+        )));
+    }
+
+    Ok(row)
+}
+
+/// Approves a quarantined price, storing it as if it had passed the plausibility checks.
+///
+/// The price keeps the time it was observed at, or else received at. Once stored it triggers
+/// webhooks, live updates and price alerts like any new price. A held correction is applied to
+/// the price it corrects instead, on behalf of the user who asked for it.
+///
+/// # Arguments
+///
+/// * `claims` - The JWT claims of the authenticated user, recorded as the reviewer.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the quarantined price.
+///
+/// # Returns
+///
+/// * `Result<PricesSuccess, QuarantineError>` - The result of the operation, either the stored price or an error.
+#[utoipa::path(
+    post,
+    path = "/prices/quarantine/{id}/approve",
+    tag = "quarantine",
+    params(("id" = i32, Path, description = "The ID of the quarantined price")),
+    responses(
+        (status = 201, description = "The price was stored", body = MessageBody),
+        (status = 200, description = "The held correction was applied", body = MessageBody),
+        (status = 400, description = "The price was already reviewed, or what it refers to no longer exists", body = ErrorBody),
+        (status = 404, description = "The quarantined price does not exist", body = ErrorBody),
+        (status = 500, description = "The price could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn approve_quarantined_price(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<PricesSuccess, QuarantineError> {
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(QuarantineError::update_error)?;
+
+    let row = lock_pending(&mut tx, id).await?;
+    let tiers: Option<Vec<PriceTier>> = serde_json::from_value(row.tiers.clone())
+        .map_err(|e| QuarantineError::invalid_input(format!("invalid tiers: {}", e)))?;
+
+    if let Some(price_id) = row.corrects_price_id {
+        let stored = fetch_stored_price(&mut tx, price_id)
+            .await
+            .map_err(QuarantineError::fetch_error)?
+            .ok_or_else(|| QuarantineError::invalid_input("the corrected price does not exist"))?;
+        if stored.deleted_at.is_some() {
+            return Err(QuarantineError::invalid_input(
+                "the corrected price was deleted",
+            ));
+        }
+
+        let mut basis = fetch_price_basis(
+            &mut tx,
+            stored.provider_id,
+            Some(stored.product_id),
+            None,
+            stored.created_at,
+        )
+        .await
+        .map_err(QuarantineError::fetch_error)?
+        .ok_or_else(|| QuarantineError::invalid_input("unknown product"))?;
+        basis.includes_vat = row.raw_includes_vat;
+        basis.per_liters = row.raw_per_liters;
+
+        let correction = PriceCorrection {
+            price: row.raw_price,
+            includes_vat: Some(row.raw_includes_vat),
+            per_liters: Some(row.raw_per_liters),
+            tiers,
+            reason: row.correction_reason.clone(),
+        };
+        let author = row.requested_by.as_deref().unwrap_or(&claims.username);
+        apply_correction(&mut tx, price_id, &stored, &basis, &correction, author)
+            .await
+            .map_err(QuarantineError::update_error)?;
+
+        mark_approved(&mut tx, id, &claims.username, price_id).await?;
+        tx.commit().await.map_err(QuarantineError::update_error)?;
+
+        return Ok(PricesSuccess::updated(price_id));
+    }
+
+    let observed_at = row.observed_at.unwrap_or(row.received_at);
+    let mut basis = fetch_price_basis(
+        &mut tx,
+        row.provider_id,
+        Some(row.product_id),
+        None,
+        Some(observed_at),
+    )
+    .await
+    .map_err(QuarantineError::fetch_error)?
+    .ok_or_else(|| QuarantineError::invalid_input("unknown product"))?;
+    basis.includes_vat = row.raw_includes_vat;
+    basis.per_liters = row.raw_per_liters;
+
+    let price_id = insert_price(
+        &mut tx,
+        row.provider_id,
+        &basis,
+        row.raw_price,
+        &tiers.unwrap_or_default(),
+        Some(observed_at),
+        None,
+    )
+    .await
+    .map_err(QuarantineError::update_error)?;
+    enqueue_price_created(&mut tx, price_id, row.provider_id, &basis, row.raw_price)
+        .await
+        .map_err(QuarantineError::update_error)?;
+
+    mark_approved(&mut tx, id, &claims.username, price_id).await?;
+    tx.commit().await.map_err(QuarantineError::update_error)?;
+
+    spawn_price_followups(state, vec![price_id]);
+
+    Ok(PricesSuccess::created(price_id))
+}
+
+/// Marks a quarantined price as approved.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, within the review's transaction.
+/// * `id` - The ID of the quarantined price.
+/// * `reviewer` - The user who approved the price.
+/// * `price_id` - The ID of the price stored or corrected on approval.
+///
+/// # Returns
+///
+/// * `Result<(), QuarantineError>` - `Ok(())` if the price was marked, or an error.
+async fn mark_approved(
+    conn: &mut PgConnection,
+    id: i32,
+    reviewer: &str,
+    price_id: i32,
+) -> Result<(), QuarantineError> {
+    sqlx::query(
+        r#"
+        UPDATE price_quarantine
+        SET status = $2, reviewed_by = $3, reviewed_at = LOCALTIMESTAMP, price_id = $4
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(QuarantineStatus::Approved.as_str())
+    .bind(reviewer)
+    .bind(price_id)
+    .execute(conn)
+    .await
+    .map_err(QuarantineError::update_error)?;
+
+    Ok(())
+}
+
+/// Rejects a quarantined price. The price is kept for reference but never stored.
+///
+/// # Arguments
+///
+/// * `claims` - The JWT claims of the authenticated user, recorded as the reviewer.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the quarantined price.
+///
+/// # Returns
+///
+/// * `Result<QuarantineSuccess, QuarantineError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/prices/quarantine/{id}/reject",
+    tag = "quarantine",
+    params(("id" = i32, Path, description = "The ID of the quarantined price")),
+    responses(
+        (status = 200, description = "The price was rejected", body = MessageBody),
+        (status = 400, description = "The price was already reviewed", body = ErrorBody),
+        (status = 404, description = "The quarantined price does not exist", body = ErrorBody),
+        (status = 500, description = "The price could not be rejected", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn reject_quarantined_price(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<QuarantineSuccess, QuarantineError> {
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(QuarantineError::update_error)?;
+
+    lock_pending(&mut tx, id).await?;
+
+    sqlx::query(
+        r#"
+        UPDATE price_quarantine
+        SET status = $2, reviewed_by = $3, reviewed_at = LOCALTIMESTAMP
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(QuarantineStatus::Rejected.as_str())
+    .bind(&claims.username)
+    .execute(&mut *tx)
+    .await
+    .map_err(QuarantineError::update_error)?;
+
+    tx.commit().await.map_err(QuarantineError::update_error)?;
+
+    Ok(QuarantineSuccess::updated(id))
+}
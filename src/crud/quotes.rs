@@ -0,0 +1,174 @@
+use crate::app_state::AppState;
+use crate::errors::{ErrorBody, QuotesError};
+use crate::models::products::DEFAULT_PRODUCT;
+use crate::models::quotes::{Quote, QuoteCandidateRow, QuoteRequest, QuoteResponse};
+use axum::extract::State;
+use axum::Json;
+use rust_decimal::Decimal;
+
+/// Calculates the delivery fee a provider charges for an order.
+///
+/// Orders at or above the free delivery threshold are delivered for free, otherwise the zone
+/// specific fee takes precedence over the provider wide fee. Fees are stored including VAT, so
+/// they can be added to totals including VAT as they are.
+///
+/// # Arguments
+///
+/// * `row` - The quote candidate containing the provider's fee rules.
+/// * `liters` - The ordered volume in liters.
+///
+/// # Returns
+///
+/// * `Decimal` - The delivery fee.
+fn delivery_fee(row: &QuoteCandidateRow, liters: i32) -> Decimal {
+    if row.free_above_liters.is_some_and(|free| liters >= free) {
+        return Decimal::ZERO;
+    }
+
+    row.zone_fee.or(row.delivery_fee).unwrap_or(Decimal::ZERO)
+}
+
+/// Calculates ranked total costs per provider for delivering an amount of oil to a postcode.
+///
+/// Only providers delivering to a zone covering the postcode and with a price for the product are
+/// considered. The unit price is taken from the highest volume tier the order qualifies for and
+/// providers whose minimum order is above the requested volume are left out. Unit prices and
+/// totals include VAT. The excise duties included in each total are listed separately, with the
+/// VAT charged on them.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `json` - The JSON payload containing the volume, postcode and optional product.
+///
+/// # Returns
+///
+/// * `Result<Json<QuoteResponse>, QuotesError>` - The result of the operation, either the ranked quotes or an error.
+#[utoipa::path(
+    post,
+    path = "/quote",
+    tag = "quotes",
+    request_body = QuoteRequest,
+    responses(
+        (status = 200, description = "The providers ranked by total cost", body = QuoteResponse),
+        (status = 400, description = "The volume is invalid", body = ErrorBody),
+        (status = 500, description = "The quotes could not be calculated", body = ErrorBody),
+    )
+)]
+pub(crate) async fn create_quote(
+    State(state): State<AppState>,
+    Json(json): Json<QuoteRequest>,
+) -> Result<Json<QuoteResponse>, QuotesError> {
+    if json.liters <= 0 {
+        return Err(QuotesError::invalid_input(
+            "liters must be greater than zero",
+        ));
+    }
+
+    let product = json.product.unwrap_or_else(|| DEFAULT_PRODUCT.to_string());
+
+    let rows = sqlx::query_as::<_, QuoteCandidateRow>(
+        r#"
+        WITH zone_providers AS (
+            SELECT DISTINCT
+                pz.provider_id, pz.zone_id
+            FROM
+                delivery_zone_postcodes zp
+            JOIN
+                provider_delivery_zones pz ON zp.zone_id = pz.zone_id
+            WHERE
+                $1 BETWEEN zp.postcode_from AND zp.postcode_to
+        ),
+        latest_prices AS (
+            SELECT DISTINCT ON (op.provider_id)
+                op.id, op.provider_id, op.product_id, op.price, op.created_at
+            FROM
+                oil_prices op
+            JOIN
+                products pr ON op.product_id = pr.id
+            WHERE
+                pr.slug = $2
+                AND op.deleted_at IS NULL
+                AND op.provider_id IN (SELECT provider_id FROM zone_providers)
+            ORDER BY
+                op.provider_id, op.created_at DESC, op.id DESC
+        )
+        SELECT
+            lp.provider_id,
+            p.name AS provider_name,
+            p.url,
+            lp.id AS price_id,
+            lp.created_at AS price_created_at,
+            ROUND(
+                COALESCE(
+                    (SELECT t.price
+                     FROM oil_price_tiers t
+                     WHERE t.price_id = lp.id AND t.min_liters <= $3
+                     ORDER BY t.min_liters DESC
+                     LIMIT 1),
+                    lp.price
+                ) * (1 + vat_rate_at(lp.created_at)),
+                4
+            ) AS unit_price,
+            ROUND(
+                duties_at(lp.product_id, lp.created_at) * (1 + vat_rate_at(lp.created_at)),
+                4
+            ) AS unit_duties,
+            f.delivery_fee,
+            (SELECT MIN(zf.delivery_fee)
+             FROM provider_zone_fees zf
+             JOIN zone_providers zp
+                ON zf.provider_id = zp.provider_id AND zf.zone_id = zp.zone_id
+             WHERE zf.provider_id = lp.provider_id) AS zone_fee,
+            f.free_above_liters,
+            f.minimum_order_liters
+        FROM
+            latest_prices lp
+        JOIN
+            providers p ON lp.provider_id = p.id
+        LEFT JOIN
+            provider_fees f ON lp.provider_id = f.provider_id
+        "#,
+    )
+    .bind(json.postcode)
+    .bind(&product)
+    .bind(json.liters)
+    .fetch_all(&state.db)
+    .await
+    .map_err(QuotesError::fetch_error)?;
+
+    let mut quotes: Vec<Quote> = rows
+        .into_iter()
+        .filter(|row| {
+            row.minimum_order_liters
+                .is_none_or(|minimum| json.liters >= minimum)
+        })
+        .map(|row| {
+            let delivery_fee = delivery_fee(&row, json.liters);
+            Quote {
+                rank: 0,
+                total: (row.unit_price * Decimal::from(json.liters) + delivery_fee).round_dp(2),
+                provider_id: row.provider_id,
+                provider_name: row.provider_name,
+                url: row.url,
+                price_id: row.price_id,
+                price_created_at: row.price_created_at,
+                unit_price: row.unit_price,
+                duties: (row.unit_duties * Decimal::from(json.liters)).round_dp(2),
+                delivery_fee,
+            }
+        })
+        .collect();
+
+    quotes.sort_by_key(|quote| quote.total);
+    for (index, quote) in quotes.iter_mut().enumerate() {
+        quote.rank = index + 1;
+    }
+
+    Ok(Json(QuoteResponse {
+        liters: json.liters,
+        postcode: json.postcode,
+        product,
+        quotes,
+    }))
+}
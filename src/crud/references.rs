@@ -0,0 +1,666 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::errors::{ErrorBody, FxRatesError, MessageBody, ReferencesError, ReferencesSuccess};
+use crate::helpers::{fetch_product_by_slug, is_unique_violation};
+use crate::models::references::{
+    FxRateAdd, FxRateQueryParams, FxRates, MarginParams, MarginPoint, ReferenceObservation,
+    ReferenceObservationAdd, ReferenceQueryParams, ReferenceSeries, ReferenceSeriesAdd,
+    UploadReport, BASE_CURRENCY, DEFAULT_MARGIN_DAYS, MAX_MARGIN_DAYS, MAX_UPLOAD_ROWS,
+};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::HeaderMap;
+use axum::Json;
+use rust_decimal::Decimal;
+use serde::de::{DeserializeOwned, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+/// Tells whether a currency code looks like an ISO 4217 code.
+fn is_currency_code(currency: &str) -> bool {
+    currency.len() == 3 && currency.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// The items of a JSON array upload, parsed no further than `MAX_UPLOAD_ROWS` items.
+struct UploadItems(Vec<serde_json::Value>);
+
+impl<'de> Deserialize<'de> for UploadItems {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ItemsVisitor;
+
+        impl<'de> Visitor<'de> for ItemsVisitor {
+            type Value = UploadItems;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a JSON array")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    if items.len() == MAX_UPLOAD_ROWS {
+                        return Err(serde::de::Error::custom(too_many_rows()));
+                    }
+                    items.push(item);
+                }
+                Ok(UploadItems(items))
+            }
+        }
+
+        deserializer.deserialize_seq(ItemsVisitor)
+    }
+}
+
+/// Returns the reason an upload with more than `MAX_UPLOAD_ROWS` rows is rejected.
+fn too_many_rows() -> String {
+    format!("at most {} rows may be uploaded at once", MAX_UPLOAD_ROWS)
+}
+
+/// Parses the rows of an upload, sent either as a JSON array or, with the `text/csv` content
+/// type, as CSV with a header row naming the fields.
+///
+/// Parsing stops as soon as the upload turns out to hold more than `MAX_UPLOAD_ROWS` rows.
+///
+/// # Arguments
+///
+/// * `headers` - The request headers, whose content type tells the two apart.
+/// * `body` - The request body.
+///
+/// # Returns
+///
+/// * `Result<Vec<Result<T, String>>, String>` - The rows, each either parsed or with the reason it could not be, or the reason the body is malformed as a whole.
+fn parse_upload<T: DeserializeOwned>(
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<Vec<Result<T, String>>, String> {
+    let is_csv = headers
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/csv"));
+
+    if is_csv {
+        let rows: Vec<Result<T, String>> = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(body.strip_prefix("\u{feff}".as_bytes()).unwrap_or(body))
+            .deserialize()
+            .map(|row| row.map_err(|e| e.to_string()))
+            .take(MAX_UPLOAD_ROWS + 1)
+            .collect();
+        if rows.len() > MAX_UPLOAD_ROWS {
+            return Err(too_many_rows());
+        }
+        return Ok(rows);
+    }
+
+    let UploadItems(items) = serde_json::from_slice(body).map_err(|e| {
+        let too_many = too_many_rows();
+        if e.to_string().starts_with(&too_many) {
+            too_many
+        } else {
+            format!("body must be a JSON array: {}", e)
+        }
+    })?;
+    Ok(items
+        .into_iter()
+        .map(|item| serde_json::from_value(item).map_err(|e| e.to_string()))
+        .collect())
+}
+
+/// Creates a new reference series in the database.
+///
+/// A slug already taken by another series is answered with `409 Conflict`.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `json` - The JSON payload containing the series details.
+///
+/// # Returns
+///
+/// * `Result<ReferencesSuccess, ReferencesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/references",
+    tag = "references",
+    request_body = ReferenceSeriesAdd,
+    responses(
+        (status = 201, description = "The reference series was created", body = MessageBody),
+        (status = 400, description = "The reference series is invalid", body = ErrorBody),
+        (status = 409, description = "The slug is already taken", body = ErrorBody),
+        (status = 500, description = "The reference series could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn create_reference_series(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<ReferenceSeriesAdd>,
+) -> Result<ReferencesSuccess, ReferencesError> {
+    if !is_currency_code(&json.currency) {
+        return Err(ReferencesError::invalid_input(
+            "currency must be an ISO 4217 code such as USD",
+        ));
+    }
+    if json.liters_per_unit <= Decimal::ZERO {
+        return Err(ReferencesError::invalid_input(
+            "liters_per_unit must be greater than zero",
+        ));
+    }
+
+    let id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO reference_series (slug, name, description, currency, unit, liters_per_unit)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
+        "#,
+    )
+    .bind(json.slug)
+    .bind(json.name)
+    .bind(json.description)
+    .bind(json.currency)
+    .bind(json.unit)
+    .bind(json.liters_per_unit)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| {
+        if is_unique_violation(&e) {
+            ReferencesError::conflict("a series with the slug already exists")
+        } else {
+            ReferencesError::insert_error(e)
+        }
+    })?;
+
+    Ok(ReferencesSuccess::created(id))
+}
+
+/// Fetches all reference series from the database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<ReferenceSeries>>, ReferencesError>` - The result of the operation, either a list of reference series or an error.
+#[utoipa::path(
+    get,
+    path = "/references",
+    tag = "references",
+    responses(
+        (status = 200, description = "All reference series", body = Vec<ReferenceSeries>),
+        (status = 500, description = "The reference series could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_reference_series(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ReferenceSeries>>, ReferencesError> {
+    let res = sqlx::query_as::<_, ReferenceSeries>(
+        r#"
+        SELECT id, slug, name, description, currency, unit, liters_per_unit, created_at
+        FROM reference_series
+        ORDER BY slug
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(ReferencesError::fetch_error)?;
+
+    Ok(Json(res))
+}
+
+/// Deletes a reference series and its observations from the database.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the reference series to delete.
+///
+/// # Returns
+///
+/// * `Result<ReferencesSuccess, ReferencesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/references/{id}",
+    tag = "references",
+    params(("id" = i32, Path, description = "The ID of the reference series")),
+    responses(
+        (status = 200, description = "The reference series was deleted", body = MessageBody),
+        (status = 404, description = "The reference series does not exist", body = ErrorBody),
+        (status = 500, description = "The reference series could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn delete_reference_series(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<ReferencesSuccess, ReferencesError> {
+    let res = sqlx::query("DELETE FROM reference_series WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(ReferencesError::delete_error)?;
+
+    if res.rows_affected() == 0 {
+        return Err(ReferencesError::not_found());
+    }
+
+    Ok(ReferencesSuccess::deleted(id))
+}
+
+/// Uploads observations of a reference series.
+///
+/// The body is a JSON array of `{"date", "value"}` objects or, with the `text/csv` content type,
+/// CSV with `date` and `value` columns. Values are in the currency and unit of the series. An
+/// observation replaces any earlier one of the same date. Invalid rows are skipped and listed in
+/// the report.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the reference series.
+/// * `headers` - The request headers.
+/// * `body` - The request body holding the observations.
+///
+/// # Returns
+///
+/// * `Result<Json<UploadReport>, ReferencesError>` - The result of the operation, either the upload report or an error.
+#[utoipa::path(
+    post,
+    path = "/references/{id}/observations",
+    tag = "references",
+    params(("id" = i32, Path, description = "The ID of the reference series")),
+    request_body(content(
+        (Vec<ReferenceObservationAdd> = "application/json"),
+        (String = "text/csv"),
+    )),
+    responses(
+        (status = 200, description = "The number of observations stored and the rows rejected", body = UploadReport),
+        (status = 400, description = "The body is malformed or holds too many rows", body = ErrorBody),
+        (status = 404, description = "The reference series does not exist", body = ErrorBody),
+        (status = 500, description = "The observations could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn upload_reference_observations(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<UploadReport>, ReferencesError> {
+    let rows = parse_upload::<ReferenceObservationAdd>(&headers, &body)
+        .map_err(ReferencesError::invalid_input)?;
+
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(ReferencesError::insert_error)?;
+
+    sqlx::query_scalar::<_, i32>("SELECT id FROM reference_series WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(ReferencesError::fetch_error)?
+        .ok_or_else(ReferencesError::not_found)?;
+
+    let mut report = UploadReport::default();
+    for (index, row) in rows.into_iter().enumerate() {
+        let observation = match row {
+            Ok(observation) => observation,
+            Err(error) => {
+                report
+                    .rejected
+                    .push(format!("row {}: {}", index + 1, error));
+                continue;
+            }
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO reference_observations (series_id, observed_on, value)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (series_id, observed_on) DO UPDATE SET value = EXCLUDED.value
+            "#,
+        )
+        .bind(id)
+        .bind(observation.date)
+        .bind(observation.value)
+        .execute(&mut *tx)
+        .await
+        .map_err(ReferencesError::insert_error)?;
+        report.stored += 1;
+    }
+
+    tx.commit().await.map_err(ReferencesError::insert_error)?;
+
+    Ok(Json(report))
+}
+
+/// Fetches the observations of a reference series, converted to DKK per liter.
+///
+/// Each observation is converted with the latest FX rate of the series' currency known on its
+/// date. Observations without a known FX rate are returned unconverted.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the reference series.
+/// * `params` - The query parameters for filtering by date.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<ReferenceObservation>>, ReferencesError>` - The result of the operation, either a list of observations or an error.
+#[utoipa::path(
+    get,
+    path = "/references/{id}/observations",
+    tag = "references",
+    params(("id" = i32, Path, description = "The ID of the reference series"), ReferenceQueryParams),
+    responses(
+        (status = 200, description = "The observations of the series, converted to DKK per liter", body = Vec<ReferenceObservation>),
+        (status = 500, description = "The observations could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_reference_observations(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<ReferenceQueryParams>,
+) -> Result<Json<Vec<ReferenceObservation>>, ReferencesError> {
+    let rows = sqlx::query_as::<_, ReferenceObservation>(
+        r#"
+        SELECT
+            o.observed_on AS date,
+            o.value,
+            fx.rate AS fx_rate,
+            ROUND(o.value * fx.rate / s.liters_per_unit, 4) AS price_per_liter
+        FROM
+            reference_observations o
+        JOIN
+            reference_series s ON o.series_id = s.id
+        LEFT JOIN LATERAL (
+            SELECT
+                CASE
+                    WHEN s.currency = $4 THEN 1
+                    ELSE (
+                        SELECT f.rate
+                        FROM fx_rates f
+                        WHERE f.currency = s.currency AND f.day <= o.observed_on
+                        ORDER BY f.day DESC
+                        LIMIT 1
+                    )
+                END AS rate
+        ) fx ON TRUE
+        WHERE
+            o.series_id = $1
+            AND ($2::DATE IS NULL OR o.observed_on >= $2)
+            AND ($3::DATE IS NULL OR o.observed_on <= $3)
+        ORDER BY
+            o.observed_on
+        "#,
+    )
+    .bind(id)
+    .bind(params.start)
+    .bind(params.end)
+    .bind(BASE_CURRENCY)
+    .fetch_all(&state.db)
+    .await
+    .map_err(ReferencesError::fetch_error)?;
+
+    Ok(Json(rows))
+}
+
+/// Uploads FX rates.
+///
+/// The body is a JSON array of `{"currency", "date", "rate"}` objects or, with the `text/csv`
+/// content type, CSV with `currency`, `date` and `rate` columns. A rate is the price of one unit
+/// of the currency in DKK and replaces any earlier rate of the same currency and date. Invalid
+/// rows are skipped and listed in the report.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `headers` - The request headers.
+/// * `body` - The request body holding the rates.
+///
+/// # Returns
+///
+/// * `Result<Json<UploadReport>, FxRatesError>` - The result of the operation, either the upload report or an error.
+#[utoipa::path(
+    post,
+    path = "/fx_rates",
+    tag = "fx rates",
+    request_body(content(
+        (Vec<FxRateAdd> = "application/json"),
+        (String = "text/csv"),
+    )),
+    responses(
+        (status = 200, description = "The number of rates stored and the rows rejected", body = UploadReport),
+        (status = 400, description = "The body is malformed or holds too many rows", body = ErrorBody),
+        (status = 500, description = "The rates could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn upload_fx_rates(
+    _claims: Claims,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<UploadReport>, FxRatesError> {
+    let rows = parse_upload::<FxRateAdd>(&headers, &body).map_err(FxRatesError::invalid_input)?;
+
+    let mut tx = state.db.begin().await.map_err(FxRatesError::insert_error)?;
+
+    let mut report = UploadReport::default();
+    for (index, row) in rows.into_iter().enumerate() {
+        let rate = match row {
+            Ok(rate) if !is_currency_code(&rate.currency) => {
+                report.rejected.push(format!(
+                    "row {}: invalid currency '{}'",
+                    index + 1,
+                    rate.currency
+                ));
+                continue;
+            }
+            Ok(rate) if rate.rate <= Decimal::ZERO => {
+                report
+                    .rejected
+                    .push(format!("row {}: rate must be greater than zero", index + 1));
+                continue;
+            }
+            Ok(rate) => rate,
+            Err(error) => {
+                report
+                    .rejected
+                    .push(format!("row {}: {}", index + 1, error));
+                continue;
+            }
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO fx_rates (currency, day, rate)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (currency, day) DO UPDATE SET rate = EXCLUDED.rate
+            "#,
+        )
+        .bind(rate.currency)
+        .bind(rate.date)
+        .bind(rate.rate)
+        .execute(&mut *tx)
+        .await
+        .map_err(FxRatesError::insert_error)?;
+        report.stored += 1;
+    }
+
+    tx.commit().await.map_err(FxRatesError::insert_error)?;
+
+    Ok(Json(report))
+}
+
+/// Fetches FX rates from the database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `params` - The query parameters for filtering by currency and date.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<FxRates>>, FxRatesError>` - The result of the operation, either a list of FX rates or an error.
+#[utoipa::path(
+    get,
+    path = "/fx_rates",
+    tag = "fx rates",
+    params(FxRateQueryParams),
+    responses(
+        (status = 200, description = "The FX rates", body = Vec<FxRates>),
+        (status = 500, description = "The rates could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_fx_rates(
+    State(state): State<AppState>,
+    Query(params): Query<FxRateQueryParams>,
+) -> Result<Json<Vec<FxRates>>, FxRatesError> {
+    let rows = sqlx::query_as::<_, FxRates>(
+        r#"
+        SELECT currency, day AS date, rate
+        FROM fx_rates
+        WHERE
+            ($1::TEXT IS NULL OR currency = $1)
+            AND ($2::DATE IS NULL OR day >= $2)
+            AND ($3::DATE IS NULL OR day <= $3)
+        ORDER BY
+            currency, day
+        "#,
+    )
+    .bind(&params.currency)
+    .bind(params.start)
+    .bind(params.end)
+    .fetch_all(&state.db)
+    .await
+    .map_err(FxRatesError::fetch_error)?;
+
+    Ok(Json(rows))
+}
+
+/// Fetches a provider's daily margin over a reference series.
+///
+/// The margin of a day is the provider's price in effect at the end of it minus the latest
+/// observation of the reference series on or before it, both in DKK per liter excluding VAT.
+/// Days on which either is unknown have no margin. The period defaults to the last 30 days and
+/// the product to the default product.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the provider.
+/// * `params` - The query parameters choosing the reference series, the product and the period.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<MarginPoint>>, ReferencesError>` - The result of the operation, either the margin of every day in the period or an error.
+#[utoipa::path(
+    get,
+    path = "/providers/{id}/margins",
+    tag = "references",
+    params(("id" = i32, Path, description = "The ID of the provider"), MarginParams),
+    responses(
+        (status = 200, description = "The provider's margin on every day in the period", body = Vec<MarginPoint>),
+        (status = 400, description = "The period, the reference series or the product is invalid", body = ErrorBody),
+        (status = 500, description = "The margins could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_provider_margins(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<MarginParams>,
+) -> Result<Json<Vec<MarginPoint>>, ReferencesError> {
+    let end = params
+        .end
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let start = params
+        .start
+        .unwrap_or(end - chrono::Duration::days(DEFAULT_MARGIN_DAYS));
+    if start > end {
+        return Err(ReferencesError::invalid_input("start must be before end"));
+    }
+    if (end - start).num_days() > MAX_MARGIN_DAYS {
+        return Err(ReferencesError::invalid_input(format!(
+            "margins may cover at most {} days",
+            MAX_MARGIN_DAYS
+        )));
+    }
+
+    let series_id: i32 = sqlx::query_scalar("SELECT id FROM reference_series WHERE slug = $1")
+        .bind(&params.reference)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(ReferencesError::fetch_error)?
+        .ok_or_else(|| ReferencesError::invalid_input("unknown reference series"))?;
+    let (product_id, _) = fetch_product_by_slug(&state.db, params.product.as_deref())
+        .await
+        .map_err(ReferencesError::fetch_error)?
+        .ok_or_else(|| ReferencesError::invalid_input("unknown product"))?;
+
+    let rows = sqlx::query_as::<_, MarginPoint>(
+        r#"
+        SELECT
+            d.day::DATE AS date,
+            p.price,
+            r.price_per_liter AS reference_price,
+            p.price - r.price_per_liter AS margin
+        FROM
+            generate_series($3::DATE, $4::DATE, INTERVAL '1 day') AS d(day)
+        LEFT JOIN LATERAL (
+            SELECT op.price
+            FROM oil_prices op
+            WHERE op.provider_id = $1
+              AND op.product_id = $2
+              AND op.valid_from < d.day + INTERVAL '1 day'
+              AND op.deleted_at IS NULL
+            ORDER BY op.valid_from DESC, op.id DESC
+            LIMIT 1
+        ) p ON TRUE
+        LEFT JOIN LATERAL (
+            SELECT
+                ROUND(
+                    o.value * CASE
+                        WHEN s.currency = $6 THEN 1
+                        ELSE (
+                            SELECT f.rate
+                            FROM fx_rates f
+                            WHERE f.currency = s.currency AND f.day <= o.observed_on
+                            ORDER BY f.day DESC
+                            LIMIT 1
+                        )
+                    END / s.liters_per_unit,
+                    4
+                ) AS price_per_liter
+            FROM reference_observations o
+            JOIN reference_series s ON o.series_id = s.id
+            WHERE o.series_id = $5 AND o.observed_on <= d.day
+            ORDER BY o.observed_on DESC
+            LIMIT 1
+        ) r ON TRUE
+        ORDER BY
+            d.day
+        "#,
+    )
+    .bind(id)
+    .bind(product_id)
+    .bind(start)
+    .bind(end)
+    .bind(series_id)
+    .bind(BASE_CURRENCY)
+    .fetch_all(&state.db)
+    .await
+    .map_err(ReferencesError::fetch_error)?;
+
+    Ok(Json(rows))
+}
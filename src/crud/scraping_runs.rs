@@ -1,9 +1,13 @@
 use crate::app_state::AppState;
 use crate::auth::jwt::Claims;
-use crate::errors::{ScrapingRunsError, ScrapingRunsSuccess};
+use crate::errors::{ErrorBody, MessageBody, ScrapingRunsError, ScrapingRunsSuccess};
+use crate::models::live_events::{LiveEvent, ScrapingRunEvent};
 use crate::models::scraping_runs::{ScrapingRuns, ScrapingRunsInsertResponse};
+use crate::models::webhooks::WebhookEvent;
+use crate::webhooks::events::enqueue_event;
 use axum::extract::State;
 use axum::Json;
+use serde_json::json;
 
 /// Creates a new scraping run in the database.
 ///
@@ -16,20 +20,60 @@ use axum::Json;
 /// # Returns
 ///
 /// * `Result<ScrapingRunsSuccess, ScrapingRunsError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/scraping_runs",
+    tag = "scraping runs",
+    request_body = ScrapingRuns,
+    responses(
+        (status = 201, description = "The scraping run was recorded", body = MessageBody),
+        (status = 500, description = "The scraping run could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub(crate) async fn create_scraping_run(
     _claims: Claims,
     State(state): State<AppState>,
     Json(json): Json<ScrapingRuns>,
 ) -> Result<ScrapingRunsSuccess, ScrapingRunsError> {
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(ScrapingRunsError::insert_error)?;
+
     let row: ScrapingRunsInsertResponse = sqlx::query_as::<_, ScrapingRunsInsertResponse>(
         "INSERT INTO scraping_runs (start_time, end_time) VALUES ($1, $2) RETURNING id",
     )
     .bind(json.start_time)
     .bind(json.end_time)
-    .fetch_one(&state.db)
+    .fetch_one(&mut *tx)
     .await
     .map_err(ScrapingRunsError::insert_error)?;
 
+    enqueue_event(
+        &mut *tx,
+        WebhookEvent::ScrapingRunFinished,
+        json!({
+            "id": row.id,
+            "start_time": json.start_time,
+            "end_time": json.end_time,
+        }),
+    )
+    .await
+    .map_err(ScrapingRunsError::insert_error)?;
+
+    tx.commit().await.map_err(ScrapingRunsError::insert_error)?;
+
+    let event = LiveEvent::ScrapingRun(ScrapingRunEvent::Finished {
+        id: row.id,
+        start_time: json.start_time,
+        end_time: json.end_time,
+    });
+    if let Err(e) = state.events.publish(&state.db, event).await {
+        tracing::error!("Error while publishing scraping run {}: {}", row.id, e);
+    }
+
     Ok(ScrapingRunsSuccess::created(row.id))
 }
 
@@ -42,6 +86,15 @@ pub(crate) async fn create_scraping_run(
 /// # Returns
 ///
 /// * `Result<Json<ScrapingRuns>, ScrapingRunsError>` - The result of the operation, either the last scraping run or an error.
+#[utoipa::path(
+    get,
+    path = "/scraping_runs",
+    tag = "scraping runs",
+    responses(
+        (status = 200, description = "The scraping run that ended last", body = ScrapingRuns),
+        (status = 500, description = "There is no scraping run or it could not be fetched", body = ErrorBody),
+    )
+)]
 pub(crate) async fn get_last_scraping_run_by_time(
     State(state): State<AppState>,
 ) -> Result<Json<ScrapingRuns>, ScrapingRunsError> {
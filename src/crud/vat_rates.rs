@@ -0,0 +1,282 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::errors::{
+    DutyRatesError, DutyRatesSuccess, ErrorBody, MessageBody, VatRatesError, VatRatesSuccess,
+};
+use crate::helpers::{is_foreign_key_violation, is_unique_violation};
+use crate::models::vat_rates::{
+    DutyRateAdd, DutyRateListParams, DutyRates, VatRateAdd, VatRates, VatRatesInsertResponse,
+};
+use axum::extract::{Path, Query, State};
+use axum::Json;
+
+/// Creates a new VAT rate in the database.
+///
+/// A rate applies to every price observed from its `valid_from` until the next rate takes effect.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `json` - The JSON payload containing the rate and the time it takes effect.
+///
+/// # Returns
+///
+/// * `Result<VatRatesSuccess, VatRatesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/vat_rates",
+    tag = "vat rates",
+    request_body = VatRateAdd,
+    responses(
+        (status = 201, description = "The VAT rate was created", body = MessageBody),
+        (status = 400, description = "The rate is negative", body = ErrorBody),
+        (status = 500, description = "The VAT rate could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn create_vat_rate(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<VatRateAdd>,
+) -> Result<VatRatesSuccess, VatRatesError> {
+    if json.rate.is_sign_negative() {
+        return Err(VatRatesError::invalid_input("rate cannot be negative"));
+    }
+
+    let row: VatRatesInsertResponse = sqlx::query_as::<_, VatRatesInsertResponse>(
+        "INSERT INTO vat_rates (rate, valid_from) VALUES ($1, $2) RETURNING id",
+    )
+    .bind(json.rate)
+    .bind(json.valid_from)
+    .fetch_one(&state.db)
+    .await
+    .map_err(VatRatesError::insert_error)?;
+
+    Ok(VatRatesSuccess::created(row.id))
+}
+
+/// Fetches all VAT rates from the database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<VatRates>>, VatRatesError>` - The result of the operation, either a list of VAT rates or an error.
+#[utoipa::path(
+    get,
+    path = "/vat_rates",
+    tag = "vat rates",
+    responses(
+        (status = 200, description = "All VAT rates", body = Vec<VatRates>),
+        (status = 500, description = "The VAT rates could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_vat_rates(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<VatRates>>, VatRatesError> {
+    let res = sqlx::query_as::<_, VatRates>(
+        "SELECT id, rate, valid_from FROM vat_rates ORDER BY valid_from",
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(VatRatesError::fetch_error)?;
+
+    Ok(Json(res))
+}
+
+/// Deletes a VAT rate from the database.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the VAT rate to delete.
+///
+/// # Returns
+///
+/// * `Result<VatRatesSuccess, VatRatesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/vat_rates/{id}",
+    tag = "vat rates",
+    params(("id" = i32, Path, description = "The ID of the VAT rate")),
+    responses(
+        (status = 200, description = "The VAT rate was deleted", body = MessageBody),
+        (status = 404, description = "The VAT rate does not exist", body = ErrorBody),
+        (status = 500, description = "The VAT rate could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn delete_vat_rate(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<VatRatesSuccess, VatRatesError> {
+    let res = sqlx::query("DELETE FROM vat_rates WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(VatRatesError::delete_error)?;
+
+    if res.rows_affected() == 0 {
+        return Err(VatRatesError::not_found());
+    }
+
+    Ok(VatRatesSuccess::deleted(id))
+}
+
+/// Creates a new excise duty rate in the database.
+///
+/// The amount is in DKK per liter excluding VAT and applies to prices of the product observed from
+/// its `valid_from` until the next amount of the same duty takes effect.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `json` - The JSON payload containing the product, the duty, its amount and the time it takes effect.
+///
+/// # Returns
+///
+/// * `Result<DutyRatesSuccess, DutyRatesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    post,
+    path = "/duty_rates",
+    tag = "duty rates",
+    request_body = DutyRateAdd,
+    responses(
+        (status = 201, description = "The duty rate was created", body = MessageBody),
+        (status = 400, description = "The duty rate is invalid", body = ErrorBody),
+        (status = 404, description = "The product does not exist", body = ErrorBody),
+        (status = 409, description = "The duty already has an amount from that time", body = ErrorBody),
+        (status = 500, description = "The duty rate could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn create_duty_rate(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<DutyRateAdd>,
+) -> Result<DutyRatesSuccess, DutyRatesError> {
+    if json.amount.is_sign_negative() {
+        return Err(DutyRatesError::invalid_input("amount cannot be negative"));
+    }
+    if json.name.trim().is_empty() {
+        return Err(DutyRatesError::invalid_input("name cannot be empty"));
+    }
+
+    let row: VatRatesInsertResponse = sqlx::query_as::<_, VatRatesInsertResponse>(
+        r#"
+        INSERT INTO duty_rates (product_id, name, amount, valid_from)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(json.product_id)
+    .bind(json.name.trim())
+    .bind(json.amount)
+    .bind(json.valid_from)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| {
+        if is_foreign_key_violation(&e) {
+            DutyRatesError::unknown("product")
+        } else if is_unique_violation(&e) {
+            DutyRatesError::conflict("the duty already has an amount from that time")
+        } else {
+            DutyRatesError::insert_error(e)
+        }
+    })?;
+
+    Ok(DutyRatesSuccess::created(row.id))
+}
+
+/// Fetches the excise duty rates from the database, optionally of a single product.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `params` - The query parameters choosing the product.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<DutyRates>>, DutyRatesError>` - The result of the operation, either a list of duty rates or an error.
+#[utoipa::path(
+    get,
+    path = "/duty_rates",
+    tag = "duty rates",
+    params(DutyRateListParams),
+    responses(
+        (status = 200, description = "The duty rates", body = Vec<DutyRates>),
+        (status = 500, description = "The duty rates could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_duty_rates(
+    State(state): State<AppState>,
+    Query(params): Query<DutyRateListParams>,
+) -> Result<Json<Vec<DutyRates>>, DutyRatesError> {
+    let res = sqlx::query_as::<_, DutyRates>(
+        r#"
+        SELECT
+            d.id, d.product_id, p.slug AS product, d.name, d.amount, d.valid_from
+        FROM
+            duty_rates d
+        JOIN
+            products p ON d.product_id = p.id
+        WHERE
+            $1::TEXT IS NULL OR p.slug = $1
+        ORDER BY
+            d.product_id, d.name, d.valid_from
+        "#,
+    )
+    .bind(params.product)
+    .fetch_all(&state.db)
+    .await
+    .map_err(DutyRatesError::fetch_error)?;
+
+    Ok(Json(res))
+}
+
+/// Deletes an excise duty rate from the database.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the duty rate to delete.
+///
+/// # Returns
+///
+/// * `Result<DutyRatesSuccess, DutyRatesError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/duty_rates/{id}",
+    tag = "duty rates",
+    params(("id" = i32, Path, description = "The ID of the duty rate")),
+    responses(
+        (status = 200, description = "The duty rate was deleted", body = MessageBody),
+        (status = 404, description = "The duty rate does not exist", body = ErrorBody),
+        (status = 500, description = "The duty rate could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn delete_duty_rate(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<DutyRatesSuccess, DutyRatesError> {
+    let res = sqlx::query("DELETE FROM duty_rates WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(DutyRatesError::delete_error)?;
+
+    if res.rows_affected() == 0 {
+        return Err(DutyRatesError::not_found());
+    }
+
+    Ok(DutyRatesSuccess::deleted(id))
+}
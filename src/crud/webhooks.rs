@@ -0,0 +1,222 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::errors::{ErrorBody, MessageBody, WebhooksError, WebhooksSuccess};
+use crate::models::webhooks::{
+    WebhookAdd, WebhookCreated, WebhookDeliveries, WebhookDeliveryQueryParams, WebhookDeliverySort,
+    Webhooks,
+};
+use crate::pagination::{PageParams, Paginated};
+use axum::extract::{OriginalUri, Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use rand::distributions::{Alphanumeric, DistString};
+use sqlx::{Postgres, QueryBuilder};
+
+/// Registers a new webhook endpoint.
+///
+/// When no secret is given one is generated. The secret is only returned here and is used to
+/// sign every payload sent to the endpoint.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `json` - The JSON payload containing the endpoint URL and the subscribed event types.
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<WebhookCreated>), WebhooksError>` - The result of the operation, either the ID and secret of the webhook or an error.
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    request_body = WebhookAdd,
+    responses(
+        (status = 201, description = "The webhook was registered, with the secret payloads are signed with", body = WebhookCreated),
+        (status = 400, description = "The webhook is invalid", body = ErrorBody),
+        (status = 500, description = "The webhook could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn create_webhook(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<WebhookAdd>,
+) -> Result<(StatusCode, Json<WebhookCreated>), WebhooksError> {
+    if !json.url.starts_with("https://") && !json.url.starts_with("http://") {
+        return Err(WebhooksError::invalid_input("url must be an HTTP(S) URL"));
+    }
+    if json.event_types.is_empty() {
+        return Err(WebhooksError::invalid_input(
+            "at least one event type is required",
+        ));
+    }
+
+    let secret = json
+        .secret
+        .unwrap_or_else(|| Alphanumeric.sample_string(&mut rand::thread_rng(), 40));
+    let event_types: Vec<&str> = json
+        .event_types
+        .iter()
+        .map(|event| event.as_str())
+        .collect();
+
+    let row = sqlx::query_as::<_, WebhookCreated>(
+        "INSERT INTO webhooks (url, secret, event_types) VALUES ($1, $2, $3) RETURNING id, secret",
+    )
+    .bind(json.url)
+    .bind(secret)
+    .bind(event_types)
+    .fetch_one(&state.db)
+    .await
+    .map_err(WebhooksError::insert_error)?;
+
+    Ok((StatusCode::CREATED, Json(row)))
+}
+
+/// Fetches all registered webhooks from the database.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<Webhooks>>, WebhooksError>` - The result of the operation, either a list of webhooks or an error.
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "All registered webhooks", body = Vec<Webhooks>),
+        (status = 500, description = "The webhooks could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn fetch_webhooks(
+    _claims: Claims,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Webhooks>>, WebhooksError> {
+    let res = sqlx::query_as::<_, Webhooks>(
+        "SELECT id, url, event_types, active, created_at FROM webhooks ORDER BY id",
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(WebhooksError::fetch_error)?;
+
+    Ok(Json(res))
+}
+
+/// Deletes a webhook and its delivery log from the database.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the webhook to delete.
+///
+/// # Returns
+///
+/// * `Result<WebhooksSuccess, WebhooksError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i32, Path, description = "The ID of the webhook")),
+    responses(
+        (status = 200, description = "The webhook was deleted", body = MessageBody),
+        (status = 404, description = "The webhook does not exist", body = ErrorBody),
+        (status = 500, description = "The webhook could not be deleted", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn delete_webhook(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<WebhooksSuccess, WebhooksError> {
+    let res = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(WebhooksError::delete_error)?;
+
+    if res.rows_affected() == 0 {
+        return Err(WebhooksError::not_found());
+    }
+
+    Ok(WebhooksSuccess::deleted(id))
+}
+
+/// Fetches a page of the delivery log of a webhook from the database.
+///
+/// Deliveries are sorted oldest first unless `order=desc` is given. The next page is linked in
+/// the `Link` and `X-Next-Cursor` response headers.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the webhook.
+/// * `uri` - The URI of the request, used to link to the next page.
+/// * `page` - The pagination and sorting parameters.
+/// * `params` - The query parameters for filtering by status.
+///
+/// # Returns
+///
+/// * `Result<Paginated<WebhookDeliveries>, WebhooksError>` - The result of the operation, either a page of deliveries or an error.
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(
+        ("id" = i32, Path, description = "The ID of the webhook"),
+        PageParams<WebhookDeliverySort>,
+        ("sort" = Option<WebhookDeliverySort>, Query, description = "The column to sort by"),
+        WebhookDeliveryQueryParams,
+    ),
+    responses(
+        (status = 200, description = "A page of the delivery log of the webhook, with the next page linked in the `Link` and `X-Next-Cursor` headers", body = Vec<WebhookDeliveries>),
+        (status = 400, description = "The cursor is malformed", body = ErrorBody),
+        (status = 500, description = "The delivery log could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn fetch_webhook_deliveries(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    OriginalUri(uri): OriginalUri,
+    Query(page): Query<PageParams<WebhookDeliverySort>>,
+    Query(params): Query<WebhookDeliveryQueryParams>,
+) -> Result<Paginated<WebhookDeliveries>, WebhooksError> {
+    let mut query = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT * FROM (
+            SELECT
+                id, webhook_id, event_type, payload, status, attempts, next_attempt_at,
+                last_status_code, last_error, created_at, delivered_at
+            FROM
+                webhook_deliveries
+        ) AS page
+        WHERE
+            page.webhook_id = "#,
+    );
+    query.push_bind(id);
+
+    if let Some(status) = &params.status {
+        query.push(" AND page.status = ").push_bind(status);
+    }
+
+    page.push_page(&mut query)
+        .map_err(WebhooksError::invalid_input)?;
+
+    let res = query
+        .build_query_as::<WebhookDeliveries>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(WebhooksError::fetch_error)?;
+
+    Ok(page.paginate(res, uri))
+}
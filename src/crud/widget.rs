@@ -0,0 +1,547 @@
+use crate::app_state::AppState;
+use crate::auth::jwt::Claims;
+use crate::errors::{
+    ErrorBody, MessageBody, WidgetApiKeysError, WidgetApiKeysSuccess, WidgetError,
+};
+use crate::models::products::DEFAULT_PRODUCT;
+use crate::models::providers::STALE_AFTER_HOURS;
+use crate::models::widget::{
+    WidgetApiKeyAdd, WidgetApiKeyCreated, WidgetApiKeys, WidgetParams, WidgetProvider,
+    WidgetResponse, WidgetUsage, API_KEY_HEADER, DEFAULT_REQUESTS_PER_MINUTE,
+    DEFAULT_WIDGET_PROVIDERS, MAX_WIDGET_PROVIDERS, WIDGET_MAX_AGE,
+};
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use axum::http::header::{
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_MAX_AGE, CACHE_CONTROL, CONTENT_TYPE, RETRY_AFTER, VARY, X_CONTENT_TYPE_OPTIONS,
+};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use rand::distributions::{Alphanumeric, DistString};
+use sha2::{Digest, Sha256};
+
+/// The longest name a JSONP callback may have.
+const MAX_CALLBACK_LENGTH: usize = 64;
+
+/// The number of characters of a key kept in plaintext to tell keys apart.
+const KEY_PREFIX_LENGTH: usize = 6;
+
+/// Fetches the cheapest providers delivering to a postcode, for embedding on other sites.
+///
+/// Only providers whose price was seen within the last 48 hours are listed. Every request needs
+/// an active API key, given as the `key` query parameter or the `X-Api-Key` header, and counts
+/// towards the key's usage and rate limit. Responses may be read from any origin and cached for
+/// five minutes, separately for every key sent in the header, and errors, including malformed
+/// query parameters, carry CORS headers too. If a `callback` is given, the data is returned as
+/// JSONP.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `headers` - The request headers, which may carry the API key.
+/// * `params` - The query parameters choosing the postcode, the product, the number of providers, the API key and the JSONP callback.
+///
+/// # Returns
+///
+/// * `Response` - The cheapest providers, or an error, with CORS headers.
+#[utoipa::path(
+    get,
+    path = "/widget",
+    tag = "widget",
+    params(
+        WidgetParams,
+        ("x-api-key" = Option<String>, Header, description = "The API key, if not given as the `key` parameter"),
+    ),
+    responses(
+        (status = 200, description = "The cheapest providers delivering to the postcode", content(
+            (WidgetResponse = "application/json"),
+            (String = "application/javascript"),
+        )),
+        (status = 400, description = "The parameters are invalid", body = ErrorBody),
+        (status = 401, description = "The API key is missing or not active", body = ErrorBody),
+        (status = 429, description = "The API key went over its rate limit", body = ErrorBody),
+        (status = 500, description = "The providers could not be fetched", body = ErrorBody),
+    )
+)]
+pub(crate) async fn fetch_widget(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    params: Result<Query<WidgetParams>, QueryRejection>,
+) -> Response {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(error) => return with_cors(rejection(error.status(), &error.body_text())),
+    };
+    let key = params
+        .key
+        .as_deref()
+        .or_else(|| headers.get(API_KEY_HEADER)?.to_str().ok());
+
+    let response = match authorize_key(&state, key).await {
+        Ok(()) => match fetch_cheapest(&state, &params).await {
+            Ok(data) => widget_response(data, params.callback.as_deref()),
+            Err(error) => error.into_response(),
+        },
+        Err(response) => response,
+    };
+
+    with_cors(response)
+}
+
+/// Answers the CORS preflight request browsers send before passing the API key in a header.
+#[utoipa::path(
+    options,
+    path = "/widget",
+    tag = "widget",
+    responses(
+        (status = 204, description = "Cross-origin requests may send the API key header"),
+    )
+)]
+pub(crate) async fn widget_preflight() -> Response {
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, OPTIONS"),
+    );
+    headers.insert(
+        ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static(API_KEY_HEADER),
+    );
+    headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("86400"));
+    with_cors(response)
+}
+
+/// Checks an API key and counts the request towards its usage and rate limit.
+///
+/// Keys are looked up by their hash. Requests are counted in windows of a calendar minute, kept in
+/// the database so the limit holds across instances.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool.
+/// * `key` - The API key sent with the request.
+///
+/// # Returns
+///
+/// * `Result<(), Response>` - `Ok(())` if the request may proceed, or the response turning it away.
+async fn authorize_key(state: &AppState, key: Option<&str>) -> Result<(), Response> {
+    let Some(key) = key else {
+        return Err(rejection(StatusCode::UNAUTHORIZED, "missing API key"));
+    };
+
+    let allowed: Option<bool> = sqlx::query_scalar(
+        r#"
+        WITH counted AS (
+            UPDATE widget_api_keys
+            SET
+                window_start = DATE_TRUNC('minute', LOCALTIMESTAMP),
+                window_requests = CASE
+                    WHEN window_start = DATE_TRUNC('minute', LOCALTIMESTAMP)
+                        THEN window_requests + 1
+                    ELSE 1
+                END
+            WHERE
+                key_hash = $1
+                AND active
+            RETURNING
+                id, window_requests <= requests_per_minute AS allowed
+        ),
+        usage AS (
+            INSERT INTO widget_usage (key_id, day, requests, rejected)
+            SELECT id, CURRENT_DATE, CASE WHEN allowed THEN 1 ELSE 0 END,
+                   CASE WHEN allowed THEN 0 ELSE 1 END
+            FROM counted
+            ON CONFLICT (key_id, day) DO UPDATE SET
+                requests = widget_usage.requests + EXCLUDED.requests,
+                rejected = widget_usage.rejected + EXCLUDED.rejected
+        )
+        SELECT allowed FROM counted
+        "#,
+    )
+    .bind(hash_key(key))
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| WidgetApiKeysError::fetch_error(e).into_response())?;
+
+    match allowed {
+        None => Err(rejection(StatusCode::UNAUTHORIZED, "invalid API key")),
+        Some(false) => {
+            let mut response = rejection(StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded");
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from_static("60"));
+            Err(response)
+        }
+        Some(true) => Ok(()),
+    }
+}
+
+/// Hashes an API key the way it is stored.
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Fetches the providers delivering to a postcode, cheapest first.
+async fn fetch_cheapest(
+    state: &AppState,
+    params: &WidgetParams,
+) -> Result<WidgetResponse, WidgetError> {
+    let limit = params.limit.unwrap_or(DEFAULT_WIDGET_PROVIDERS);
+    if !(1..=MAX_WIDGET_PROVIDERS).contains(&limit) {
+        return Err(WidgetError::invalid_input(format!(
+            "limit must be between 1 and {}",
+            MAX_WIDGET_PROVIDERS
+        )));
+    }
+    if let Some(callback) = &params.callback {
+        if !is_callback_name(callback) {
+            return Err(WidgetError::invalid_input("invalid callback name"));
+        }
+    }
+
+    let product = params
+        .product
+        .clone()
+        .unwrap_or_else(|| DEFAULT_PRODUCT.to_string());
+
+    let providers = sqlx::query_as::<_, WidgetProvider>(
+        r#"
+        WITH zone_providers AS (
+            SELECT DISTINCT
+                pz.provider_id
+            FROM
+                delivery_zone_postcodes zp
+            JOIN
+                provider_delivery_zones pz ON zp.zone_id = pz.zone_id
+            WHERE
+                $1 BETWEEN zp.postcode_from AND zp.postcode_to
+        ),
+        current_prices AS (
+            SELECT DISTINCT ON (op.provider_id)
+                op.provider_id, op.price, op.last_seen_at
+            FROM
+                oil_prices op
+            JOIN
+                products pr ON op.product_id = pr.id
+            WHERE
+                pr.slug = $2
+                AND op.deleted_at IS NULL
+                AND op.provider_id IN (SELECT provider_id FROM zone_providers)
+            ORDER BY
+                op.provider_id, op.valid_from DESC, op.id DESC
+        )
+        SELECT
+            p.name,
+            p.url,
+            ROUND(cp.price * (1 + vat_rate_at(LOCALTIMESTAMP)), 2) AS price,
+            cp.last_seen_at AS updated_at,
+            CAST(EXTRACT(EPOCH FROM LOCALTIMESTAMP - cp.last_seen_at) / 60 AS BIGINT)
+                AS age_minutes
+        FROM
+            current_prices cp
+        JOIN
+            providers p ON cp.provider_id = p.id
+        WHERE
+            cp.last_seen_at >= LOCALTIMESTAMP - MAKE_INTERVAL(hours => $3)
+        ORDER BY
+            price, p.name
+        LIMIT $4
+        "#,
+    )
+    .bind(params.postcode)
+    .bind(&product)
+    .bind(STALE_AFTER_HOURS as i32)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await
+    .map_err(WidgetError::fetch_error)?;
+
+    Ok(WidgetResponse {
+        postcode: params.postcode,
+        product,
+        providers,
+    })
+}
+
+/// Checks that a JSONP callback is a plain, possibly dotted, JavaScript identifier.
+fn is_callback_name(name: &str) -> bool {
+    name.len() <= MAX_CALLBACK_LENGTH
+        && name.split('.').all(|part| match part.chars().next() {
+            Some(first) => {
+                !first.is_ascii_digit()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+            }
+            None => false,
+        })
+}
+
+/// Sends widget data as cacheable JSON, or as JSONP if a callback is given.
+///
+/// Responses vary by the API key header, so a shared cache never answers for one key with a
+/// response to another, and every key is still checked and counted.
+fn widget_response(data: WidgetResponse, callback: Option<&str>) -> Response {
+    let cache_control = format!(
+        "public, max-age={}, stale-while-revalidate={}",
+        WIDGET_MAX_AGE,
+        WIDGET_MAX_AGE * 12
+    );
+
+    let Some(callback) = callback else {
+        return (
+            [
+                (CACHE_CONTROL, cache_control),
+                (VARY, API_KEY_HEADER.to_string()),
+            ],
+            Json(data),
+        )
+            .into_response();
+    };
+
+    // The comment keeps the response from starting with bytes an attacker could choose.
+    let body = format!(
+        "/**/{}({});",
+        callback,
+        serde_json::to_string(&data).unwrap_or_default()
+    );
+    (
+        [
+            (
+                CONTENT_TYPE,
+                "application/javascript; charset=utf-8".to_string(),
+            ),
+            (X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (CACHE_CONTROL, cache_control),
+            (VARY, API_KEY_HEADER.to_string()),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Builds the response to a widget request that is not answered with data.
+///
+/// # Arguments
+///
+/// * `status` - The status of the response.
+/// * `message` - A description of what went wrong.
+///
+/// # Returns
+///
+/// * `Response` - The error response, without CORS headers.
+fn rejection(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(ErrorBody {
+            error: message.to_string(),
+        }),
+    )
+        .into_response()
+}
+
+/// Lets any site read the response.
+fn with_cors(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    response
+}
+
+/// Creates an API key for an embedder.
+///
+/// The key is generated and only returned here; only its hash and first characters are stored.
+/// The rate limit defaults to 60 requests per minute.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `json` - The JSON payload containing the name of the embedder and the rate limit.
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<WidgetApiKeyCreated>), WidgetApiKeysError>` - The result of the operation, either the ID and the key or an error.
+#[utoipa::path(
+    post,
+    path = "/widget/keys",
+    tag = "widget",
+    request_body = WidgetApiKeyAdd,
+    responses(
+        (status = 201, description = "The API key was created. The key is only returned here", body = WidgetApiKeyCreated),
+        (status = 400, description = "The name or the rate limit is invalid", body = ErrorBody),
+        (status = 500, description = "The API key could not be stored", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn create_widget_api_key(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<WidgetApiKeyAdd>,
+) -> Result<(StatusCode, Json<WidgetApiKeyCreated>), WidgetApiKeysError> {
+    if json.name.trim().is_empty() {
+        return Err(WidgetApiKeysError::invalid_input("name must not be empty"));
+    }
+    let requests_per_minute = json
+        .requests_per_minute
+        .unwrap_or(DEFAULT_REQUESTS_PER_MINUTE);
+    if requests_per_minute <= 0 {
+        return Err(WidgetApiKeysError::invalid_input(
+            "requests_per_minute must be greater than zero",
+        ));
+    }
+
+    let key = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+
+    let id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO widget_api_keys (key_hash, key_prefix, name, requests_per_minute)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(hash_key(&key))
+    .bind(&key[..KEY_PREFIX_LENGTH])
+    .bind(json.name.trim())
+    .bind(requests_per_minute)
+    .fetch_one(&state.db)
+    .await
+    .map_err(WidgetApiKeysError::insert_error)?;
+
+    Ok((StatusCode::CREATED, Json(WidgetApiKeyCreated { id, key })))
+}
+
+/// Fetches all widget API keys with their usage.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<WidgetApiKeys>>, WidgetApiKeysError>` - The result of the operation, either a list of API keys or an error.
+#[utoipa::path(
+    get,
+    path = "/widget/keys",
+    tag = "widget",
+    responses(
+        (status = 200, description = "All API keys with their usage", body = Vec<WidgetApiKeys>),
+        (status = 500, description = "The API keys could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn fetch_widget_api_keys(
+    _claims: Claims,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<WidgetApiKeys>>, WidgetApiKeysError> {
+    let rows = sqlx::query_as::<_, WidgetApiKeys>(
+        r#"
+        SELECT
+            k.id,
+            k.name,
+            k.key_prefix,
+            k.requests_per_minute,
+            k.active,
+            k.created_at,
+            COALESCE(SUM(u.requests) FILTER (WHERE u.day = CURRENT_DATE), 0)::BIGINT
+                AS requests_today,
+            COALESCE(SUM(u.requests), 0)::BIGINT AS requests_total
+        FROM
+            widget_api_keys k
+        LEFT JOIN
+            widget_usage u ON u.key_id = k.id
+        GROUP BY
+            k.id
+        ORDER BY
+            k.id
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(WidgetApiKeysError::fetch_error)?;
+
+    Ok(Json(rows))
+}
+
+/// Fetches the daily usage of a widget API key, newest first.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the API key.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<WidgetUsage>>, WidgetApiKeysError>` - The result of the operation, either the usage of every day the key was used or an error.
+#[utoipa::path(
+    get,
+    path = "/widget/keys/{id}/usage",
+    tag = "widget",
+    params(("id" = i32, Path, description = "The ID of the API key")),
+    responses(
+        (status = 200, description = "The usage of every day the key was used, newest first", body = Vec<WidgetUsage>),
+        (status = 500, description = "The usage could not be fetched", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn fetch_widget_usage(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<WidgetUsage>>, WidgetApiKeysError> {
+    let rows = sqlx::query_as::<_, WidgetUsage>(
+        "SELECT day, requests, rejected FROM widget_usage WHERE key_id = $1 ORDER BY day DESC",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(WidgetApiKeysError::fetch_error)?;
+
+    Ok(Json(rows))
+}
+
+/// Revokes a widget API key. The key is kept, along with its usage, but no longer accepted.
+///
+/// # Arguments
+///
+/// * `_claims` - The JWT claims of the authenticated user.
+/// * `state` - The application state containing the database connection pool.
+/// * `id` - The ID of the API key to revoke.
+///
+/// # Returns
+///
+/// * `Result<WidgetApiKeysSuccess, WidgetApiKeysError>` - The result of the operation, either a success or an error.
+#[utoipa::path(
+    delete,
+    path = "/widget/keys/{id}",
+    tag = "widget",
+    params(("id" = i32, Path, description = "The ID of the API key")),
+    responses(
+        (status = 200, description = "The API key was revoked", body = MessageBody),
+        (status = 404, description = "The API key does not exist", body = ErrorBody),
+        (status = 500, description = "The API key could not be revoked", body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub(crate) async fn revoke_widget_api_key(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<WidgetApiKeysSuccess, WidgetApiKeysError> {
+    let res = sqlx::query("UPDATE widget_api_keys SET active = FALSE WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(WidgetApiKeysError::delete_error)?;
+
+    if res.rows_affected() == 0 {
+        return Err(WidgetApiKeysError::not_found());
+    }
+
+    Ok(WidgetApiKeysSuccess::deleted(id))
+}
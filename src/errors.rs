@@ -2,8 +2,23 @@ use axum::body::Body;
 use axum::http::{Response, StatusCode};
 use axum::response::IntoResponse;
 use axum::Json;
-use serde_json::json;
+use serde::Serialize;
 use sqlx::Error as SqlxError;
+use utoipa::ToSchema;
+
+/// The body of an error response.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ErrorBody {
+    /// A description of what went wrong.
+    pub(crate) error: String,
+}
+
+/// The body of a response to a successful change.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct MessageBody {
+    /// A description of what was changed, naming the ID of the resource.
+    pub(crate) message: String,
+}
 
 /// Enum representing different types of application errors.
 pub(crate) enum AppError {
@@ -26,6 +41,22 @@ pub(crate) enum AppError {
     NotFound {
         resource: &'static str,
     },
+    InvalidInput {
+        resource: &'static str,
+        message: String,
+    },
+    Conflict {
+        resource: &'static str,
+        message: String,
+    },
+    Unauthorized {
+        resource: &'static str,
+        message: String,
+    },
+    RenderError {
+        resource: &'static str,
+        message: String,
+    },
 }
 
 impl IntoResponse for AppError {
@@ -38,33 +69,57 @@ impl IntoResponse for AppError {
         let (status, body) = match self {
             AppError::InsertError { resource, error } => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": format!("Error while inserting {}: {}", resource, error),
-                })),
+                Json(ErrorBody {
+                    error: format!("Error while inserting {}: {}", resource, error),
+                }),
             ),
             AppError::FetchError { resource, error } => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": format!("Error while fetching {}: {}", resource, error),
-                })),
+                Json(ErrorBody {
+                    error: format!("Error while fetching {}: {}", resource, error),
+                }),
             ),
             AppError::UpdateError { resource, error } => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": format!("Error while updating {}: {}", resource, error),
-                })),
+                Json(ErrorBody {
+                    error: format!("Error while updating {}: {}", resource, error),
+                }),
             ),
             AppError::DeleteError { resource, error } => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": format!("Error while deleting {}: {}", resource, error),
-                })),
+                Json(ErrorBody {
+                    error: format!("Error while deleting {}: {}", resource, error),
+                }),
             ),
             AppError::NotFound { resource } => (
                 StatusCode::NOT_FOUND,
-                Json(json!({
-                    "error": format!("{} not found", resource),
-                })),
+                Json(ErrorBody {
+                    error: format!("{} not found", resource),
+                }),
+            ),
+            AppError::InvalidInput { resource, message } => (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorBody {
+                    error: format!("Invalid {}: {}", resource, message),
+                }),
+            ),
+            AppError::Conflict { resource, message } => (
+                StatusCode::CONFLICT,
+                Json(ErrorBody {
+                    error: format!("Conflicting {}: {}", resource, message),
+                }),
+            ),
+            AppError::Unauthorized { resource, message } => (
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorBody {
+                    error: format!("Unauthorized {}: {}", resource, message),
+                }),
+            ),
+            AppError::RenderError { resource, message } => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorBody {
+                    error: format!("Error while rendering {}: {}", resource, message),
+                }),
             ),
         };
         (status, body).into_response()
@@ -118,6 +173,7 @@ macro_rules! impl_error {
             }
         }
 
+        #[allow(dead_code)]
         impl $name {
             /// Creates a new insert error.
             ///
@@ -198,6 +254,87 @@ macro_rules! impl_error {
                 }
                 .into()
             }
+
+            /// Creates a new not found error for another resource the request refers to.
+            ///
+            /// # Arguments
+            ///
+            /// * `resource` - The name of the resource that does not exist.
+            ///
+            /// # Returns
+            ///
+            /// * `Self` - The specific error type.
+            pub fn unknown(resource: &'static str) -> Self {
+                AppError::NotFound { resource }.into()
+            }
+
+            /// Creates a new invalid input error.
+            ///
+            /// # Arguments
+            ///
+            /// * `message` - A description of what is wrong with the input.
+            ///
+            /// # Returns
+            ///
+            /// * `Self` - The specific error type.
+            pub fn invalid_input(message: impl Into<String>) -> Self {
+                AppError::InvalidInput {
+                    resource: $resource,
+                    message: message.into(),
+                }
+                .into()
+            }
+
+            /// Creates a new conflict error.
+            ///
+            /// # Arguments
+            ///
+            /// * `message` - A description of what the request conflicts with.
+            ///
+            /// # Returns
+            ///
+            /// * `Self` - The specific error type.
+            pub fn conflict(message: impl Into<String>) -> Self {
+                AppError::Conflict {
+                    resource: $resource,
+                    message: message.into(),
+                }
+                .into()
+            }
+
+            /// Creates a new unauthorized error, for requests that need authentication.
+            ///
+            /// # Arguments
+            ///
+            /// * `message` - A description of what requires authentication.
+            ///
+            /// # Returns
+            ///
+            /// * `Self` - The specific error type.
+            pub fn unauthorized(message: impl Into<String>) -> Self {
+                AppError::Unauthorized {
+                    resource: $resource,
+                    message: message.into(),
+                }
+                .into()
+            }
+
+            /// Creates a new render error.
+            ///
+            /// # Arguments
+            ///
+            /// * `message` - A description of why the resource could not be rendered.
+            ///
+            /// # Returns
+            ///
+            /// * `Self` - The specific error type.
+            pub fn render_error(message: impl Into<String>) -> Self {
+                AppError::RenderError {
+                    resource: $resource,
+                    message: message.into(),
+                }
+                .into()
+            }
         }
     };
 }
@@ -219,21 +356,21 @@ impl IntoResponse for AppSuccess {
         let (status, body) = match self {
             AppSuccess::Created { resource, id } => (
                 StatusCode::CREATED,
-                Json(json!({
-                    "message": format!("Created {} with id: {}", resource, id),
-                })),
+                Json(MessageBody {
+                    message: format!("Created {} with id: {}", resource, id),
+                }),
             ),
             AppSuccess::Updated { resource, id } => (
                 StatusCode::OK,
-                Json(json!({
-                    "message": format!("Updated {} with id: {}", resource, id),
-                })),
+                Json(MessageBody {
+                    message: format!("Updated {} with id: {}", resource, id),
+                }),
             ),
             AppSuccess::Deleted { resource, id } => (
                 StatusCode::OK,
-                Json(json!({
-                    "message": format!("Deleted {} with id: {}", resource, id),
-                })),
+                Json(MessageBody {
+                    message: format!("Deleted {} with id: {}", resource, id),
+                }),
             ),
         };
         (status, body).into_response()
@@ -287,6 +424,7 @@ macro_rules! impl_success {
             }
         }
 
+        #[allow(dead_code)]
         impl $name {
             /// Creates a new created success response.
             ///
@@ -300,7 +438,7 @@ macro_rules! impl_success {
             pub fn created(id: i32) -> Self {
                 AppSuccess::Created {
                     resource: $resource,
-                    id: id,
+                    id,
                 }
                 .into()
             }
@@ -317,7 +455,7 @@ macro_rules! impl_success {
             pub fn deleted(id: i32) -> Self {
                 AppSuccess::Deleted {
                     resource: $resource,
-                    id: id,
+                    id,
                 }
                 .into()
             }
@@ -334,7 +472,7 @@ macro_rules! impl_success {
             pub fn updated(id: i32) -> Self {
                 AppSuccess::Updated {
                     resource: $resource,
-                    id: id,
+                    id,
                 }
                 .into()
             }
@@ -346,13 +484,38 @@ macro_rules! impl_success {
 impl_success!(ProvidersSuccess, "provider");
 impl_success!(DeliveryZonesSuccess, "delivery zone");
 impl_success!(PricesSuccess, "price");
+impl_success!(ProductsSuccess, "product");
 impl_success!(ScrapingRunsSuccess, "scraping run");
+impl_success!(FeesSuccess, "fee rules");
+impl_success!(VatRatesSuccess, "VAT rate");
+impl_success!(DutyRatesSuccess, "duty rate");
+impl_success!(PriceAlertsSuccess, "price alert");
+impl_success!(WebhooksSuccess, "webhook");
+impl_success!(QuarantineSuccess, "quarantined price");
+impl_success!(ReferencesSuccess, "reference series");
+impl_success!(WidgetApiKeysSuccess, "widget API key");
 
 // Implement specific error enums using the macro
 impl_error!(ProvidersError, "provider");
 impl_error!(DeliveryZonesError, "delivery zone");
 impl_error!(PricesError, "price");
+impl_error!(ProductsError, "product");
 impl_error!(ScrapingRunsError, "scraping run");
+impl_error!(FeesError, "fee rules");
+impl_error!(QuotesError, "quote");
+impl_error!(VatRatesError, "VAT rate");
+impl_error!(DutyRatesError, "duty rate");
+impl_error!(PriceAlertsError, "price alert");
+impl_error!(WebhooksError, "webhook");
+impl_error!(QuarantineError, "quarantined price");
+impl_error!(MarketIndexError, "market index");
+impl_error!(ReferencesError, "reference series");
+impl_error!(FxRatesError, "FX rate");
+impl_error!(ForecastError, "forecast");
+impl_error!(ChartsError, "chart");
+impl_error!(WidgetError, "widget");
+impl_error!(WidgetApiKeysError, "widget API key");
+impl_error!(FeedsError, "feed");
 
 impl From<DeliveryZonesError> for ProvidersError {
     /// Converts `DeliveryZonesError` into `ProvidersError`.
@@ -0,0 +1,235 @@
+use crate::pagination::{link_next, Paginated};
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::header::{ACCEPT, CONTENT_TYPE};
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse, Response};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use utoipa::ToSchema;
+
+/// The byte order mark Excel needs to read a CSV file as UTF-8.
+const UTF8_BOM: &str = "\u{feff}";
+
+/// The characters Excel starts a formula with when a cell begins with them.
+const FORMULA_PREFIXES: [char; 4] = ['=', '+', '-', '@'];
+
+/// The format a list of records is sent in.
+///
+/// Negotiated from the `Accept` header: `text/csv`, `application/vnd.ms-excel` and
+/// `application/x-ndjson` select the respective formats, anything else selects JSON. Of several
+/// supported formats, the one with the highest quality value is picked.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+    Excel,
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// Picks the supported format with the highest quality value from an `Accept` header.
+    /// Of formats with the same quality, the first one listed wins, and formats with a quality
+    /// of zero are never picked.
+    fn from_accept(accept: &str) -> Self {
+        let mut best: Option<(ExportFormat, f32)> = None;
+        for media_range in accept.split(',') {
+            let mut params = media_range.split(';');
+            let format = match params.next().unwrap_or_default().trim() {
+                "text/csv" => ExportFormat::Csv,
+                "application/vnd.ms-excel" => ExportFormat::Excel,
+                "application/x-ndjson" | "application/ndjson" => ExportFormat::Ndjson,
+                "application/json" => ExportFormat::Json,
+                _ => continue,
+            };
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if quality > 0.0 && best.is_none_or(|(_, best)| quality > best) {
+                best = Some((format, quality));
+            }
+        }
+
+        best.map(|(format, _)| format).unwrap_or_default()
+    }
+
+    /// Returns the content type of the format.
+    pub(crate) fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Excel => "application/vnd.ms-excel; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// Returns the text a list of `T` starts with in this format: the header row for CSV,
+    /// preceded by a byte order mark for Excel, and nothing otherwise.
+    pub(crate) fn preamble<T: CsvRecord>(self) -> String {
+        match self {
+            ExportFormat::Csv => self.write_row(T::HEADER.iter().map(|name| name.to_string())),
+            ExportFormat::Excel => format!(
+                "{}{}",
+                UTF8_BOM,
+                self.write_row(T::HEADER.iter().map(|name| name.to_string()))
+            ),
+            ExportFormat::Json | ExportFormat::Ndjson => String::new(),
+        }
+    }
+
+    /// Formats a single record as a line of this format. JSON records are formatted as NDJSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record to format.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The formatted line, including its line terminator.
+    pub(crate) fn line<T: CsvRecord + Serialize>(self, record: &T) -> String {
+        match self {
+            ExportFormat::Json | ExportFormat::Ndjson => {
+                let mut line = serde_json::to_string(record).unwrap_or_default();
+                line.push('\n');
+                line
+            }
+            ExportFormat::Csv | ExportFormat::Excel => self.write_row(
+                record
+                    .csv_fields()
+                    .into_iter()
+                    .map(|field| field.format(self)),
+            ),
+        }
+    }
+
+    /// Writes a row of CSV with the delimiter and line terminator of this format.
+    fn write_row(self, fields: impl IntoIterator<Item = String>) -> String {
+        let mut builder = csv::WriterBuilder::new();
+        if self == ExportFormat::Excel {
+            // Danish Excel expects semicolons, since the comma is the decimal separator
+            builder.delimiter(b';').terminator(csv::Terminator::CRLF);
+        }
+
+        let mut writer = builder.from_writer(vec![]);
+        // Writing to memory cannot fail
+        let _ = writer.write_record(fields);
+        let bytes = writer.into_inner().unwrap_or_default();
+        String::from_utf8(bytes).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ExportFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    /// Negotiates the format from the `Accept` header.
+    ///
+    /// # Arguments
+    ///
+    /// * `parts` - The request parts.
+    /// * `_state` - The state.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ExportFormat, Infallible>` - The negotiated format.
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .headers
+            .get(ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .map(ExportFormat::from_accept)
+            .unwrap_or_default())
+    }
+}
+
+/// A value in a CSV record.
+pub(crate) enum CsvField {
+    Text(String),
+    Number(Decimal),
+}
+
+impl CsvField {
+    /// Formats the value, using a decimal comma for Excel.
+    ///
+    /// For Excel, text that would be read as a formula is prefixed with an apostrophe, so a
+    /// cell is always shown as the text it holds.
+    fn format(self, format: ExportFormat) -> String {
+        match self {
+            CsvField::Text(text)
+                if format == ExportFormat::Excel && text.starts_with(FORMULA_PREFIXES) =>
+            {
+                format!("'{}", text)
+            }
+            CsvField::Text(text) => text,
+            CsvField::Number(number) if format == ExportFormat::Excel => {
+                number.to_string().replace('.', ",")
+            }
+            CsvField::Number(number) => number.to_string(),
+        }
+    }
+}
+
+impl From<String> for CsvField {
+    fn from(text: String) -> Self {
+        CsvField::Text(text)
+    }
+}
+
+impl From<Decimal> for CsvField {
+    fn from(number: Decimal) -> Self {
+        CsvField::Number(number)
+    }
+}
+
+/// A record that can be exported as a row of CSV.
+pub(crate) trait CsvRecord {
+    /// The names of the columns.
+    const HEADER: &'static [&'static str];
+
+    /// Returns the values of the columns, in the order of `HEADER`.
+    fn csv_fields(&self) -> Vec<CsvField>;
+}
+
+/// A page of a list, sent in the format negotiated with the client.
+pub(crate) struct Negotiated<T> {
+    pub(crate) page: Paginated<T>,
+    pub(crate) format: ExportFormat,
+}
+
+impl<T> Paginated<T> {
+    /// Sends the page in a negotiated format.
+    pub(crate) fn negotiated(self, format: ExportFormat) -> Negotiated<T> {
+        Negotiated { page: self, format }
+    }
+}
+
+impl<T: CsvRecord + Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        if self.format == ExportFormat::Json {
+            return self.page.into_response();
+        }
+
+        let link = self.page.next_link();
+        let mut body = self.format.preamble::<T>();
+        for item in &self.page.items {
+            body.push_str(&self.format.line(item));
+        }
+
+        let mut response = body.into_response();
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(self.format.content_type()),
+        );
+        link_next(&mut response, link, self.page.next_cursor.as_ref());
+        response
+    }
+}
@@ -0,0 +1,97 @@
+use crate::helpers::escape_xml;
+use chrono::{NaiveDateTime, SecondsFormat};
+use std::fmt::Write;
+
+/// The authority and date the IDs of feeds and entries are minted under, as `tag:` URIs.
+const TAG_AUTHORITY: &str = "oliepriser.dk,2024";
+
+/// The media type of Atom feeds.
+pub(crate) const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+/// An Atom feed.
+pub(crate) struct AtomFeed {
+    /// The path the ID of the feed is made from, such as `feeds/prices`.
+    pub(crate) id: String,
+    pub(crate) title: String,
+    /// The absolute URL the feed is served from.
+    pub(crate) self_link: String,
+    pub(crate) entries: Vec<AtomEntry>,
+}
+
+/// An entry of an Atom feed.
+pub(crate) struct AtomEntry {
+    /// The path the ID of the entry is made from, such as `price/42`. It must never change.
+    pub(crate) id: String,
+    pub(crate) title: String,
+    pub(crate) link: Option<String>,
+    pub(crate) author: String,
+    pub(crate) published: NaiveDateTime,
+    pub(crate) updated: NaiveDateTime,
+    pub(crate) summary: String,
+}
+
+impl AtomFeed {
+    /// Renders the feed as an Atom document.
+    ///
+    /// The feed was updated when its most recently updated entry was, or now if it has none.
+    /// Timestamps are taken to be UTC.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The Atom document.
+    pub(crate) fn render(&self) -> String {
+        let updated = self
+            .entries
+            .iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+
+        let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        let _ = write!(
+            xml,
+            r#"<feed xmlns="http://www.w3.org/2005/Atom"><id>{}</id><title>{}</title><updated>{}</updated><link rel="self" href="{}"/><author><name>Oliepriser</name></author>"#,
+            escape_xml(&tag(&self.id)),
+            escape_xml(&self.title),
+            timestamp(updated),
+            escape_xml(&self.self_link)
+        );
+
+        for entry in &self.entries {
+            let _ = write!(
+                xml,
+                "<entry><id>{}</id><title>{}</title><published>{}</published><updated>{}</updated><author><name>{}</name></author>",
+                tag(&entry.id),
+                escape_xml(&entry.title),
+                timestamp(entry.published),
+                timestamp(entry.updated),
+                escape_xml(&entry.author)
+            );
+            if let Some(link) = &entry.link {
+                let _ = write!(
+                    xml,
+                    r#"<link rel="alternate" href="{}"/>"#,
+                    escape_xml(link)
+                );
+            }
+            let _ = write!(
+                xml,
+                "<summary>{}</summary></entry>",
+                escape_xml(&entry.summary)
+            );
+        }
+
+        xml.push_str("</feed>");
+        xml
+    }
+}
+
+/// Makes a `tag:` URI from a path.
+fn tag(path: &str) -> String {
+    format!("tag:{}:{}", TAG_AUTHORITY, path)
+}
+
+/// Formats a UTC timestamp as RFC 3339.
+fn timestamp(at: NaiveDateTime) -> String {
+    at.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
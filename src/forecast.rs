@@ -0,0 +1,212 @@
+/// The smoothing parameters tried when fitting a model, from 0.05 to 0.95.
+const ALPHA_STEPS: std::ops::RangeInclusive<u32> = 1..=19;
+
+/// The damping factors tried when fitting a model. A factor of 1 leaves the trend undamped.
+const PHI_CANDIDATES: [f64; 5] = [0.8, 0.9, 0.95, 0.98, 1.0];
+
+/// The fewest observations a model is fitted to.
+pub(crate) const MIN_OBSERVATIONS: usize = 14;
+
+/// An exponential smoothing model with a damped additive trend, fitted to a daily price series.
+///
+/// The model is written in error correction form: after each observation the level moves by
+/// `alpha` times the one step error and the trend by `beta` times it, while `phi` damps the trend
+/// so forecasts level off instead of trending forever.
+pub(crate) struct Model {
+    pub(crate) alpha: f64,
+    pub(crate) beta: f64,
+    pub(crate) phi: f64,
+    /// The level after the last observation.
+    pub(crate) level: f64,
+    /// The trend per day after the last observation.
+    pub(crate) trend: f64,
+    /// The standard deviation of the one step errors.
+    pub(crate) sigma: f64,
+    pub(crate) observations: usize,
+}
+
+/// The errors of forecasts made for the last days of a series from the days before them.
+pub(crate) struct Backtest {
+    pub(crate) days: usize,
+    pub(crate) mean_absolute_error: f64,
+    pub(crate) mean_absolute_percentage_error: f64,
+}
+
+impl Model {
+    /// Fits a model to a series by picking the parameters with the smallest sum of squared one
+    /// step errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The daily values, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Model>` - The fitted model, or `None` if the series holds too few observations.
+    pub(crate) fn fit(series: &[f64]) -> Option<Model> {
+        if series.len() < MIN_OBSERVATIONS {
+            return None;
+        }
+
+        let mut best: Option<(f64, Model)> = None;
+        for alpha in ALPHA_STEPS.map(|step| f64::from(step) / 20.0) {
+            // The trend never reacts more than the level does.
+            for beta in (0..=ALPHA_STEPS.end() / 2)
+                .map(|step| f64::from(step) / 20.0)
+                .filter(|beta| *beta <= alpha)
+            {
+                for phi in PHI_CANDIDATES {
+                    let (sse, model) = Model::smooth(series, alpha, beta, phi);
+                    if best.as_ref().is_none_or(|(best_sse, _)| sse < *best_sse) {
+                        best = Some((sse, model));
+                    }
+                }
+            }
+        }
+
+        best.map(|(_, model)| model)
+    }
+
+    /// Runs the model over a series with the given parameters.
+    ///
+    /// Returns the sum of squared one step errors along with the model as it stands after the
+    /// last observation.
+    fn smooth(series: &[f64], alpha: f64, beta: f64, phi: f64) -> (f64, Model) {
+        let mut level = series[0];
+        let mut trend = 0.0;
+        let mut sse = 0.0;
+
+        for value in &series[1..] {
+            let error = value - (level + phi * trend);
+            sse += error * error;
+            level += phi * trend + alpha * error;
+            trend = phi * trend + beta * error;
+        }
+
+        let errors = series.len() - 1;
+        let sigma = (sse / errors.saturating_sub(3).max(1) as f64).sqrt();
+
+        (
+            sse,
+            Model {
+                alpha,
+                beta,
+                phi,
+                level,
+                trend,
+                sigma,
+                observations: series.len(),
+            },
+        )
+    }
+
+    /// The sum of the damping factor's powers from 1 to `horizon`.
+    fn damped(&self, horizon: usize) -> f64 {
+        (1..=horizon).map(|i| self.phi.powi(i as i32)).sum()
+    }
+
+    /// Forecasts the value a number of days after the last observation.
+    pub(crate) fn forecast(&self, horizon: usize) -> f64 {
+        self.level + self.damped(horizon) * self.trend
+    }
+
+    /// The standard error of the forecast a number of days after the last observation.
+    pub(crate) fn standard_error(&self, horizon: usize) -> f64 {
+        let variance: f64 = 1.0
+            + (1..horizon)
+                .map(|j| (self.alpha + self.beta * self.damped(j)).powi(2))
+                .sum::<f64>();
+        self.sigma * variance.sqrt()
+    }
+}
+
+impl Backtest {
+    /// Holds out the last days of a series, fits a model to the days before them and measures how
+    /// far its forecasts were off.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The daily values, oldest first.
+    /// * `days` - The number of days to hold out.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Backtest>` - The errors of the forecasts, or `None` if too few days would be left
+    ///   to fit a model to.
+    pub(crate) fn run(series: &[f64], days: usize) -> Option<Backtest> {
+        if days == 0 || series.len() < days + MIN_OBSERVATIONS {
+            return None;
+        }
+
+        let (training, held_out) = series.split_at(series.len() - days);
+        let model = Model::fit(training)?;
+
+        let (absolute, percentage) =
+            held_out
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(absolute, percentage), (i, actual)| {
+                    let error = (actual - model.forecast(i + 1)).abs();
+                    (
+                        absolute + error,
+                        percentage + error / actual.abs().max(f64::EPSILON),
+                    )
+                });
+
+        Some(Backtest {
+            days,
+            mean_absolute_error: absolute / days as f64,
+            mean_absolute_percentage_error: percentage / days as f64 * 100.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backtest, Model, MIN_OBSERVATIONS};
+
+    /// How close computed values must be to the expected ones.
+    const TOLERANCE: f64 = 1e-6;
+
+    #[test]
+    fn constant_series_forecasts_the_constant() {
+        let series = vec![10.0; 30];
+        let model = Model::fit(&series).unwrap();
+
+        assert!(model.trend.abs() < TOLERANCE);
+        assert!(model.sigma.abs() < TOLERANCE);
+        for horizon in [1, 7, 30] {
+            assert!((model.forecast(horizon) - 10.0).abs() < TOLERANCE);
+        }
+
+        let backtest = Backtest::run(&series, 7).unwrap();
+        assert_eq!(backtest.days, 7);
+        assert!(backtest.mean_absolute_error < TOLERANCE);
+        assert!(backtest.mean_absolute_percentage_error < TOLERANCE);
+    }
+
+    #[test]
+    fn linear_trend_is_followed() {
+        let series: Vec<f64> = (0..60).map(|day| 10.0 + 0.1 * f64::from(day)).collect();
+        let model = Model::fit(&series).unwrap();
+
+        assert!((model.trend - 0.1).abs() < 0.01);
+        assert!((model.forecast(1) - 16.0).abs() < 0.05);
+        assert!(model.forecast(7) > model.forecast(1));
+
+        let backtest = Backtest::run(&series, 7).unwrap();
+        assert!(backtest.mean_absolute_percentage_error < 1.0);
+    }
+
+    #[test]
+    fn too_short_series_is_not_fitted() {
+        let series = vec![10.0; MIN_OBSERVATIONS - 1];
+        assert!(Model::fit(&series).is_none());
+        assert!(Model::fit(&[]).is_none());
+
+        let series = vec![10.0; MIN_OBSERVATIONS + 6];
+        assert!(Backtest::run(&series, 7).is_none());
+        assert!(Backtest::run(&series, 0).is_none());
+        assert!(Backtest::run(&series, 6).is_some());
+    }
+}
@@ -1,43 +1,90 @@
-use crate::errors::{DeliveryZonesError, ProvidersError};
+use crate::errors::DeliveryZonesError;
+use crate::models::products::DEFAULT_PRODUCT;
 
-/// Checks if a provider exists in the database.
+/// Checks if a delivery zone exists in the database.
 ///
 /// # Arguments
 ///
-/// * `id` - The ID of the provider to check.
+/// * `id` - The ID of the delivery zone to check.
 /// * `db` - The database connection pool.
 ///
 /// # Returns
 ///
-/// * `Result<bool, ProvidersError>` - `Ok(true)` if the provider exists, `Ok(false)` if not, or an error if the query fails.
-pub(crate) async fn provider_exists(id: i32, db: &sqlx::PgPool) -> Result<bool, ProvidersError> {
+/// * `Result<bool, DeliveryZonesError>` - `Ok(true)` if the delivery zone exists, `Ok(false)` if not, or an error if the query fails.
+pub(crate) async fn zone_exists(id: i32, db: &sqlx::PgPool) -> Result<bool, DeliveryZonesError> {
     let check_record: Option<(i32,)> =
-        sqlx::query_as::<_, (i32,)>("SELECT id FROM providers WHERE id = $1")
+        sqlx::query_as::<_, (i32,)>("SELECT id FROM delivery_zones WHERE id = $1")
             .bind(id)
             .fetch_optional(db)
             .await
-            .map_err(ProvidersError::fetch_error)?;
+            .map_err(DeliveryZonesError::fetch_error)?;
 
     Ok(check_record.is_some())
 }
 
-/// Checks if a delivery zone exists in the database.
+/// Escapes text for use in an XML document such as an SVG image or an Atom feed.
 ///
 /// # Arguments
 ///
-/// * `id` - The ID of the delivery zone to check.
-/// * `db` - The database connection pool.
+/// * `text` - The text to escape.
 ///
 /// # Returns
 ///
-/// * `Result<bool, DeliveryZonesError>` - `Ok(true)` if the delivery zone exists, `Ok(false)` if not, or an error if the query fails.
-pub(crate) async fn zone_exists(id: i32, db: &sqlx::PgPool) -> Result<bool, DeliveryZonesError> {
-    let check_record: Option<(i32,)> =
-        sqlx::query_as::<_, (i32,)>("SELECT id FROM delivery_zones WHERE id = $1")
-            .bind(id)
-            .fetch_optional(db)
-            .await
-            .map_err(DeliveryZonesError::fetch_error)?;
+/// * `String` - The text with its markup characters replaced by entities.
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
 
-    Ok(check_record.is_some())
+/// Checks if a database error was raised by a row referring to a row that does not exist, or by
+/// deleting a row that is still referred to.
+///
+/// # Arguments
+///
+/// * `error` - The database error.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the error is a foreign key violation.
+pub(crate) fn is_foreign_key_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|error| error.is_foreign_key_violation())
+}
+
+/// Checks if a database error was raised by a row duplicating a unique value.
+///
+/// # Arguments
+///
+/// * `error` - The database error.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the error is a unique violation.
+pub(crate) fn is_unique_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|error| error.is_unique_violation())
+}
+
+/// Looks up a product by its slug, falling back to the default product if none is given.
+///
+/// # Arguments
+///
+/// * `db` - The database connection pool.
+/// * `slug` - The slug of the product, or `None` for the default product.
+///
+/// # Returns
+///
+/// * `Result<Option<(i32, String)>, sqlx::Error>` - The ID and name of the product, `None` if no product has the slug, or an error if the query fails.
+pub(crate) async fn fetch_product_by_slug(
+    db: &sqlx::PgPool,
+    slug: Option<&str>,
+) -> Result<Option<(i32, String)>, sqlx::Error> {
+    sqlx::query_as::<_, (i32, String)>("SELECT id, name FROM products WHERE slug = $1")
+        .bind(slug.unwrap_or(DEFAULT_PRODUCT))
+        .fetch_optional(db)
+        .await
 }
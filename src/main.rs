@@ -1,15 +1,31 @@
 use crate::app_state::AppState;
+use crate::charts::cache::ChartCache;
+use crate::notifications::notifier::Notifier;
+use crate::notifications::smtp::{SmtpConfig, SmtpNotifier};
+use crate::streams::events::EventBus;
 use routes::router;
+use shuttle_runtime::{CustomError, SecretStore};
 use sqlx::Executor;
 use sqlx::PgPool;
+use std::sync::Arc;
 
 mod app_state;
 mod auth;
+mod charts;
 mod crud;
 mod errors;
+mod export;
+mod feeds;
+mod forecast;
 mod helpers;
+mod market_index;
 mod models;
+mod notifications;
+mod openapi;
+mod pagination;
 mod routes;
+mod streams;
+mod webhooks;
 
 ///
 /// Main function
@@ -19,16 +35,56 @@ mod routes;
 /// # Arguments
 ///
 /// * `db` - The database connection pool
+/// * `secrets` - The secrets used to configure optional services such as SMTP
 ///
 /// # Returns
 ///
 /// The application instance
 ///
 #[shuttle_runtime::main]
-async fn main(#[shuttle_shared_db::Postgres] db: PgPool) -> shuttle_axum::ShuttleAxum {
+async fn main(
+    #[shuttle_shared_db::Postgres] db: PgPool,
+    #[shuttle_runtime::Secrets] secrets: SecretStore,
+) -> shuttle_axum::ShuttleAxum {
     db.execute(include_str!("../migrations.sql")).await.unwrap();
 
-    let state = AppState { db };
+    let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+    if let Some(host) = secrets.get("SMTP_HOST") {
+        let config = SmtpConfig {
+            host,
+            port: secrets
+                .get("SMTP_PORT")
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(587),
+            tls: secrets.get("SMTP_TLS").is_none_or(|tls| tls != "false"),
+            username: secrets.get("SMTP_USERNAME"),
+            password: secrets.get("SMTP_PASSWORD"),
+            from: secrets
+                .get("SMTP_FROM")
+                .unwrap_or_else(|| "noreply@oliepriser.dk".to_string()),
+        };
+        let notifier = SmtpNotifier::new(config)
+            .map_err(|e| shuttle_runtime::Error::Custom(CustomError::msg(e.0)))?;
+        notifiers.push(Arc::new(notifier));
+    }
+
+    tokio::spawn(webhooks::worker::run(db.clone()));
+    tokio::spawn(market_index::job::run(db.clone()));
+
+    let events = Arc::new(EventBus::new());
+    tokio::spawn({
+        let events = events.clone();
+        let db = db.clone();
+        async move { events.listen(db).await }
+    });
+    tokio::spawn(streams::provider_health::run(db.clone(), events.clone()));
+
+    let state = AppState {
+        db,
+        notifiers: Arc::new(notifiers),
+        events,
+        charts: Arc::new(ChartCache::new()),
+    };
 
     Ok(router(state).into())
 }
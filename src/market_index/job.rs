@@ -0,0 +1,199 @@
+use crate::models::providers::STALE_AFTER_HOURS;
+use chrono::NaiveDate;
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration;
+
+/// How often the index is recomputed.
+const COMPUTE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The share of the cheapest and the most expensive prices left out of the trimmed mean.
+const TRIM_FRACTION: f64 = 0.1;
+
+/// The most days of history computed when the index is first built.
+const MAX_BACKFILL_DAYS: i32 = 3 * 365;
+
+/// Runs the market index job until the application shuts down.
+///
+/// The index of a day is computed from the price each active provider had in effect at the end
+/// of the day. A provider is active if its price was seen within `STALE_AFTER_HOURS` of the end
+/// of the day. Missing days are backfilled, and yesterday and today are recomputed on every run
+/// since prices may still arrive for them. Days changed in the past, as marked by `mark_dirty`,
+/// are recomputed as well.
+///
+/// # Arguments
+///
+/// * `db` - The database connection pool.
+pub(crate) async fn run(db: PgPool) {
+    let mut interval = tokio::time::interval(COMPUTE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = compute_pending(&db).await {
+            tracing::error!("Error while computing the market index: {}", e);
+        }
+    }
+}
+
+/// Marks the index as out of date from a day onwards, so the next run recomputes it from there.
+///
+/// Called within the transaction of every change to prices in effect before yesterday: prices
+/// stored back-dated, corrected, deleted or restored, and prices seen again in the past.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection, within the change's transaction.
+/// * `from` - The first day the change affects.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` if the index was marked, or a database error.
+pub(crate) async fn mark_dirty(
+    conn: &mut PgConnection,
+    from: NaiveDate,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO market_index_dirty (id, dirty_from)
+        VALUES (TRUE, $1)
+        ON CONFLICT (id) DO UPDATE SET
+            dirty_from = LEAST(market_index_dirty.dirty_from, EXCLUDED.dirty_from)
+        "#,
+    )
+    .bind(from)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Computes the index for every day not computed yet, for every day marked out of date, and for
+/// yesterday and today.
+///
+/// The mark is taken before computing, so changes marking the index while it is computed are
+/// picked up by the next run. It is put back if computing fails.
+///
+/// # Arguments
+///
+/// * `db` - The database connection pool.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` if the index was computed, or a database error.
+async fn compute_pending(db: &PgPool) -> Result<(), sqlx::Error> {
+    let dirty_from: Option<NaiveDate> =
+        sqlx::query_scalar("DELETE FROM market_index_dirty RETURNING dirty_from")
+            .fetch_optional(db)
+            .await?;
+
+    let range = sqlx::query_as::<_, (Option<NaiveDate>, NaiveDate)>(
+        r#"
+        SELECT
+            GREATEST(
+                LEAST(
+                    COALESCE(
+                        (SELECT MAX(day) - 1 FROM market_index),
+                        (SELECT MIN(valid_from)::DATE FROM oil_prices WHERE deleted_at IS NULL)
+                    ),
+                    $2
+                ),
+                CURRENT_DATE - $1
+            ),
+            CURRENT_DATE
+        "#,
+    )
+    .bind(MAX_BACKFILL_DAYS)
+    .bind(dirty_from)
+    .fetch_one(db)
+    .await;
+
+    let result = match range {
+        Ok((Some(start), end)) => compute_days(db, start, end).await,
+        Ok((None, _)) => Ok(()),
+        Err(e) => Err(e),
+    };
+
+    if let (Err(_), Some(dirty_from)) = (&result, dirty_from) {
+        mark_dirty(&mut *db.acquire().await?, dirty_from).await?;
+    }
+
+    result
+}
+
+/// Computes the index of every product for a range of days.
+///
+/// # Arguments
+///
+/// * `db` - The database connection pool.
+/// * `start` - The first day to compute.
+/// * `end` - The last day to compute.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` if the index was computed, or a database error.
+async fn compute_days(db: &PgPool, start: NaiveDate, end: NaiveDate) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO market_index
+            (day, product_id, providers, median, trimmed_mean, weighted_mean, computed_at)
+        SELECT
+            d.day::DATE, pr.id, s.providers, s.median, s.trimmed_mean, s.weighted_mean,
+            LOCALTIMESTAMP
+        FROM
+            generate_series($1::DATE, $2::DATE, INTERVAL '1 day') AS d(day)
+        CROSS JOIN
+            products pr
+        CROSS JOIN LATERAL (
+            SELECT
+                COUNT(*) AS providers,
+                CAST(PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY a.price) AS NUMERIC(12, 4))
+                    AS median,
+                ROUND(
+                    AVG(a.price) FILTER (
+                        WHERE a.rank > FLOOR(a.total * $4) AND a.rank <= a.total - FLOOR(a.total * $4)
+                    ),
+                    4
+                ) AS trimmed_mean,
+                ROUND(SUM(a.price * a.zones) / NULLIF(SUM(a.zones), 0), 4) AS weighted_mean
+            FROM (
+                SELECT
+                    l.price,
+                    (SELECT COUNT(*) FROM provider_delivery_zones pdz
+                     WHERE pdz.provider_id = l.provider_id) AS zones,
+                    ROW_NUMBER() OVER (ORDER BY l.price) AS rank,
+                    COUNT(*) OVER () AS total
+                FROM (
+                    SELECT DISTINCT ON (op.provider_id)
+                        op.provider_id, op.price, op.valid_to, op.last_seen_at
+                    FROM
+                        oil_prices op
+                    WHERE
+                        op.product_id = pr.id
+                        AND op.deleted_at IS NULL
+                        AND op.valid_from < d.day + INTERVAL '1 day'
+                    ORDER BY
+                        op.provider_id, op.valid_from DESC, op.id DESC
+                ) l
+                WHERE
+                    GREATEST(l.last_seen_at, COALESCE(l.valid_to, l.last_seen_at))
+                        >= d.day + INTERVAL '1 day' - MAKE_INTERVAL(hours => $3)
+            ) a
+        ) s
+        WHERE
+            s.providers > 0
+        ON CONFLICT (product_id, day) DO UPDATE SET
+            providers = EXCLUDED.providers,
+            median = EXCLUDED.median,
+            trimmed_mean = EXCLUDED.trimmed_mean,
+            weighted_mean = EXCLUDED.weighted_mean,
+            computed_at = EXCLUDED.computed_at
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .bind(STALE_AFTER_HOURS as i32)
+    .bind(TRIM_FRACTION)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::models::prices::VatBasis;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+/// The number of days a chart covers when no period is given.
+pub(crate) const DEFAULT_CHART_DAYS: i64 = 90;
+
+/// The most days a chart may cover.
+pub(crate) const MAX_CHART_DAYS: i64 = 3660;
+
+pub(crate) const DEFAULT_CHART_WIDTH: u32 = 800;
+pub(crate) const DEFAULT_CHART_HEIGHT: u32 = 400;
+
+/// The smallest and largest width of a chart, in pixels.
+pub(crate) const CHART_WIDTH_RANGE: std::ops::RangeInclusive<u32> = 200..=2000;
+
+/// The smallest and largest height of a chart, in pixels.
+pub(crate) const CHART_HEIGHT_RANGE: std::ops::RangeInclusive<u32> = 150..=1200;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ChartParams {
+    pub(crate) product: Option<String>,
+    pub(crate) start: Option<chrono::NaiveDate>,
+    pub(crate) end: Option<chrono::NaiveDate>,
+    pub(crate) width: Option<u32>,
+    pub(crate) height: Option<u32>,
+    #[serde(default)]
+    pub(crate) theme: ChartTheme,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
+
+/// The colour scheme of a chart.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ChartTheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+/// The colours of a chart theme.
+pub(crate) struct ChartColors {
+    pub(crate) background: &'static str,
+    pub(crate) text: &'static str,
+    pub(crate) grid: &'static str,
+    pub(crate) line: &'static str,
+}
+
+impl ChartTheme {
+    /// Returns the colours of the theme.
+    pub(crate) fn colors(self) -> ChartColors {
+        match self {
+            ChartTheme::Light => ChartColors {
+                background: "#ffffff",
+                text: "#1f2933",
+                grid: "#e4e7eb",
+                line: "#d64545",
+            },
+            ChartTheme::Dark => ChartColors {
+                background: "#1f2933",
+                text: "#e4e7eb",
+                grid: "#3e4c59",
+                line: "#ff9b9b",
+            },
+        }
+    }
+}
+
+/// The image format a chart is rendered in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ChartFormat {
+    Svg,
+    Png,
+}
+
+impl ChartFormat {
+    /// Returns the media type of the format.
+    pub(crate) fn content_type(self) -> &'static str {
+        match self {
+            ChartFormat::Svg => "image/svg+xml",
+            ChartFormat::Png => "image/png",
+        }
+    }
+}
@@ -1,19 +1,22 @@
+use crate::pagination::{Cursor, Keyset, SortKey};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(sqlx::FromRow, Serialize, Debug)]
+#[derive(sqlx::FromRow, Serialize, Debug, ToSchema)]
 pub(crate) struct DeliveryZones {
     pub(crate) id: i32,
     pub(crate) name: String,
-    pub(crate) description: String,
+    pub(crate) description: Option<String>,
+    pub(crate) created_at: chrono::NaiveDateTime,
 }
 
-#[derive(sqlx::FromRow, Deserialize)]
+#[derive(sqlx::FromRow, Deserialize, ToSchema)]
 pub(crate) struct DeliveryZonesAdd {
     pub(crate) name: String,
     pub(crate) description: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub(crate) struct DeliveryZoneProviderAdd {
     pub(crate) zone_ids: Vec<i32>,
 }
@@ -22,3 +25,61 @@ pub(crate) struct DeliveryZoneProviderAdd {
 pub(crate) struct DeliveryZonesInsertResponse {
     pub(crate) id: i32,
 }
+
+#[derive(Serialize, Debug, ToSchema)]
+pub(crate) struct DeliveryZoneProviderAddResponse {
+    pub(crate) provider_id: i32,
+    pub(crate) added: Vec<i32>,
+    pub(crate) already_present: Vec<i32>,
+    pub(crate) unknown: Vec<i32>,
+}
+
+#[derive(sqlx::FromRow, Deserialize, Serialize, ToSchema)]
+pub(crate) struct DeliveryZonePostcodes {
+    pub(crate) postcode_from: i32,
+    pub(crate) postcode_to: i32,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct DeliveryZoneListParams {
+    pub(crate) provider_id: Option<i32>,
+}
+
+/// The columns delivery zone lists can be sorted by.
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DeliveryZoneSort {
+    #[default]
+    CreatedAt,
+    Name,
+}
+
+impl SortKey for DeliveryZoneSort {
+    fn column(self) -> &'static str {
+        match self {
+            DeliveryZoneSort::CreatedAt => "created_at",
+            DeliveryZoneSort::Name => "name",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            DeliveryZoneSort::CreatedAt => "TIMESTAMP",
+            DeliveryZoneSort::Name => "TEXT",
+        }
+    }
+}
+
+impl Keyset<DeliveryZoneSort> for DeliveryZones {
+    fn cursor(&self, sort: DeliveryZoneSort) -> Cursor {
+        let value = match sort {
+            DeliveryZoneSort::CreatedAt => self.created_at.to_string(),
+            DeliveryZoneSort::Name => self.name.clone(),
+        };
+        Cursor {
+            value,
+            id: self.id.into(),
+        }
+    }
+}
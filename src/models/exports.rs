@@ -0,0 +1,61 @@
+use crate::export::{CsvField, CsvRecord, ExportFormat};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+#[derive(sqlx::FromRow, Serialize)]
+pub(crate) struct PriceExportRow {
+    pub(crate) id: i32,
+    pub(crate) provider_id: i32,
+    pub(crate) provider_name: String,
+    pub(crate) zones: Vec<String>,
+    pub(crate) product_id: i32,
+    pub(crate) product: String,
+    pub(crate) price: Decimal,
+    pub(crate) currency: String,
+    pub(crate) unit: String,
+    pub(crate) includes_vat: bool,
+    pub(crate) raw_price: Option<Decimal>,
+    pub(crate) created_at: chrono::NaiveDateTime,
+}
+
+impl CsvRecord for PriceExportRow {
+    const HEADER: &'static [&'static str] = &[
+        "id",
+        "provider_id",
+        "provider_name",
+        "zones",
+        "product_id",
+        "product",
+        "price",
+        "currency",
+        "unit",
+        "includes_vat",
+        "raw_price",
+        "created_at",
+    ];
+
+    fn csv_fields(&self) -> Vec<CsvField> {
+        vec![
+            self.id.to_string().into(),
+            self.provider_id.to_string().into(),
+            self.provider_name.clone().into(),
+            self.zones.join("|").into(),
+            self.product_id.to_string().into(),
+            self.product.clone().into(),
+            self.price.into(),
+            self.currency.clone().into(),
+            self.unit.clone().into(),
+            self.includes_vat.to_string().into(),
+            self.raw_price
+                .map_or_else(|| String::new().into(), CsvField::from),
+            self.created_at.to_string().into(),
+        ]
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ExportParams {
+    pub(crate) format: Option<ExportFormat>,
+}
@@ -0,0 +1,37 @@
+use crate::models::prices::VatBasis;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// The number of entries in a feed when no limit is given.
+pub(crate) const DEFAULT_FEED_ENTRIES: i64 = 50;
+
+/// The most entries a feed may hold.
+pub(crate) const MAX_FEED_ENTRIES: i64 = 200;
+
+/// The number of days back feeds list price changes for.
+pub(crate) const FEED_WINDOW_DAYS: i32 = 365;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct FeedParams {
+    pub(crate) product: Option<String>,
+    pub(crate) limit: Option<i64>,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
+
+/// A change of a provider's price, as listed in a feed.
+#[derive(sqlx::FromRow)]
+pub(crate) struct FeedPriceChange {
+    /// The ID of the price the change introduced.
+    pub(crate) id: i32,
+    pub(crate) provider_name: String,
+    pub(crate) provider_url: String,
+    pub(crate) product_name: String,
+    pub(crate) price: Decimal,
+    pub(crate) previous_price: Option<Decimal>,
+    pub(crate) valid_from: chrono::NaiveDateTime,
+    /// When the price was last corrected, if ever.
+    pub(crate) corrected_at: Option<chrono::NaiveDateTime>,
+}
@@ -0,0 +1,23 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The delivery fee rules of a provider. Fees are in DKK as charged to customers, so including
+/// VAT, and are added as they are to quote totals, which include VAT too.
+#[derive(sqlx::FromRow, Deserialize, Serialize, Default, ToSchema)]
+pub(crate) struct ProviderFees {
+    pub(crate) delivery_fee: Option<Decimal>,
+    pub(crate) free_above_liters: Option<i32>,
+    pub(crate) minimum_order_liters: Option<i32>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub(crate) zone_fees: Vec<ZoneFee>,
+}
+
+/// The delivery fee a provider charges in one of its zones, including VAT like the provider wide
+/// fee.
+#[derive(sqlx::FromRow, Deserialize, Serialize, ToSchema)]
+pub(crate) struct ZoneFee {
+    pub(crate) zone_id: i32,
+    pub(crate) delivery_fee: Decimal,
+}
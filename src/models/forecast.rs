@@ -0,0 +1,80 @@
+use crate::models::prices::VatBasis;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// The number of days forecast when none is given.
+pub(crate) const DEFAULT_FORECAST_DAYS: usize = 14;
+
+/// The most days that may be forecast.
+pub(crate) const MAX_FORECAST_DAYS: usize = 60;
+
+/// The number of days of history a model is fitted to when none is given.
+pub(crate) const DEFAULT_HISTORY_DAYS: i32 = 180;
+
+/// The most days of history a model may be fitted to.
+pub(crate) const MAX_HISTORY_DAYS: i32 = 1095;
+
+/// The probability the actual price falls within the bounds of a forecast point.
+pub(crate) const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// The number of standard errors either side of a forecast the confidence level corresponds to.
+pub(crate) const CONFIDENCE_Z: f64 = 1.96;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ForecastParams {
+    pub(crate) product: Option<String>,
+    /// The number of days to forecast.
+    pub(crate) days: Option<usize>,
+    /// The number of days of history to fit the model to.
+    pub(crate) history_days: Option<i32>,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
+
+/// A forecast of a price for the days after the last observed one.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct Forecast {
+    pub(crate) product_id: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) provider_id: Option<i32>,
+    pub(crate) includes_vat: bool,
+    pub(crate) last_date: chrono::NaiveDate,
+    pub(crate) last_price: Decimal,
+    pub(crate) confidence: f64,
+    pub(crate) model: ForecastModel,
+    /// How far the model was off when forecasting the last days of the history from the days
+    /// before them. Left out if the history is too short to hold days out.
+    pub(crate) backtest: Option<ForecastBacktest>,
+    pub(crate) points: Vec<ForecastPoint>,
+}
+
+/// The fitted parameters of the model a forecast was made with.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ForecastModel {
+    pub(crate) method: &'static str,
+    pub(crate) alpha: f64,
+    pub(crate) beta: f64,
+    pub(crate) phi: f64,
+    pub(crate) level: Decimal,
+    pub(crate) trend: Decimal,
+    pub(crate) residual_std_dev: Decimal,
+    pub(crate) observations: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ForecastBacktest {
+    pub(crate) days: usize,
+    pub(crate) mean_absolute_error: Decimal,
+    pub(crate) mean_absolute_percentage_error: Decimal,
+}
+
+/// The forecast price on a day, with the bounds the price falls within at the confidence level.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ForecastPoint {
+    pub(crate) date: chrono::NaiveDate,
+    pub(crate) price: Decimal,
+    pub(crate) lower: Decimal,
+    pub(crate) upper: Decimal,
+}
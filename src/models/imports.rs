@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// The largest number of row errors listed in an import report.
+pub(crate) const MAX_REPORTED_ERRORS: usize = 1000;
+
+/// The largest CSV file accepted in one import, in bytes.
+pub(crate) const MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+/// How a CSV file of historical prices is read.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PriceImportParams {
+    #[serde(default = "default_provider_column")]
+    pub(crate) provider_column: String,
+    #[serde(default = "default_price_column")]
+    pub(crate) price_column: String,
+    #[serde(default = "default_timestamp_column")]
+    pub(crate) timestamp_column: String,
+    pub(crate) product_column: Option<String>,
+    pub(crate) product: Option<String>,
+    pub(crate) timestamp_format: Option<String>,
+    pub(crate) delimiter: Option<char>,
+    #[serde(default)]
+    pub(crate) decimal_comma: bool,
+    pub(crate) includes_vat: Option<bool>,
+    pub(crate) per_liters: Option<i32>,
+    #[serde(default)]
+    pub(crate) dry_run: bool,
+}
+
+fn default_provider_column() -> String {
+    "provider".to_string()
+}
+
+fn default_price_column() -> String {
+    "price".to_string()
+}
+
+fn default_timestamp_column() -> String {
+    "timestamp".to_string()
+}
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct PriceImportRowError {
+    pub(crate) line: u64,
+    pub(crate) error: String,
+}
+
+#[derive(Serialize, Default, ToSchema)]
+pub(crate) struct PriceImportReport {
+    pub(crate) dry_run: bool,
+    pub(crate) rows: usize,
+    pub(crate) imported: usize,
+    pub(crate) duplicates: usize,
+    /// Rows failing the plausibility checks, held for review instead of being imported.
+    pub(crate) quarantined: usize,
+    pub(crate) rejected: usize,
+    pub(crate) errors: Vec<PriceImportRowError>,
+}
+
+impl PriceImportReport {
+    /// Records a rejected row, listing it unless the report already lists `MAX_REPORTED_ERRORS` rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line of the row in the file.
+    /// * `error` - The reason the row was rejected.
+    pub(crate) fn reject(&mut self, line: u64, error: String) {
+        self.rejected += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(PriceImportRowError { line, error });
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::models::prices::PriceEvent;
+use crate::models::providers::ProviderHealth;
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+/// An update pushed to live subscribers.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "topic", content = "data", rename_all = "snake_case")]
+pub(crate) enum LiveEvent {
+    Price(PriceEvent),
+    ScrapingRun(ScrapingRunEvent),
+    ProviderHealth(ProviderHealthEvent),
+}
+
+/// Progress of a scraping run.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum ScrapingRunEvent {
+    ProviderScraped {
+        provider_id: i32,
+        last_accessed: chrono::NaiveDateTime,
+    },
+    Finished {
+        id: i32,
+        start_time: chrono::NaiveDateTime,
+        end_time: chrono::NaiveDateTime,
+    },
+}
+
+/// A change in the health of a provider.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub(crate) struct ProviderHealthEvent {
+    pub(crate) provider_id: i32,
+    pub(crate) health: ProviderHealth,
+    pub(crate) previous: ProviderHealth,
+    pub(crate) last_price_at: Option<chrono::NaiveDateTime>,
+}
+
+/// A topic a WebSocket client can subscribe to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum Topic {
+    Prices,
+    ProviderPrices(i32),
+    ScrapingRuns,
+    ProviderHealth,
+}
+
+impl Topic {
+    /// Parses a topic name such as `prices`, `prices:3`, `scraping_runs` or `provider_health`.
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name.split_once(':') {
+            Some(("prices", provider_id)) => provider_id.parse().ok().map(Topic::ProviderPrices),
+            Some(_) => None,
+            None => match name {
+                "prices" => Some(Topic::Prices),
+                "scraping_runs" => Some(Topic::ScrapingRuns),
+                "provider_health" => Some(Topic::ProviderHealth),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns `true` if an event belongs to this topic.
+    pub(crate) fn matches(self, event: &LiveEvent) -> bool {
+        match (self, event) {
+            (Topic::Prices, LiveEvent::Price(_)) => true,
+            (Topic::ProviderPrices(id), LiveEvent::Price(price)) => price.provider_id == id,
+            (Topic::ScrapingRuns, LiveEvent::ScrapingRun(_)) => true,
+            (Topic::ProviderHealth, LiveEvent::ProviderHealth(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A message sent by a WebSocket client.
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub(crate) enum ClientMessage {
+    Subscribe { topic: String },
+    Unsubscribe { topic: String },
+}
+
+/// A message sent to a WebSocket client.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ServerMessage<'a> {
+    Subscribed { topic: &'a str },
+    Unsubscribed { topic: &'a str },
+    Event { event: &'a LiveEvent },
+    Error { message: String },
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct LiveUpdatesParams {
+    pub(crate) token: Option<String>,
+}
@@ -0,0 +1,34 @@
+use crate::models::prices::VatBasis;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// The number of days of history returned when no period is given.
+pub(crate) const DEFAULT_INDEX_DAYS: i32 = 30;
+
+/// The market index of a product on a day, with its change from the day and year before.
+///
+/// The changes are those of the median, which is the headline figure of the index.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct MarketIndexPoint {
+    pub(crate) day: chrono::NaiveDate,
+    pub(crate) providers: i32,
+    pub(crate) median: Decimal,
+    pub(crate) trimmed_mean: Decimal,
+    pub(crate) weighted_mean: Option<Decimal>,
+    pub(crate) includes_vat: bool,
+    pub(crate) day_change: Option<Decimal>,
+    pub(crate) day_change_percent: Option<Decimal>,
+    pub(crate) year_change: Option<Decimal>,
+    pub(crate) year_change_percent: Option<Decimal>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct MarketIndexParams {
+    pub(crate) product: Option<String>,
+    pub(crate) start: Option<chrono::NaiveDate>,
+    pub(crate) end: Option<chrono::NaiveDate>,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
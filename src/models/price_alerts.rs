@@ -0,0 +1,165 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The channel an alert notification is delivered through.
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, Debug, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AlertChannel {
+    #[default]
+    Email,
+}
+
+impl AlertChannel {
+    /// Returns the name the channel is stored as.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            AlertChannel::Email => "email",
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct PriceAlertAdd {
+    pub(crate) recipient: String,
+    #[serde(default)]
+    pub(crate) channel: AlertChannel,
+    pub(crate) product_id: Option<i32>,
+    pub(crate) zone_id: Option<i32>,
+    pub(crate) postcode: Option<i32>,
+    pub(crate) target_price: Option<Decimal>,
+    pub(crate) percent_drop: Option<Decimal>,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct PriceAlertCreated {
+    pub(crate) id: i32,
+    pub(crate) token: String,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct PriceAlerts {
+    pub(crate) id: i32,
+    pub(crate) recipient: String,
+    pub(crate) channel: String,
+    pub(crate) product_id: i32,
+    pub(crate) zone_id: Option<i32>,
+    pub(crate) postcode: Option<i32>,
+    pub(crate) target_price: Option<Decimal>,
+    pub(crate) percent_drop: Option<Decimal>,
+    pub(crate) reference_price: Option<Decimal>,
+    pub(crate) last_notified_price: Option<Decimal>,
+    pub(crate) created_at: chrono::NaiveDateTime,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct AlertPriceRow {
+    pub(crate) provider_id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) provider_name: String,
+    pub(crate) product_name: String,
+    pub(crate) price: Decimal,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct AlertCandidateRow {
+    pub(crate) id: i32,
+    pub(crate) token: String,
+    pub(crate) recipient: String,
+    pub(crate) channel: String,
+    pub(crate) target_price: Option<Decimal>,
+    pub(crate) percent_drop: Option<Decimal>,
+    pub(crate) reference_price: Option<Decimal>,
+    pub(crate) last_notified_price: Option<Decimal>,
+}
+
+impl AlertCandidateRow {
+    /// Decides whether a price should trigger this alert.
+    ///
+    /// A target price triggers once the price is at or below the target and lower than the price
+    /// last notified about. A percent drop triggers once the price has dropped at least that much
+    /// below the reference price.
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - The new price including VAT.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the alert should be notified.
+    pub(crate) fn is_triggered_by(&self, price: Decimal) -> bool {
+        let below_target = self.target_price.is_some_and(|target| {
+            price <= target && self.last_notified_price.is_none_or(|last| price < last)
+        });
+        let dropped = match (self.percent_drop, self.reference_price) {
+            (Some(percent), Some(reference)) => {
+                price <= reference * (Decimal::ONE_HUNDRED - percent) / Decimal::ONE_HUNDRED
+            }
+            _ => false,
+        };
+
+        below_target || dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AlertCandidateRow;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn alert(target_price: Option<&str>, percent_drop: Option<&str>) -> AlertCandidateRow {
+        AlertCandidateRow {
+            id: 1,
+            token: "token".to_string(),
+            recipient: "alert@example.com".to_string(),
+            channel: "email".to_string(),
+            target_price: target_price.map(decimal),
+            percent_drop: percent_drop.map(decimal),
+            reference_price: None,
+            last_notified_price: None,
+        }
+    }
+
+    #[test]
+    fn target_triggers_at_or_below_the_target() {
+        let alert = alert(Some("10"), None);
+
+        assert!(alert.is_triggered_by(decimal("9.99")));
+        assert!(alert.is_triggered_by(decimal("10")));
+        assert!(!alert.is_triggered_by(decimal("10.01")));
+    }
+
+    #[test]
+    fn target_only_triggers_again_below_the_last_notified_price() {
+        let mut alert = alert(Some("10"), None);
+        alert.last_notified_price = Some(decimal("9.50"));
+
+        assert!(!alert.is_triggered_by(decimal("9.50")));
+        assert!(!alert.is_triggered_by(decimal("9.75")));
+        assert!(alert.is_triggered_by(decimal("9.49")));
+    }
+
+    #[test]
+    fn percent_drop_triggers_below_the_reference() {
+        let mut alert = alert(None, Some("5"));
+        alert.reference_price = Some(decimal("10"));
+
+        assert!(alert.is_triggered_by(decimal("9.50")));
+        assert!(alert.is_triggered_by(decimal("9")));
+        assert!(!alert.is_triggered_by(decimal("9.51")));
+    }
+
+    #[test]
+    fn first_price_seen_only_triggers_targets() {
+        // Without a reference price, the first price is checked against the target alone
+        assert!(alert(Some("10"), None).is_triggered_by(decimal("9")));
+        assert!(alert(Some("10"), Some("5")).is_triggered_by(decimal("9")));
+        assert!(!alert(Some("8"), Some("5")).is_triggered_by(decimal("9")));
+        assert!(!alert(None, Some("5")).is_triggered_by(decimal("1")));
+    }
+}
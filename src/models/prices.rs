@@ -1,16 +1,68 @@
+use crate::export::{CsvField, CsvRecord};
+use crate::pagination::{Cursor, Keyset, SortKey};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(sqlx::FromRow, Deserialize, Serialize)]
+#[derive(sqlx::FromRow, Deserialize, Serialize, ToSchema)]
 pub(crate) struct Prices {
     pub(crate) id: i32,
     pub(crate) provider_id: i32,
-    pub(crate) price: f64,
+    pub(crate) product_id: i32,
+    pub(crate) price: Decimal,
+    pub(crate) currency: String,
+    pub(crate) unit: String,
+    pub(crate) includes_vat: bool,
+    pub(crate) raw_price: Option<Decimal>,
     pub(crate) created_at: chrono::NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) deleted_at: Option<chrono::NaiveDateTime>,
 }
 
-#[derive(sqlx::FromRow, Deserialize)]
+impl CsvRecord for Prices {
+    const HEADER: &'static [&'static str] = &[
+        "id",
+        "provider_id",
+        "product_id",
+        "price",
+        "currency",
+        "unit",
+        "includes_vat",
+        "raw_price",
+        "created_at",
+    ];
+
+    fn csv_fields(&self) -> Vec<CsvField> {
+        vec![
+            self.id.to_string().into(),
+            self.provider_id.to_string().into(),
+            self.product_id.to_string().into(),
+            self.price.into(),
+            self.currency.clone().into(),
+            self.unit.clone().into(),
+            self.includes_vat.to_string().into(),
+            self.raw_price
+                .map_or_else(|| String::new().into(), CsvField::from),
+            self.created_at.to_string().into(),
+        ]
+    }
+}
+
+#[derive(sqlx::FromRow, Deserialize, ToSchema)]
 pub(crate) struct ProviderPriceAdd {
-    pub(crate) price: f64,
+    pub(crate) price: Decimal,
+    pub(crate) product_id: Option<i32>,
+    pub(crate) includes_vat: Option<bool>,
+    pub(crate) per_liters: Option<i32>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub(crate) tiers: Vec<PriceTier>,
+}
+
+#[derive(sqlx::FromRow, Deserialize, Serialize, ToSchema)]
+pub(crate) struct PriceTier {
+    pub(crate) min_liters: i32,
+    pub(crate) price: Decimal,
 }
 
 #[derive(sqlx::FromRow)]
@@ -18,16 +70,488 @@ pub(crate) struct PriceInsertResponse {
     pub(crate) id: i32,
 }
 
-#[derive(sqlx::FromRow, Serialize)]
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
 pub(crate) struct PriceDetails {
-    pub(crate) price: f64,
+    pub(crate) id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) price: Decimal,
+    pub(crate) currency: String,
+    pub(crate) unit: String,
+    pub(crate) includes_vat: bool,
     pub(crate) created_at: chrono::NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) deleted_at: Option<chrono::NaiveDateTime>,
 }
 
-#[derive(Deserialize)]
-pub(crate) struct PriceQueryParams {
-    pub(crate) limit: Option<i64>,
+impl CsvRecord for PriceDetails {
+    const HEADER: &'static [&'static str] = &[
+        "id",
+        "product_id",
+        "price",
+        "currency",
+        "unit",
+        "includes_vat",
+        "created_at",
+    ];
+
+    fn csv_fields(&self) -> Vec<CsvField> {
+        vec![
+            self.id.to_string().into(),
+            self.product_id.to_string().into(),
+            self.price.into(),
+            self.currency.clone().into(),
+            self.unit.clone().into(),
+            self.includes_vat.to_string().into(),
+            self.created_at.to_string().into(),
+        ]
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PriceListParams {
+    pub(crate) provider_id: Option<i32>,
+    pub(crate) zone_id: Option<i32>,
+    pub(crate) product: Option<String>,
+    pub(crate) start: Option<chrono::NaiveDateTime>,
+    pub(crate) end: Option<chrono::NaiveDateTime>,
+    pub(crate) min_price: Option<Decimal>,
+    pub(crate) max_price: Option<Decimal>,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+    /// Whether deleted prices are listed too. Ignored by exports.
+    #[serde(default)]
+    pub(crate) include_deleted: bool,
+    /// The number of prices to skip on the first page. Deprecated in favour of `cursor`, and
+    /// ignored by exports.
     pub(crate) offset: Option<i64>,
+}
+
+/// The columns price lists can be sorted by.
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PriceSort {
+    #[default]
+    CreatedAt,
+    Price,
+}
+
+impl SortKey for PriceSort {
+    fn column(self) -> &'static str {
+        match self {
+            PriceSort::CreatedAt => "created_at",
+            PriceSort::Price => "price",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            PriceSort::CreatedAt => "TIMESTAMP",
+            PriceSort::Price => "NUMERIC",
+        }
+    }
+}
+
+impl Keyset<PriceSort> for Prices {
+    fn cursor(&self, sort: PriceSort) -> Cursor {
+        let value = match sort {
+            PriceSort::CreatedAt => self.created_at.to_string(),
+            PriceSort::Price => self.price.to_string(),
+        };
+        Cursor {
+            value,
+            id: self.id.into(),
+        }
+    }
+}
+
+impl Keyset<PriceSort> for PriceDetails {
+    fn cursor(&self, sort: PriceSort) -> Cursor {
+        let value = match sort {
+            PriceSort::CreatedAt => self.created_at.to_string(),
+            PriceSort::Price => self.price.to_string(),
+        };
+        Cursor {
+            value,
+            id: self.id.into(),
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct VatQueryParams {
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
+
+/// Whether prices are returned including or excluding VAT.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum VatBasis {
+    #[default]
+    Incl,
+    Excl,
+}
+
+impl VatBasis {
+    /// Returns `true` if prices should include VAT.
+    pub(crate) fn includes_vat(self) -> bool {
+        self == VatBasis::Incl
+    }
+}
+
+/// The basis a provider publishes a product's price on, together with the VAT rate in effect.
+#[derive(sqlx::FromRow)]
+pub(crate) struct PriceBasis {
+    pub(crate) product_id: i32,
+    pub(crate) includes_vat: bool,
+    pub(crate) per_liters: i32,
+    pub(crate) vat_rate: Decimal,
+}
+
+impl PriceBasis {
+    /// Normalises a published price to DKK per liter excluding VAT.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The price as published by the provider.
+    ///
+    /// # Returns
+    ///
+    /// * `Decimal` - The normalised price, rounded to four decimals.
+    pub(crate) fn normalise(&self, raw: Decimal) -> Decimal {
+        let mut price = raw / Decimal::from(self.per_liters);
+        if self.includes_vat {
+            price /= Decimal::ONE + self.vat_rate;
+        }
+        price.round_dp(4)
+    }
+}
+
+/// A newly inserted price as pushed to live subscribers.
+#[derive(sqlx::FromRow, Deserialize, Serialize, Clone, Debug, ToSchema)]
+pub(crate) struct PriceEvent {
+    pub(crate) id: i32,
+    pub(crate) provider_id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) product: String,
+    pub(crate) zone_ids: Vec<i32>,
+    pub(crate) price: Decimal,
+    pub(crate) price_excl_vat: Decimal,
+    pub(crate) currency: String,
+    pub(crate) unit: String,
+    pub(crate) created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PriceStreamParams {
+    pub(crate) provider_id: Option<i32>,
+    pub(crate) zone_id: Option<i32>,
+    pub(crate) product: Option<String>,
+}
+
+impl PriceStreamParams {
+    /// Returns `true` if a price event passes the stream's filters.
+    pub(crate) fn matches(&self, event: &PriceEvent) -> bool {
+        self.provider_id.is_none_or(|id| event.provider_id == id)
+            && self.zone_id.is_none_or(|id| event.zone_ids.contains(&id))
+            && self
+                .product
+                .as_ref()
+                .is_none_or(|product| &event.product == product)
+    }
+}
+
+/// The largest number of prices accepted in one bulk request.
+pub(crate) const MAX_BULK_PRICES: usize = 10_000;
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct BulkPriceItem {
+    pub(crate) provider_id: i32,
+    pub(crate) price: Decimal,
+    pub(crate) observed_at: Option<chrono::NaiveDateTime>,
+    pub(crate) product: Option<String>,
+}
+
+/// How a bulk request treats invalid items.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum BulkMode {
+    /// Store nothing unless every item is valid.
+    #[default]
+    Atomic,
+    /// Store the valid items and reject the rest.
+    Partial,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct BulkPriceParams {
+    pub(crate) scraping_run_id: Option<i32>,
+    #[serde(default)]
+    pub(crate) mode: BulkMode,
+    /// Whether unchanged prices only mark the price in effect as seen again.
+    #[serde(default)]
+    pub(crate) dedupe: bool,
+}
+
+/// What happened to an item of a bulk request.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum BulkItemStatus {
+    Created,
+    Rejected,
+    /// The price was unchanged, so the price in effect was marked as seen again.
+    Unchanged,
+    /// The item was valid, but not stored because another item of an atomic request was rejected.
+    Skipped,
+    /// The price failed the plausibility checks and was held for review.
+    Quarantined,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct BulkPriceResult {
+    pub(crate) index: usize,
+    pub(crate) status: BulkItemStatus,
+    /// The ID of the stored price, or of the quarantined price if it was held for review.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+    /// The reasons a quarantined price is suspicious.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) reasons: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct BulkPriceResponse {
+    pub(crate) scraping_run_id: Option<i32>,
+    pub(crate) created: usize,
+    pub(crate) unchanged: usize,
+    pub(crate) quarantined: usize,
+    pub(crate) rejected: usize,
+    pub(crate) results: Vec<BulkPriceResult>,
+}
+
+/// The largest number of points a reconstructed price series may hold.
+pub(crate) const MAX_SERIES_POINTS: i64 = 10_000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PriceIngestParams {
+    /// Whether an unchanged price only marks the price in effect as seen again.
+    #[serde(default)]
+    pub(crate) dedupe: bool,
+}
+
+/// A change of a provider's price for a product.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct PriceChange {
+    pub(crate) id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) product: String,
+    pub(crate) price: Decimal,
+    pub(crate) previous_price: Option<Decimal>,
+    pub(crate) change: Option<Decimal>,
+    pub(crate) currency: String,
+    pub(crate) unit: String,
+    pub(crate) includes_vat: bool,
+    pub(crate) valid_from: chrono::NaiveDateTime,
+    pub(crate) valid_to: Option<chrono::NaiveDateTime>,
+    pub(crate) last_seen_at: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PriceChangeParams {
+    pub(crate) product: Option<String>,
+    pub(crate) start: Option<chrono::NaiveDateTime>,
+    pub(crate) end: Option<chrono::NaiveDateTime>,
+    pub(crate) limit: Option<i64>,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
+
+/// The spacing of the points of a reconstructed price series.
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SeriesInterval {
+    Hour,
+    #[default]
+    Day,
+    Week,
+}
+
+impl SeriesInterval {
+    /// Returns the interval as a PostgreSQL interval literal.
+    pub(crate) fn as_sql(self) -> &'static str {
+        match self {
+            SeriesInterval::Hour => "1 hour",
+            SeriesInterval::Day => "1 day",
+            SeriesInterval::Week => "1 week",
+        }
+    }
+
+    /// Returns the field PostgreSQL's `date_trunc` truncates a timestamp to the start of the
+    /// interval holding it with.
+    pub(crate) fn as_sql_field(self) -> &'static str {
+        match self {
+            SeriesInterval::Hour => "hour",
+            SeriesInterval::Day => "day",
+            SeriesInterval::Week => "week",
+        }
+    }
+
+    /// Returns the length of the interval.
+    pub(crate) fn duration(self) -> chrono::Duration {
+        match self {
+            SeriesInterval::Hour => chrono::Duration::hours(1),
+            SeriesInterval::Day => chrono::Duration::days(1),
+            SeriesInterval::Week => chrono::Duration::weeks(1),
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PriceSeriesParams {
+    pub(crate) product: Option<String>,
+    pub(crate) start: Option<chrono::NaiveDateTime>,
+    pub(crate) end: Option<chrono::NaiveDateTime>,
+    #[serde(default)]
+    pub(crate) interval: SeriesInterval,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
+
+/// A point of a reconstructed price series: the price in effect at a point in time.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct PriceSeriesPoint {
+    pub(crate) at: chrono::NaiveDateTime,
+    pub(crate) price: Option<Decimal>,
+}
+
+/// The most providers that may be compared at once.
+pub(crate) const MAX_COMPARED_PROVIDERS: usize = 20;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PriceCompareParams {
+    /// A comma-separated list of provider IDs, such as `1,2,3`.
+    pub(crate) providers: String,
+    pub(crate) product: Option<String>,
     pub(crate) start: Option<chrono::NaiveDateTime>,
     pub(crate) end: Option<chrono::NaiveDateTime>,
+    #[serde(default)]
+    pub(crate) bucket: SeriesInterval,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
+
+impl PriceCompareParams {
+    /// Parses the list of provider IDs, dropping repeated IDs.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<i32>>` - The IDs in the order given, or `None` if the list holds something
+    ///   that is not an ID.
+    pub(crate) fn provider_ids(&self) -> Option<Vec<i32>> {
+        let mut ids: Vec<i32> = Vec::new();
+        for id in self
+            .providers
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+        {
+            let id = id.parse().ok()?;
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        Some(ids)
+    }
+}
+
+/// The prices of several providers aligned on the same points in time.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct PriceComparison {
+    pub(crate) product_id: i32,
+    pub(crate) includes_vat: bool,
+    pub(crate) providers: Vec<ComparedProvider>,
+    /// The average difference between the most expensive and the cheapest provider, over the
+    /// points at which at least two providers had a price.
+    pub(crate) average_spread: Option<Decimal>,
+    pub(crate) points: Vec<ComparisonPoint>,
+}
+
+/// The summary of a provider's prices over a comparison.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ComparedProvider {
+    pub(crate) provider_id: i32,
+    pub(crate) name: String,
+    /// The number of points at which the provider had a price.
+    pub(crate) priced: usize,
+    /// The number of points at which the provider was the cheapest, ties included.
+    pub(crate) cheapest: usize,
+    pub(crate) average_price: Option<Decimal>,
+    pub(crate) min_price: Option<Decimal>,
+    pub(crate) max_price: Option<Decimal>,
+    /// The average amount the provider was above the cheapest provider, over the points at which
+    /// it and at least one other provider had a price.
+    pub(crate) average_premium: Option<Decimal>,
+}
+
+/// The prices in effect at a point in time, in the order the providers are listed in. A provider
+/// without a price yet has `null`.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ComparisonPoint {
+    pub(crate) at: chrono::NaiveDateTime,
+    pub(crate) prices: Vec<Option<Decimal>>,
+    pub(crate) spread: Option<Decimal>,
+}
+
+/// A price as stored, locked for a change by an administrator.
+#[derive(sqlx::FromRow)]
+pub(crate) struct StoredPrice {
+    pub(crate) provider_id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) price: Decimal,
+    pub(crate) raw_price: Option<Decimal>,
+    pub(crate) raw_includes_vat: bool,
+    pub(crate) raw_per_liters: i32,
+    pub(crate) created_at: Option<chrono::NaiveDateTime>,
+    pub(crate) valid_from: chrono::NaiveDateTime,
+    pub(crate) deleted_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct PriceCorrection {
+    pub(crate) price: Decimal,
+    pub(crate) includes_vat: Option<bool>,
+    pub(crate) per_liters: Option<i32>,
+    /// The corrected volume tiers. The tiers are left as they are if not given.
+    pub(crate) tiers: Option<Vec<PriceTier>>,
+    pub(crate) reason: Option<String>,
+}
+
+/// The value a price had before a correction.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct PriceRevision {
+    pub(crate) id: i32,
+    pub(crate) price: Decimal,
+    pub(crate) raw_price: Option<Decimal>,
+    pub(crate) raw_includes_vat: bool,
+    pub(crate) raw_per_liters: i32,
+    pub(crate) tiers: serde_json::Value,
+    pub(crate) reason: Option<String>,
+    pub(crate) revised_by: String,
+    pub(crate) revised_at: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PriceDeleteParams {
+    pub(crate) reason: Option<String>,
 }
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(sqlx::FromRow, Serialize, Debug, ToSchema)]
+pub(crate) struct Products {
+    pub(crate) id: i32,
+    pub(crate) slug: String,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct ProductAdd {
+    pub(crate) slug: String,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct ProductsInsertResponse {
+    pub(crate) id: i32,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct ProviderProducts {
+    pub(crate) provider_id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) slug: String,
+    pub(crate) name: String,
+    pub(crate) html_element: String,
+    pub(crate) includes_vat: bool,
+    pub(crate) per_liters: i32,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct ProviderProductSummary {
+    #[serde(skip)]
+    pub(crate) provider_id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) slug: String,
+    pub(crate) name: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct ProviderProductAdd {
+    pub(crate) html_element: String,
+    pub(crate) includes_vat: Option<bool>,
+    pub(crate) per_liters: Option<i32>,
+}
+
+/// Slug of the product prices are recorded against when no product is given.
+pub(crate) const DEFAULT_PRODUCT: &str = "fyringsolie";
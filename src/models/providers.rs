@@ -1,7 +1,12 @@
 use crate::models::delivery_zones::DeliveryZones;
+use crate::models::prices::VatBasis;
+use crate::models::products::ProviderProductSummary;
+use crate::pagination::{Cursor, Keyset, SortKey};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(sqlx::FromRow, Deserialize, Serialize)]
+#[derive(sqlx::FromRow, Deserialize, Serialize, ToSchema)]
 pub(crate) struct Providers {
     pub(crate) id: i32,
     pub(crate) name: String,
@@ -15,37 +20,200 @@ pub(crate) struct ProvidersInsertResponse {
     pub(crate) id: i32,
 }
 
-#[derive(sqlx::FromRow, Deserialize, Serialize)]
+#[derive(sqlx::FromRow, Deserialize, Serialize, ToSchema)]
 pub(crate) struct ProviderIds {
     pub(crate) id: i32,
+    pub(crate) name: String,
+    pub(crate) created_at: chrono::NaiveDateTime,
     pub(crate) last_accessed: chrono::NaiveDateTime,
 }
 
-#[derive(sqlx::FromRow, Deserialize, Serialize)]
+#[derive(sqlx::FromRow, Deserialize, Serialize, ToSchema)]
 pub(crate) struct ProviderAdd {
     pub(crate) name: String,
     pub(crate) url: String,
     pub(crate) html_element: String,
 }
 
-#[derive(Serialize)]
-pub(crate) struct ProviderWithZones {
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct ProviderListing {
     pub(crate) id: i32,
     pub(crate) name: String,
     pub(crate) url: String,
     pub(crate) created_at: chrono::NaiveDateTime,
     pub(crate) last_updated: chrono::NaiveDateTime,
-    pub(crate) zones: Vec<DeliveryZones>,
+    #[sqlx(skip)]
+    pub(crate) health: ProviderHealth,
+    #[sqlx(skip)]
+    pub(crate) latest_price: Option<ProviderLatestPrice>,
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) zones: Option<Vec<DeliveryZones>>,
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) products: Option<Vec<ProviderProductSummary>>,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct ProviderLatestPrice {
+    #[serde(skip)]
+    pub(crate) provider_id: i32,
+    pub(crate) id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) product: String,
+    pub(crate) price: Decimal,
+    pub(crate) currency: String,
+    pub(crate) unit: String,
+    pub(crate) includes_vat: bool,
+    pub(crate) created_at: chrono::NaiveDateTime,
 }
 
 #[derive(sqlx::FromRow)]
 pub(crate) struct ProviderZoneRow {
     pub(crate) provider_id: i32,
-    pub(crate) provider_name: String,
-    pub(crate) url: String,
-    pub(crate) created_at: chrono::NaiveDateTime,
-    pub(crate) last_updated: chrono::NaiveDateTime,
+    #[sqlx(flatten)]
+    pub(crate) zone: DeliveryZones,
+}
+
+/// Related data that can be included when listing providers.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProviderInclude {
+    Zones,
+    Products,
+}
+
+impl ProviderInclude {
+    /// Parses a comma-separated list such as `zones,products`.
+    ///
+    /// # Arguments
+    ///
+    /// * `include` - The list to parse.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<ProviderInclude>>` - The parsed list, or `None` if it names unknown data.
+    pub(crate) fn parse_list(include: &str) -> Option<Vec<Self>> {
+        include
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match name {
+                "zones" => Some(ProviderInclude::Zones),
+                "products" => Some(ProviderInclude::Products),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ProviderIncludeParams {
+    pub(crate) include: Option<String>,
+    /// The slug of the product the latest prices are of. Defaults to heating oil.
+    pub(crate) product: Option<String>,
+    #[serde(default)]
+    pub(crate) vat: VatBasis,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ProviderListParams {
     pub(crate) zone_id: Option<i32>,
-    pub(crate) zone_name: Option<String>,
-    pub(crate) description: Option<String>,
+}
+
+/// The columns provider lists can be sorted by.
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ProviderSort {
+    #[default]
+    CreatedAt,
+    Name,
+}
+
+impl SortKey for ProviderSort {
+    fn column(self) -> &'static str {
+        match self {
+            ProviderSort::CreatedAt => "created_at",
+            ProviderSort::Name => "name",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            ProviderSort::CreatedAt => "TIMESTAMP",
+            ProviderSort::Name => "TEXT",
+        }
+    }
+}
+
+impl Keyset<ProviderSort> for ProviderListing {
+    fn cursor(&self, sort: ProviderSort) -> Cursor {
+        let value = match sort {
+            ProviderSort::CreatedAt => self.created_at.to_string(),
+            ProviderSort::Name => self.name.clone(),
+        };
+        Cursor {
+            value,
+            id: self.id.into(),
+        }
+    }
+}
+
+impl Keyset<ProviderSort> for ProviderIds {
+    fn cursor(&self, sort: ProviderSort) -> Cursor {
+        let value = match sort {
+            ProviderSort::CreatedAt => self.created_at.to_string(),
+            ProviderSort::Name => self.name.clone(),
+        };
+        Cursor {
+            value,
+            id: self.id.into(),
+        }
+    }
+}
+
+/// How many hours may pass without a new price before a provider is considered stale.
+pub(crate) const STALE_AFTER_HOURS: i64 = 48;
+
+/// The health of a provider, derived from how recently the scraper saw a price of it. Unchanged
+/// prices only mark the price in effect as seen, so this is not when the price last changed.
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, Debug, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ProviderHealth {
+    Healthy,
+    Stale,
+    #[default]
+    NoPrices,
+}
+
+impl ProviderHealth {
+    /// Derives the health of a provider from the last time a price of it was seen.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_price_at` - The last time a price of the provider was seen, if ever.
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    ///
+    /// * `ProviderHealth` - The health of the provider.
+    pub(crate) fn from_last_price(
+        last_price_at: Option<chrono::NaiveDateTime>,
+        now: chrono::NaiveDateTime,
+    ) -> Self {
+        match last_price_at {
+            None => ProviderHealth::NoPrices,
+            Some(at) if now - at > chrono::Duration::hours(STALE_AFTER_HOURS) => {
+                ProviderHealth::Stale
+            }
+            Some(_) => ProviderHealth::Healthy,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct ProviderLastPriceRow {
+    pub(crate) provider_id: i32,
+    pub(crate) last_price_at: Option<chrono::NaiveDateTime>,
 }
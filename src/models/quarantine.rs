@@ -0,0 +1,117 @@
+use crate::pagination::{Cursor, Keyset, SortKey};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// The fewest other providers a market median is taken over before prices are checked against it.
+pub(crate) const MIN_MEDIAN_PROVIDERS: i64 = 3;
+
+/// The number of days a provider's price counts towards the market median after it was last seen.
+pub(crate) const MEDIAN_WINDOW_DAYS: i32 = 7;
+
+/// The plausibility checks incoming prices of a product must pass. Checks left out are skipped.
+///
+/// Prices are compared per liter excluding VAT.
+#[derive(sqlx::FromRow, Deserialize, Serialize, Default, ToSchema)]
+pub(crate) struct PlausibilityRules {
+    pub(crate) min_price: Option<Decimal>,
+    pub(crate) max_price: Option<Decimal>,
+    /// The largest change, in percent, from the provider's price in effect.
+    pub(crate) max_change_percent: Option<Decimal>,
+    /// The largest deviation, in percent, from the median of the other providers' prices.
+    pub(crate) max_median_deviation_percent: Option<Decimal>,
+}
+
+/// Where a quarantined price is in the review workflow.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum QuarantineStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl QuarantineStatus {
+    /// Returns the name the status is stored as.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            QuarantineStatus::Pending => "pending",
+            QuarantineStatus::Approved => "approved",
+            QuarantineStatus::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct QuarantinedPrice {
+    pub(crate) id: i32,
+    pub(crate) provider_id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) price: Decimal,
+    pub(crate) raw_price: Decimal,
+    pub(crate) raw_includes_vat: bool,
+    pub(crate) raw_per_liters: i32,
+    pub(crate) tiers: serde_json::Value,
+    pub(crate) reasons: Vec<String>,
+    pub(crate) status: String,
+    pub(crate) received_at: chrono::NaiveDateTime,
+    /// The time the price was observed at, if it was sent with one.
+    pub(crate) observed_at: Option<chrono::NaiveDateTime>,
+    /// The price a held correction would correct.
+    pub(crate) corrects_price_id: Option<i32>,
+    pub(crate) correction_reason: Option<String>,
+    pub(crate) requested_by: Option<String>,
+    pub(crate) reviewed_by: Option<String>,
+    pub(crate) reviewed_at: Option<chrono::NaiveDateTime>,
+    pub(crate) price_id: Option<i32>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct QuarantineQueryParams {
+    #[serde(default)]
+    pub(crate) status: QuarantineStatus,
+    pub(crate) provider_id: Option<i32>,
+}
+
+/// The columns quarantined price lists can be sorted by.
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum QuarantineSort {
+    #[default]
+    ReceivedAt,
+}
+
+impl SortKey for QuarantineSort {
+    fn column(self) -> &'static str {
+        match self {
+            QuarantineSort::ReceivedAt => "received_at",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            QuarantineSort::ReceivedAt => "TIMESTAMP",
+        }
+    }
+}
+
+impl Keyset<QuarantineSort> for QuarantinedPrice {
+    fn cursor(&self, sort: QuarantineSort) -> Cursor {
+        let value = match sort {
+            QuarantineSort::ReceivedAt => self.received_at.to_string(),
+        };
+        Cursor {
+            value,
+            id: self.id.into(),
+        }
+    }
+}
+
+/// The response to a price that was held for review instead of being stored.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct QuarantinedPriceResponse {
+    pub(crate) quarantine_id: i32,
+    pub(crate) reasons: Vec<String>,
+}
@@ -0,0 +1,49 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct QuoteRequest {
+    pub(crate) liters: i32,
+    pub(crate) postcode: i32,
+    pub(crate) product: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct QuoteCandidateRow {
+    pub(crate) provider_id: i32,
+    pub(crate) provider_name: String,
+    pub(crate) url: String,
+    pub(crate) price_id: i32,
+    pub(crate) price_created_at: chrono::NaiveDateTime,
+    pub(crate) unit_price: Decimal,
+    /// The excise duties per liter included in the unit price.
+    pub(crate) unit_duties: Decimal,
+    pub(crate) delivery_fee: Option<Decimal>,
+    pub(crate) zone_fee: Option<Decimal>,
+    pub(crate) free_above_liters: Option<i32>,
+    pub(crate) minimum_order_liters: Option<i32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct Quote {
+    pub(crate) rank: usize,
+    pub(crate) provider_id: i32,
+    pub(crate) provider_name: String,
+    pub(crate) url: String,
+    pub(crate) price_id: i32,
+    pub(crate) price_created_at: chrono::NaiveDateTime,
+    pub(crate) unit_price: Decimal,
+    /// The excise duties included in the total, with the VAT charged on them.
+    pub(crate) duties: Decimal,
+    pub(crate) delivery_fee: Decimal,
+    pub(crate) total: Decimal,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct QuoteResponse {
+    pub(crate) liters: i32,
+    pub(crate) postcode: i32,
+    pub(crate) product: String,
+    pub(crate) quotes: Vec<Quote>,
+}
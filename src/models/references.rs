@@ -0,0 +1,117 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// The currency prices are stored in, which needs no FX rate.
+pub(crate) const BASE_CURRENCY: &str = "DKK";
+
+/// An upstream price series providers' prices can be compared with, such as gasoil futures or
+/// Brent crude.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct ReferenceSeries {
+    pub(crate) id: i32,
+    pub(crate) slug: String,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    pub(crate) currency: String,
+    pub(crate) unit: String,
+    pub(crate) liters_per_unit: Decimal,
+    pub(crate) created_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct ReferenceSeriesAdd {
+    pub(crate) slug: String,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    /// The ISO 4217 code of the currency the series is quoted in.
+    pub(crate) currency: String,
+    /// The unit the series is quoted per, such as `tonne` or `barrel`.
+    pub(crate) unit: String,
+    /// The number of liters in one unit.
+    pub(crate) liters_per_unit: Decimal,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct ReferenceObservationAdd {
+    pub(crate) date: chrono::NaiveDate,
+    pub(crate) value: Decimal,
+}
+
+/// An observation of a reference series, with its value converted to DKK per liter if an FX rate
+/// is known.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct ReferenceObservation {
+    pub(crate) date: chrono::NaiveDate,
+    pub(crate) value: Decimal,
+    pub(crate) fx_rate: Option<Decimal>,
+    pub(crate) price_per_liter: Option<Decimal>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct ReferenceQueryParams {
+    pub(crate) start: Option<chrono::NaiveDate>,
+    pub(crate) end: Option<chrono::NaiveDate>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct FxRateAdd {
+    pub(crate) currency: String,
+    pub(crate) date: chrono::NaiveDate,
+    /// The price of one unit of the currency in DKK.
+    pub(crate) rate: Decimal,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct FxRates {
+    pub(crate) currency: String,
+    pub(crate) date: chrono::NaiveDate,
+    pub(crate) rate: Decimal,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct FxRateQueryParams {
+    pub(crate) currency: Option<String>,
+    pub(crate) start: Option<chrono::NaiveDate>,
+    pub(crate) end: Option<chrono::NaiveDate>,
+}
+
+/// The most rows accepted in one upload.
+pub(crate) const MAX_UPLOAD_ROWS: usize = 100_000;
+
+/// The largest body accepted in one upload, in bytes.
+pub(crate) const MAX_UPLOAD_BYTES: usize = 32 * 1024 * 1024;
+
+/// The longest period, in days, margins are computed for at once.
+pub(crate) const MAX_MARGIN_DAYS: i64 = 3660;
+
+/// The number of days of margins returned when no period is given.
+pub(crate) const DEFAULT_MARGIN_DAYS: i64 = 30;
+
+/// The outcome of an upload of observations or FX rates.
+#[derive(Serialize, Default, ToSchema)]
+pub(crate) struct UploadReport {
+    pub(crate) stored: usize,
+    pub(crate) rejected: Vec<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct MarginParams {
+    /// The slug of the reference series.
+    pub(crate) reference: String,
+    pub(crate) product: Option<String>,
+    pub(crate) start: Option<chrono::NaiveDate>,
+    pub(crate) end: Option<chrono::NaiveDate>,
+}
+
+/// A provider's margin over a reference series on a day, per liter excluding VAT.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct MarginPoint {
+    pub(crate) date: chrono::NaiveDate,
+    pub(crate) price: Option<Decimal>,
+    pub(crate) reference_price: Option<Decimal>,
+    pub(crate) margin: Option<Decimal>,
+}
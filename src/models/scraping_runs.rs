@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(sqlx::FromRow, Deserialize, Serialize)]
+#[derive(sqlx::FromRow, Deserialize, Serialize, ToSchema)]
 pub(crate) struct ScrapingRuns {
     pub(crate) start_time: chrono::NaiveDateTime,
     pub(crate) end_time: chrono::NaiveDateTime,
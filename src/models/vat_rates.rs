@@ -0,0 +1,51 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct VatRates {
+    pub(crate) id: i32,
+    pub(crate) rate: Decimal,
+    pub(crate) valid_from: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct VatRateAdd {
+    pub(crate) rate: Decimal,
+    pub(crate) valid_from: chrono::NaiveDateTime,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct VatRatesInsertResponse {
+    pub(crate) id: i32,
+}
+
+/// An excise duty levied on a product, in DKK per liter excluding VAT.
+///
+/// A duty applies from its `valid_from` until the next amount of the same duty takes effect. A
+/// duty that is abolished is given an amount of zero.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct DutyRates {
+    pub(crate) id: i32,
+    pub(crate) product_id: i32,
+    pub(crate) product: String,
+    /// The name of the duty, such as `energiafgift` or `co2-afgift`.
+    pub(crate) name: String,
+    pub(crate) amount: Decimal,
+    pub(crate) valid_from: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct DutyRateAdd {
+    pub(crate) product_id: i32,
+    pub(crate) name: String,
+    pub(crate) amount: Decimal,
+    pub(crate) valid_from: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct DutyRateListParams {
+    /// The slug of the product to list the duties of.
+    pub(crate) product: Option<String>,
+}
@@ -0,0 +1,117 @@
+use crate::pagination::{Cursor, Keyset, SortKey};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// The events webhooks can subscribe to.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug, ToSchema)]
+pub(crate) enum WebhookEvent {
+    #[serde(rename = "price.created")]
+    PriceCreated,
+    #[serde(rename = "price.deleted")]
+    PriceDeleted,
+    #[serde(rename = "price.corrected")]
+    PriceCorrected,
+    #[serde(rename = "price.restored")]
+    PriceRestored,
+    #[serde(rename = "provider.updated")]
+    ProviderUpdated,
+    #[serde(rename = "scraping_run.finished")]
+    ScrapingRunFinished,
+}
+
+impl WebhookEvent {
+    /// Returns the name the event is stored and sent as.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::PriceCreated => "price.created",
+            WebhookEvent::PriceDeleted => "price.deleted",
+            WebhookEvent::PriceCorrected => "price.corrected",
+            WebhookEvent::PriceRestored => "price.restored",
+            WebhookEvent::ProviderUpdated => "provider.updated",
+            WebhookEvent::ScrapingRunFinished => "scraping_run.finished",
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct Webhooks {
+    pub(crate) id: i32,
+    pub(crate) url: String,
+    pub(crate) event_types: Vec<String>,
+    pub(crate) active: bool,
+    pub(crate) created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct WebhookAdd {
+    pub(crate) url: String,
+    pub(crate) event_types: Vec<WebhookEvent>,
+    pub(crate) secret: Option<String>,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct WebhookCreated {
+    pub(crate) id: i32,
+    pub(crate) secret: String,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct WebhookDeliveries {
+    pub(crate) id: i64,
+    pub(crate) event_type: String,
+    pub(crate) payload: serde_json::Value,
+    pub(crate) status: String,
+    pub(crate) attempts: i32,
+    pub(crate) next_attempt_at: chrono::NaiveDateTime,
+    pub(crate) last_status_code: Option<i32>,
+    pub(crate) last_error: Option<String>,
+    pub(crate) created_at: chrono::NaiveDateTime,
+    pub(crate) delivered_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct WebhookDeliveryQueryParams {
+    pub(crate) status: Option<String>,
+}
+
+/// The columns delivery logs can be sorted by.
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum WebhookDeliverySort {
+    #[default]
+    CreatedAt,
+}
+
+impl SortKey for WebhookDeliverySort {
+    fn column(self) -> &'static str {
+        match self {
+            WebhookDeliverySort::CreatedAt => "created_at",
+        }
+    }
+
+    fn sql_type(self) -> &'static str {
+        match self {
+            WebhookDeliverySort::CreatedAt => "TIMESTAMP",
+        }
+    }
+}
+
+impl Keyset<WebhookDeliverySort> for WebhookDeliveries {
+    fn cursor(&self, sort: WebhookDeliverySort) -> Cursor {
+        let value = match sort {
+            WebhookDeliverySort::CreatedAt => self.created_at.to_string(),
+        };
+        Cursor { value, id: self.id }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct PendingDeliveryRow {
+    pub(crate) id: i64,
+    pub(crate) url: String,
+    pub(crate) secret: String,
+    pub(crate) event_type: String,
+    pub(crate) payload: serde_json::Value,
+    pub(crate) attempts: i32,
+}
@@ -0,0 +1,84 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// The number of providers listed when no limit is given.
+pub(crate) const DEFAULT_WIDGET_PROVIDERS: i64 = 3;
+
+/// The most providers a widget may list.
+pub(crate) const MAX_WIDGET_PROVIDERS: i64 = 10;
+
+/// The number of requests per minute an API key may make when none is given.
+pub(crate) const DEFAULT_REQUESTS_PER_MINUTE: i32 = 60;
+
+/// How long, in seconds, browsers and shared caches may keep widget data.
+pub(crate) const WIDGET_MAX_AGE: u32 = 300;
+
+/// The header an API key may be sent in instead of the `key` query parameter.
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct WidgetParams {
+    pub(crate) postcode: i32,
+    pub(crate) product: Option<String>,
+    pub(crate) limit: Option<i64>,
+    /// The API key of the embedder.
+    pub(crate) key: Option<String>,
+    /// The name of the function to wrap the data in for JSONP.
+    pub(crate) callback: Option<String>,
+}
+
+/// The cheapest providers delivering to a postcode.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct WidgetResponse {
+    pub(crate) postcode: i32,
+    pub(crate) product: String,
+    pub(crate) providers: Vec<WidgetProvider>,
+}
+
+/// A provider's current price as shown in a widget. Prices are per liter including VAT.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct WidgetProvider {
+    pub(crate) name: String,
+    pub(crate) url: String,
+    pub(crate) price: Decimal,
+    /// When the price was last seen on the provider's site.
+    pub(crate) updated_at: chrono::NaiveDateTime,
+    /// The number of minutes since the price was last seen.
+    pub(crate) age_minutes: i64,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct WidgetApiKeys {
+    pub(crate) id: i32,
+    pub(crate) name: String,
+    /// The first characters of the key, enough to tell keys apart.
+    pub(crate) key_prefix: String,
+    pub(crate) requests_per_minute: i32,
+    pub(crate) active: bool,
+    pub(crate) created_at: chrono::NaiveDateTime,
+    pub(crate) requests_today: i64,
+    pub(crate) requests_total: i64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct WidgetApiKeyAdd {
+    pub(crate) name: String,
+    pub(crate) requests_per_minute: Option<i32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct WidgetApiKeyCreated {
+    pub(crate) id: i32,
+    pub(crate) key: String,
+}
+
+/// The requests made with an API key on a day.
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub(crate) struct WidgetUsage {
+    pub(crate) day: chrono::NaiveDate,
+    pub(crate) requests: i64,
+    /// The requests turned away for going over the rate limit.
+    pub(crate) rejected: i64,
+}
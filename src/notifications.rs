@@ -0,0 +1,3 @@
+pub(crate) mod evaluator;
+pub(crate) mod notifier;
+pub(crate) mod smtp;
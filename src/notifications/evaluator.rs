@@ -0,0 +1,153 @@
+use crate::app_state::AppState;
+use crate::models::price_alerts::{AlertCandidateRow, AlertPriceRow};
+use crate::notifications::notifier::AlertNotification;
+
+/// Evaluates the price alerts affected by a newly inserted price and notifies their subscribers.
+///
+/// Alerts match when they are for the price's product and their zone or postcode is served by
+/// the provider. Each alert is notified at most once per price, recorded in
+/// `price_alert_notifications`, and failed deliveries are rolled back so a later price can retry.
+/// The first price an alert sees becomes its reference for percent drops, and is checked against
+/// its target price right away.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the database connection pool and notifiers.
+/// * `price_id` - The ID of the inserted price.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` once all alerts have been evaluated, or a database error.
+pub(crate) async fn evaluate_price_alerts(
+    state: AppState,
+    price_id: i32,
+) -> Result<(), sqlx::Error> {
+    let Some(price) = sqlx::query_as::<_, AlertPriceRow>(
+        r#"
+        SELECT
+            op.provider_id,
+            op.product_id,
+            p.name AS provider_name,
+            pr.name AS product_name,
+            ROUND(op.price * (1 + vat_rate_at(op.created_at)), 4) AS price
+        FROM
+            oil_prices op
+        JOIN
+            providers p ON op.provider_id = p.id
+        JOIN
+            products pr ON op.product_id = pr.id
+        WHERE
+            op.id = $1
+        "#,
+    )
+    .bind(price_id)
+    .fetch_optional(&state.db)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let alerts = sqlx::query_as::<_, AlertCandidateRow>(
+        r#"
+        SELECT
+            a.id, a.token, a.recipient, a.channel, a.target_price, a.percent_drop,
+            a.reference_price, a.last_notified_price
+        FROM
+            price_alerts a
+        WHERE
+            a.product_id = $2
+            AND EXISTS (
+                SELECT 1
+                FROM provider_delivery_zones pz
+                LEFT JOIN delivery_zone_postcodes zp ON pz.zone_id = zp.zone_id
+                WHERE pz.provider_id = $1
+                  AND (pz.zone_id = a.zone_id
+                       OR a.postcode BETWEEN zp.postcode_from AND zp.postcode_to)
+            )
+        "#,
+    )
+    .bind(price.provider_id)
+    .bind(price.product_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    for alert in alerts {
+        if alert.reference_price.is_none() {
+            sqlx::query("UPDATE price_alerts SET reference_price = $2 WHERE id = $1")
+                .bind(alert.id)
+                .bind(price.price)
+                .execute(&state.db)
+                .await?;
+
+            // A percent drop needs a baseline, but a target price can be met by the first price
+            if alert.target_price.is_none() {
+                continue;
+            }
+        }
+
+        if !alert.is_triggered_by(price.price) {
+            continue;
+        }
+
+        let Some(notifier) = state
+            .notifiers
+            .iter()
+            .find(|notifier| notifier.channel() == alert.channel)
+        else {
+            tracing::warn!("No notifier configured for channel {}", alert.channel);
+            continue;
+        };
+
+        // Claim the notification first so concurrent evaluations never notify twice
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO price_alert_notifications (alert_id, price_id, price)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (alert_id, price_id) DO NOTHING
+            "#,
+        )
+        .bind(alert.id)
+        .bind(price_id)
+        .bind(price.price)
+        .execute(&state.db)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if !claimed {
+            continue;
+        }
+
+        let notification = AlertNotification {
+            recipient: alert.recipient,
+            provider_name: price.provider_name.clone(),
+            product_name: price.product_name.clone(),
+            price: price.price,
+            unsubscribe_token: alert.token,
+        };
+
+        match notifier.notify(&notification).await {
+            Ok(()) => {
+                sqlx::query(
+                    "UPDATE price_alerts SET last_notified_price = $2, reference_price = $2 WHERE id = $1",
+                )
+                .bind(alert.id)
+                .bind(price.price)
+                .execute(&state.db)
+                .await?;
+            }
+            Err(e) => {
+                tracing::error!("Error while notifying price alert {}: {}", alert.id, e);
+                sqlx::query(
+                    "DELETE FROM price_alert_notifications WHERE alert_id = $1 AND price_id = $2",
+                )
+                .bind(alert.id)
+                .bind(price_id)
+                .execute(&state.db)
+                .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
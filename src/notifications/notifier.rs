@@ -0,0 +1,43 @@
+use axum::async_trait;
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// A price alert that has been triggered and should be delivered to its subscriber.
+pub(crate) struct AlertNotification {
+    pub(crate) recipient: String,
+    pub(crate) provider_name: String,
+    pub(crate) product_name: String,
+    pub(crate) price: Decimal,
+    pub(crate) unsubscribe_token: String,
+}
+
+/// Error returned when a notification could not be delivered.
+#[derive(Debug)]
+pub(crate) struct NotifierError(pub(crate) String);
+
+impl fmt::Display for NotifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A delivery channel for price alert notifications.
+///
+/// Implementations are registered in the application state and picked by the channel stored on
+/// each subscription.
+#[async_trait]
+pub(crate) trait Notifier: Send + Sync {
+    /// Returns the name of the channel this notifier delivers through.
+    fn channel(&self) -> &'static str;
+
+    /// Delivers a notification.
+    ///
+    /// # Arguments
+    ///
+    /// * `notification` - The notification to deliver.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), NotifierError>` - `Ok(())` if the notification was delivered, or an error.
+    async fn notify(&self, notification: &AlertNotification) -> Result<(), NotifierError>;
+}
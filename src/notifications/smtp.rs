@@ -0,0 +1,185 @@
+use crate::notifications::notifier::{AlertNotification, Notifier, NotifierError};
+use axum::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+/// Settings for connecting to an SMTP server.
+pub(crate) struct SmtpConfig {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) tls: bool,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+    pub(crate) from: String,
+}
+
+/// Delivers price alerts as emails over SMTP.
+pub(crate) struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpNotifier {
+    /// Creates a new `SmtpNotifier`.
+    ///
+    /// Without TLS the connection is made in plain text, which is meant for local mock SMTP
+    /// servers only.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The SMTP settings.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, NotifierError>` - The notifier or an error if the settings are invalid.
+    pub(crate) fn new(config: SmtpConfig) -> Result<Self, NotifierError> {
+        let from = config
+            .from
+            .parse::<Mailbox>()
+            .map_err(|e| NotifierError(format!("Invalid sender address: {e}")))?;
+
+        let mut builder = if config.tls {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .map_err(|e| NotifierError(format!("Invalid SMTP relay: {e}")))?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+        }
+        .port(config.port);
+
+        if let (Some(username), Some(password)) = (config.username, config.password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    fn channel(&self) -> &'static str {
+        "email"
+    }
+
+    async fn notify(&self, notification: &AlertNotification) -> Result<(), NotifierError> {
+        let to = notification
+            .recipient
+            .parse::<Mailbox>()
+            .map_err(|e| NotifierError(format!("Invalid recipient address: {e}")))?;
+        let price = notification.price.round_dp(2).to_string().replace('.', ",");
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(format!(
+                "Prisalarm: {} koster nu {} kr./l hos {}",
+                notification.product_name, price, notification.provider_name
+            ))
+            .body(format!(
+                "{} koster nu {} kr. pr. liter inkl. moms hos {}.\n\n\
+                 Du kan afmelde denne alarm med koden {}.\n",
+                notification.product_name,
+                price,
+                notification.provider_name,
+                notification.unsubscribe_token
+            ))
+            .map_err(|e| NotifierError(format!("Error while building email: {e}")))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| NotifierError(format!("Error while sending email: {e}")))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SmtpConfig, SmtpNotifier};
+    use crate::notifications::notifier::{AlertNotification, Notifier};
+    use rust_decimal::Decimal;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// Starts a mock SMTP server accepting a single connection, which sends every message it
+    /// receives over the returned channel.
+    fn mock_server() -> (u16, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            writer.write_all(b"220 localhost ESMTP\r\n").unwrap();
+
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                let command = line.trim_end().to_ascii_uppercase();
+                let reply: &[u8] = if command.starts_with("DATA") {
+                    writer.write_all(b"354 end data with .\r\n").unwrap();
+                    let mut message = String::new();
+                    loop {
+                        let mut data = String::new();
+                        reader.read_line(&mut data).unwrap();
+                        if data == ".\r\n" {
+                            break;
+                        }
+                        message.push_str(&data);
+                    }
+                    sender.send(message).unwrap();
+                    b"250 queued\r\n"
+                } else if command.starts_with("QUIT") {
+                    writer.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).unwrap();
+                line.clear();
+            }
+        });
+
+        (port, receiver)
+    }
+
+    #[tokio::test]
+    async fn sends_the_alert_as_an_email() {
+        let (port, messages) = mock_server();
+        let notifier = SmtpNotifier::new(SmtpConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: false,
+            username: None,
+            password: None,
+            from: "alerts@example.com".to_string(),
+        })
+        .unwrap();
+
+        notifier
+            .notify(&AlertNotification {
+                recipient: "subscriber@example.com".to_string(),
+                provider_name: "Oliefirma".to_string(),
+                product_name: "Fyringsolie".to_string(),
+                price: Decimal::new(1250, 2),
+                unsubscribe_token: "token".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let message = messages.recv().unwrap();
+        assert!(message.contains("To: subscriber@example.com"));
+        assert!(message.contains("From: alerts@example.com"));
+        assert!(
+            message.contains("Subject: Prisalarm: Fyringsolie koster nu 12,50 kr./l hos Oliefirma")
+        );
+        assert!(message.contains("Du kan afmelde denne alarm med koden token."));
+    }
+}
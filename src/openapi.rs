@@ -0,0 +1,304 @@
+use crate::auth::jwt::{AuthBody, AuthPayload};
+use crate::errors::{ErrorBody, MessageBody};
+use crate::export::ExportFormat;
+use crate::models::charts::ChartTheme;
+use crate::models::delivery_zones::DeliveryZoneSort;
+use crate::models::prices::{BulkMode, PriceSort, SeriesInterval, VatBasis};
+use crate::models::providers::ProviderSort;
+use crate::models::quarantine::{QuarantineSort, QuarantineStatus};
+use crate::models::webhooks::WebhookDeliverySort;
+use crate::pagination::SortOrder;
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+/// The name of the security scheme of endpoints taking the `Claims` of a token from `/auth/login`.
+const BEARER_AUTH: &str = "bearer";
+
+/// The heading handler doc comments document their Rust arguments under.
+const ARGUMENTS_HEADING: &str = "# Arguments";
+
+/// The OpenAPI document of the API, generated from the annotated handlers and their models.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Oliepriser API",
+        description = "Heating oil prices of Danish providers, their delivery zones and the scraping runs that collect them."
+    ),
+    paths(
+        crate::routes::hello_world,
+        crate::auth::routes::authorize,
+        crate::auth::routes::create_user,
+        crate::crud::providers::fetch_providers_with_zones,
+        crate::crud::providers::create_provider,
+        crate::crud::providers::fetch_provider,
+        crate::crud::providers::update_provider,
+        crate::crud::providers::delete_provider,
+        crate::crud::providers::update_last_accessed,
+        crate::crud::providers::add_delivery_zones_to_provider,
+        crate::crud::prices::fetch_prices_by_provider,
+        crate::crud::prices::create_price_for_provider,
+        crate::crud::prices::create_prices_bulk,
+        crate::crud::prices::fetch_prices,
+        crate::crud::prices::correct_price,
+        crate::crud::prices::fetch_price_revisions,
+        crate::crud::prices::delete_price,
+        crate::crud::prices::restore_price,
+        crate::crud::prices::fetch_price_changes,
+        crate::crud::prices::fetch_price_series,
+        crate::crud::prices::compare_prices,
+        crate::crud::prices::fetch_price_tiers,
+        crate::crud::prices::stream_prices,
+        crate::crud::delivery_zones::fetch_delivery_zones,
+        crate::crud::delivery_zones::create_delivery_zone,
+        crate::crud::delivery_zones::delete_delivery_zone,
+        crate::crud::delivery_zones::fetch_zone_postcodes,
+        crate::crud::delivery_zones::update_zone_postcodes,
+        crate::crud::scraping_runs::get_last_scraping_run_by_time,
+        crate::crud::scraping_runs::create_scraping_run,
+        crate::crud::providers::fetch_providers_ids,
+        crate::crud::products::fetch_products,
+        crate::crud::products::create_product,
+        crate::crud::products::delete_product,
+        crate::crud::products::fetch_provider_products,
+        crate::crud::products::upsert_provider_product,
+        crate::crud::products::delete_provider_product,
+        crate::crud::quarantine::fetch_plausibility_rules,
+        crate::crud::quarantine::update_plausibility_rules,
+        crate::crud::quarantine::fetch_quarantined_prices,
+        crate::crud::quarantine::approve_quarantined_price,
+        crate::crud::quarantine::reject_quarantined_price,
+        crate::crud::fees::fetch_provider_fees,
+        crate::crud::fees::update_provider_fees,
+        crate::crud::vat_rates::fetch_vat_rates,
+        crate::crud::vat_rates::create_vat_rate,
+        crate::crud::vat_rates::delete_vat_rate,
+        crate::crud::vat_rates::fetch_duty_rates,
+        crate::crud::vat_rates::create_duty_rate,
+        crate::crud::vat_rates::delete_duty_rate,
+        crate::crud::references::fetch_reference_series,
+        crate::crud::references::create_reference_series,
+        crate::crud::references::delete_reference_series,
+        crate::crud::references::fetch_reference_observations,
+        crate::crud::references::upload_reference_observations,
+        crate::crud::references::fetch_fx_rates,
+        crate::crud::references::upload_fx_rates,
+        crate::crud::references::fetch_provider_margins,
+        crate::crud::price_alerts::fetch_price_alerts,
+        crate::crud::price_alerts::create_price_alert,
+        crate::crud::price_alerts::delete_price_alert,
+        crate::crud::webhooks::fetch_webhooks,
+        crate::crud::webhooks::create_webhook,
+        crate::crud::webhooks::delete_webhook,
+        crate::crud::webhooks::fetch_webhook_deliveries,
+        crate::crud::widget::fetch_widget,
+        crate::crud::widget::widget_preflight,
+        crate::crud::widget::fetch_widget_api_keys,
+        crate::crud::widget::create_widget_api_key,
+        crate::crud::widget::revoke_widget_api_key,
+        crate::crud::widget::fetch_widget_usage,
+        crate::crud::feeds::fetch_price_feed,
+        crate::crud::feeds::fetch_provider_price_feed,
+        crate::crud::feeds::fetch_zone_price_feed,
+        crate::crud::exports::export_prices,
+        crate::crud::imports::import_prices,
+        crate::crud::market_index::fetch_market_index,
+        crate::crud::forecast::fetch_provider_forecast,
+        crate::crud::forecast::fetch_market_forecast,
+        crate::crud::charts::provider_chart_svg,
+        crate::crud::charts::provider_chart_png,
+        crate::crud::charts::market_index_chart_svg,
+        crate::crud::charts::market_index_chart_png,
+        crate::crud::quotes::create_quote,
+        crate::crud::live_updates::live_updates,
+    ),
+    components(schemas(
+        AuthPayload,
+        AuthBody,
+        ErrorBody,
+        MessageBody,
+        SortOrder,
+        ProviderSort,
+        PriceSort,
+        DeliveryZoneSort,
+        VatBasis,
+        BulkMode,
+        SeriesInterval,
+        QuarantineStatus,
+        QuarantineSort,
+        WebhookDeliverySort,
+        ExportFormat,
+        ChartTheme,
+    )),
+    modifiers(&BearerAuth, &HandlerDocs),
+    tags(
+        (name = "auth", description = "Users and the tokens authenticating them"),
+        (name = "providers", description = "Heating oil providers"),
+        (name = "prices", description = "Prices recorded for providers"),
+        (name = "zones", description = "The delivery zones providers deliver to"),
+        (name = "scraping runs", description = "Runs of the scraper collecting prices"),
+        (name = "products", description = "The products providers sell and their product codes"),
+        (name = "quarantine", description = "Prices held back as implausible and the rules flagging them"),
+        (name = "fees", description = "Delivery fees of providers per zone"),
+        (name = "vat rates", description = "VAT rates applied to prices over time"),
+        (name = "duty rates", description = "Energy duty rates per product over time"),
+        (name = "references", description = "Reference series, such as gasoil futures, and their observations"),
+        (name = "fx rates", description = "Exchange rates converting reference observations to DKK"),
+        (name = "alerts", description = "Alerts notifying users of prices below a threshold"),
+        (name = "webhooks", description = "Webhooks receiving events and their deliveries"),
+        (name = "widget", description = "The embeddable price widget and its API keys"),
+        (name = "feeds", description = "Atom feeds of price changes"),
+        (name = "exports", description = "Downloads of the price history"),
+        (name = "imports", description = "Uploads of prices in bulk"),
+        (name = "market index", description = "The average price across providers"),
+        (name = "forecasts", description = "Forecasts of prices"),
+        (name = "charts", description = "Rendered charts of prices"),
+        (name = "quotes", description = "Quotes for a delivery of an amount of a product"),
+        (name = "live updates", description = "Prices and scraping runs pushed as they happen"),
+        (name = "status", description = "Whether the API is up"),
+    )
+)]
+struct ApiDoc;
+
+/// Builds the OpenAPI document served at `/openapi.json`.
+///
+/// The crate declares no license, so the empty one derived from the manifest is left out.
+///
+/// # Returns
+///
+/// * `utoipa::openapi::OpenApi` - The OpenAPI document.
+pub(crate) fn api_doc() -> utoipa::openapi::OpenApi {
+    let mut openapi = ApiDoc::openapi();
+    openapi
+        .info
+        .license
+        .take_if(|license| license.name.is_empty());
+    openapi
+}
+
+/// Adds the security scheme of endpoints taking `Claims`: a bearer token from `/auth/login`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        let mut scheme = Http::new(HttpAuthScheme::Bearer);
+        scheme.bearer_format = Some("JWT".to_string());
+        components.add_security_scheme(BEARER_AUTH, SecurityScheme::Http(scheme));
+    }
+}
+
+/// Drops the `# Arguments` and `# Returns` sections of handler doc comments from the operation
+/// descriptions, as they describe the Rust function rather than the endpoint.
+struct HandlerDocs;
+
+impl Modify for HandlerDocs {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.patch,
+                &mut item.options,
+            ];
+            for operation in operations.into_iter().flatten() {
+                operation.description = operation.description.take().and_then(|description| {
+                    let description = description
+                        .split(ARGUMENTS_HEADING)
+                        .next()
+                        .unwrap_or_default()
+                        .trim();
+                    (!description.is_empty()).then(|| description.to_string())
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::api_doc;
+    use crate::app_state::AppState;
+    use crate::charts::cache::ChartCache;
+    use crate::routes::router;
+    use crate::streams::events::EventBus;
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Arc;
+
+    /// Paths of the router serving the document itself, and axum's fallback.
+    const UNDOCUMENTED_PATHS: [&str; 5] = [
+        "/docs",
+        "/docs/",
+        "/docs/*rest",
+        "/openapi.json",
+        "/*__private__axum_fallback",
+    ];
+
+    /// Routes of nested and top level routers that must be found, so a change to the debug output
+    /// the routes are read from fails the test rather than letting it pass on no routes.
+    const KNOWN_PATHS: [&str; 4] = [
+        "/",
+        "/providers/{id}/prices",
+        "/references",
+        "/widget/keys/{id}/usage",
+    ];
+
+    /// Lists the paths routed by the router, in OpenAPI syntax, taken from its debug output as
+    /// axum exposes no other way to enumerate them.
+    fn routed_paths() -> Vec<String> {
+        let db = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/oliepriser")
+            .unwrap();
+        let state = AppState {
+            db,
+            notifiers: Arc::new(Vec::new()),
+            events: Arc::new(EventBus::new()),
+            charts: Arc::new(ChartCache::new()),
+        };
+        let debug = format!("{:?}", router(state));
+        let mut paths: Vec<String> = debug
+            .split('"')
+            .filter(|part| part.starts_with('/') && !UNDOCUMENTED_PATHS.contains(part))
+            .map(|path| {
+                path.split('/')
+                    .map(|segment| match segment.strip_prefix(':') {
+                        Some(name) => format!("{{{name}}}"),
+                        None => segment.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    #[tokio::test]
+    async fn documents_exactly_the_routed_paths() {
+        let documented = api_doc().paths.paths;
+        let paths = routed_paths();
+        for known in KNOWN_PATHS {
+            assert!(
+                paths.iter().any(|path| path == known),
+                "{known} not found among the routed paths: {paths:?}"
+            );
+        }
+
+        let undocumented: Vec<&String> = paths
+            .iter()
+            .filter(|path| !documented.contains_key(*path))
+            .collect();
+        assert!(
+            undocumented.is_empty(),
+            "undocumented paths: {undocumented:?}"
+        );
+
+        let unrouted: Vec<&String> = documented
+            .keys()
+            .filter(|path| !paths.contains(path))
+            .collect();
+        assert!(unrouted.is_empty(), "unrouted paths: {unrouted:?}");
+    }
+}
@@ -0,0 +1,264 @@
+use axum::http::header::LINK;
+use axum::http::{HeaderValue, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+use utoipa::{IntoParams, ToSchema};
+
+/// The number of items returned when no limit is given.
+pub(crate) const DEFAULT_LIMIT: i64 = 100;
+
+/// The largest number of items a single page may hold.
+pub(crate) const MAX_LIMIT: i64 = 1000;
+
+/// The header carrying the cursor of the next page.
+const NEXT_CURSOR: &str = "x-next-cursor";
+
+/// The direction items are sorted in.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// A column a list endpoint can be sorted by.
+pub(crate) trait SortKey: Copy {
+    /// The column of the paginated derived table to sort by.
+    fn column(self) -> &'static str;
+
+    /// The SQL type of the column, used to cast cursor values.
+    fn sql_type(self) -> &'static str;
+}
+
+/// A row that can be resumed from with a cursor.
+pub(crate) trait Keyset<S> {
+    /// Returns the cursor pointing just past this row when sorting by `sort`.
+    fn cursor(&self, sort: S) -> Cursor;
+}
+
+/// A position in a sorted list: the sort column value of the last row seen and its ID.
+///
+/// Cursors are opaque to clients, who only pass them back as the `cursor` parameter.
+pub(crate) struct Cursor {
+    pub(crate) value: String,
+    pub(crate) id: i64,
+}
+
+impl Cursor {
+    /// Encodes the cursor for use in a URL.
+    pub(crate) fn encode(&self) -> String {
+        hex::encode(format!("{}|{}", self.id, self.value))
+    }
+
+    /// Decodes a cursor produced by `encode`.
+    pub(crate) fn decode(cursor: &str) -> Option<Self> {
+        let decoded = String::from_utf8(hex::decode(cursor).ok()?).ok()?;
+        let (id, value) = decoded.split_once('|')?;
+        Some(Self {
+            value: value.to_string(),
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// The pagination and sorting parameters shared by list endpoints.
+#[derive(Deserialize, IntoParams)]
+#[serde(bound(deserialize = "S: Deserialize<'de> + Default"))]
+#[into_params(parameter_in = Query)]
+pub(crate) struct PageParams<S> {
+    /// The number of items per page, at most 1000.
+    pub(crate) limit: Option<i64>,
+    /// The cursor of the page to fetch, as returned with the previous page.
+    pub(crate) cursor: Option<String>,
+    /// The column to sort by. As the columns differ between lists, each list endpoint documents
+    /// the parameter itself.
+    #[serde(default)]
+    #[param(ignore)]
+    pub(crate) sort: S,
+    #[serde(default)]
+    pub(crate) order: SortOrder,
+}
+
+impl<S: SortKey> PageParams<S> {
+    /// Returns the requested page size, capped to `MAX_LIMIT`.
+    pub(crate) fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /// Appends the keyset condition, ordering and limit to a query.
+    ///
+    /// The query must select from a derived table aliased `page` with an `id` column and end in
+    /// a `WHERE` clause. One row more than the page size is fetched to tell whether another page
+    /// follows.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query to append to.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), &'static str>` - `Ok(())`, or an error if the cursor is malformed.
+    pub(crate) fn push_page(
+        &self,
+        query: &mut QueryBuilder<'_, Postgres>,
+    ) -> Result<(), &'static str> {
+        let column = self.sort.column();
+
+        if let Some(cursor) = &self.cursor {
+            let cursor = Cursor::decode(cursor).ok_or("invalid cursor")?;
+            query
+                .push(format_args!(" AND (page.{}, page.id) ", column))
+                .push(if self.order == SortOrder::Asc {
+                    ">"
+                } else {
+                    "<"
+                })
+                .push(" (CAST(")
+                .push_bind(cursor.value)
+                .push(format_args!(" AS {}), ", self.sort.sql_type()))
+                .push_bind(cursor.id)
+                .push(")");
+        }
+
+        self.push_order(query);
+        query.push(" LIMIT ").push_bind(self.limit() + 1);
+
+        Ok(())
+    }
+
+    /// Whether a page was asked for, by a limit or a cursor.
+    pub(crate) fn is_requested(&self) -> bool {
+        self.limit.is_some() || self.cursor.is_some()
+    }
+
+    /// Appends the ordering to a query, without a limit. Used by lists that return every row
+    /// unless a page is asked for.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query to append to.
+    pub(crate) fn push_order(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        let column = self.sort.column();
+        let order = self.order.as_sql();
+        query.push(format_args!(
+            " ORDER BY page.{} {}, page.id {}",
+            column, order, order
+        ));
+    }
+
+    /// Turns the rows fetched by a query built with `push_page` into a page.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The fetched rows.
+    /// * `uri` - The URI of the request, used to link to the next page.
+    ///
+    /// # Returns
+    ///
+    /// * `Paginated<T>` - The page of rows.
+    pub(crate) fn paginate<T: Keyset<S>>(&self, mut rows: Vec<T>, uri: Uri) -> Paginated<T> {
+        let limit = self.limit() as usize;
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last().map(|row| row.cursor(self.sort).encode())
+        } else {
+            None
+        };
+
+        Paginated {
+            items: rows,
+            next_cursor,
+            uri,
+        }
+    }
+}
+
+/// A page of a list, sent as a JSON array with the next page linked in the `Link` and
+/// `X-Next-Cursor` headers.
+pub(crate) struct Paginated<T> {
+    pub(crate) items: Vec<T>,
+    pub(crate) next_cursor: Option<String>,
+    pub(crate) uri: Uri,
+}
+
+impl<T> Paginated<T> {
+    /// Returns the path and query of the next page, if there is one.
+    pub(crate) fn next_link(&self) -> Option<String> {
+        let cursor = self.next_cursor.as_ref()?;
+        let mut query: Vec<&str> = self
+            .uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty() && !pair.starts_with("cursor="))
+            .collect();
+        let cursor = format!("cursor={}", cursor);
+        query.push(&cursor);
+
+        Some(format!("{}?{}", self.uri.path(), query.join("&")))
+    }
+
+    /// Wraps the page in a JSON envelope.
+    pub(crate) fn enveloped(self) -> Enveloped<T> {
+        Enveloped(self)
+    }
+}
+
+/// Adds the `Link` and `X-Next-Cursor` headers pointing to the next page to a response.
+pub(crate) fn link_next(response: &mut Response, link: Option<String>, cursor: Option<&String>) {
+    if let (Some(link), Some(cursor)) = (link, cursor) {
+        let headers = response.headers_mut();
+        if let Ok(value) = HeaderValue::from_str(&format!("<{}>; rel=\"next\"", link)) {
+            headers.insert(LINK, value);
+        }
+        if let Ok(value) = HeaderValue::from_str(cursor) {
+            headers.insert(NEXT_CURSOR, value);
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Paginated<T> {
+    fn into_response(self) -> Response {
+        let link = self.next_link();
+        let mut response = Json(self.items).into_response();
+        link_next(&mut response, link, self.next_cursor.as_ref());
+        response
+    }
+}
+
+/// A page of a list, sent as a JSON object holding the items and the cursor and link of the
+/// next page. The next page is linked in the headers as well.
+pub(crate) struct Enveloped<T>(Paginated<T>);
+
+#[derive(Serialize, ToSchema)]
+pub(crate) struct Envelope<T> {
+    data: Vec<T>,
+    next_cursor: Option<String>,
+    next: Option<String>,
+}
+
+impl<T: Serialize> IntoResponse for Enveloped<T> {
+    fn into_response(self) -> Response {
+        let link = self.0.next_link();
+        let mut response = Json(Envelope {
+            data: self.0.items,
+            next_cursor: self.0.next_cursor.clone(),
+            next: link.clone(),
+        })
+        .into_response();
+        link_next(&mut response, link, self.0.next_cursor.as_ref());
+        response
+    }
+}
@@ -1,21 +1,78 @@
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{delete, post, put};
 use axum::{routing::get, Router};
+use utoipa_swagger_ui::SwaggerUi;
 
 use crate::app_state::AppState;
 use crate::auth::routes::{authorize, create_user};
+use crate::crud::charts::{
+    market_index_chart_png, market_index_chart_svg, provider_chart_png, provider_chart_svg,
+};
 use crate::crud::delivery_zones::{
-    create_delivery_zone, delete_delivery_zone, fetch_delivery_zones,
+    create_delivery_zone, delete_delivery_zone, fetch_delivery_zones, fetch_zone_postcodes,
+    update_zone_postcodes,
 };
+use crate::crud::exports::export_prices;
+use crate::crud::feeds::{fetch_price_feed, fetch_provider_price_feed, fetch_zone_price_feed};
+use crate::crud::fees::{fetch_provider_fees, update_provider_fees};
+use crate::crud::forecast::{fetch_market_forecast, fetch_provider_forecast};
+use crate::crud::imports::import_prices;
+use crate::crud::live_updates::live_updates;
+use crate::crud::market_index::fetch_market_index;
+use crate::crud::price_alerts::{create_price_alert, delete_price_alert, fetch_price_alerts};
 use crate::crud::prices::{
-    create_price_for_provider, delete_price, fetch_prices, fetch_prices_by_provider,
+    compare_prices, correct_price, create_price_for_provider, create_prices_bulk, delete_price,
+    fetch_price_changes, fetch_price_revisions, fetch_price_series, fetch_price_tiers,
+    fetch_prices, fetch_prices_by_provider, restore_price, stream_prices,
+};
+use crate::crud::products::{
+    create_product, delete_product, delete_provider_product, fetch_products,
+    fetch_provider_products, upsert_provider_product,
 };
 use crate::crud::providers::{
     add_delivery_zones_to_provider, create_provider, delete_provider, fetch_provider,
     fetch_providers_ids, fetch_providers_with_zones, update_last_accessed, update_provider,
 };
+use crate::crud::quarantine::{
+    approve_quarantined_price, fetch_plausibility_rules, fetch_quarantined_prices,
+    reject_quarantined_price, update_plausibility_rules,
+};
+use crate::crud::quotes::create_quote;
+use crate::crud::references::{
+    create_reference_series, delete_reference_series, fetch_fx_rates, fetch_provider_margins,
+    fetch_reference_observations, fetch_reference_series, upload_fx_rates,
+    upload_reference_observations,
+};
 use crate::crud::scraping_runs::{create_scraping_run, get_last_scraping_run_by_time};
+use crate::crud::vat_rates::{
+    create_duty_rate, create_vat_rate, delete_duty_rate, delete_vat_rate, fetch_duty_rates,
+    fetch_vat_rates,
+};
+use crate::crud::webhooks::{
+    create_webhook, delete_webhook, fetch_webhook_deliveries, fetch_webhooks,
+};
+use crate::crud::widget::{
+    create_widget_api_key, fetch_widget, fetch_widget_api_keys, fetch_widget_usage,
+    revoke_widget_api_key, widget_preflight,
+};
+use crate::models::imports::MAX_IMPORT_BYTES;
+use crate::models::references::MAX_UPLOAD_BYTES;
+use crate::openapi::api_doc;
 
-async fn hello_world() -> &'static str {
+/// Greets the caller, showing that the API is up.
+///
+/// # Returns
+///
+/// * `&'static str` - A greeting.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "status",
+    responses(
+        (status = 200, description = "The API is up", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn hello_world() -> &'static str {
     "Hello, world!"
 }
 
@@ -38,18 +95,123 @@ pub(crate) fn router(state: AppState) -> Router {
             "/:id/prices",
             get(fetch_prices_by_provider).post(create_price_for_provider),
         )
+        .route("/:id/prices/chart.svg", get(provider_chart_svg))
+        .route("/:id/prices/chart.png", get(provider_chart_png))
+        .route("/:id/prices/changes", get(fetch_price_changes))
+        .route("/:id/prices/series", get(fetch_price_series))
         .route("/:id/zones", post(add_delivery_zones_to_provider))
+        .route("/:id/products", get(fetch_provider_products))
+        .route("/:id/margins", get(fetch_provider_margins))
+        .route("/:id/forecast", get(fetch_provider_forecast))
+        .route(
+            "/:id/fees",
+            get(fetch_provider_fees).put(update_provider_fees),
+        )
+        .route(
+            "/:id/products/:product_id",
+            put(upsert_provider_product).delete(delete_provider_product),
+        )
         .route("/:id/last_access", put(update_last_accessed));
 
     // Price routes
     let price_routes = Router::new()
         .route("/", get(fetch_prices))
-        .route("/:id", delete(delete_price));
+        .route("/bulk", post(create_prices_bulk))
+        .route("/compare", get(compare_prices))
+        .route("/stream", get(stream_prices))
+        .route("/quarantine", get(fetch_quarantined_prices))
+        .route("/quarantine/:id/approve", post(approve_quarantined_price))
+        .route("/quarantine/:id/reject", post(reject_quarantined_price))
+        .route("/:id", delete(delete_price).patch(correct_price))
+        .route("/:id/restore", post(restore_price))
+        .route("/:id/revisions", get(fetch_price_revisions))
+        .route("/:id/tiers", get(fetch_price_tiers));
 
     // Zone routes
     let zone_routes = Router::new()
         .route("/", get(fetch_delivery_zones).post(create_delivery_zone))
-        .route("/:id", delete(delete_delivery_zone));
+        .route("/:id", delete(delete_delivery_zone))
+        .route(
+            "/:id/postcodes",
+            get(fetch_zone_postcodes).put(update_zone_postcodes),
+        );
+
+    // Product routes
+    let product_routes = Router::new()
+        .route("/", get(fetch_products).post(create_product))
+        .route("/:id", delete(delete_product))
+        .route(
+            "/:id/plausibility",
+            get(fetch_plausibility_rules).put(update_plausibility_rules),
+        );
+
+    // VAT rate routes
+    let vat_rate_routes = Router::new()
+        .route("/", get(fetch_vat_rates).post(create_vat_rate))
+        .route("/:id", delete(delete_vat_rate));
+
+    // Duty rate routes
+    let duty_rate_routes = Router::new()
+        .route("/", get(fetch_duty_rates).post(create_duty_rate))
+        .route("/:id", delete(delete_duty_rate));
+
+    // Reference series routes
+    let reference_routes = Router::new()
+        .route(
+            "/",
+            get(fetch_reference_series).post(create_reference_series),
+        )
+        .route("/:id", delete(delete_reference_series))
+        .route(
+            "/:id/observations",
+            get(fetch_reference_observations)
+                .post(upload_reference_observations)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        );
+
+    // FX rate routes
+    let fx_rate_routes = Router::new().route(
+        "/",
+        get(fetch_fx_rates)
+            .post(upload_fx_rates)
+            .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+    );
+
+    // Price alert routes
+    let price_alert_routes = Router::new()
+        .route("/", get(fetch_price_alerts).post(create_price_alert))
+        .route("/:token", delete(delete_price_alert));
+
+    // Webhook routes
+    let webhook_routes = Router::new()
+        .route("/", get(fetch_webhooks).post(create_webhook))
+        .route("/:id", delete(delete_webhook))
+        .route("/:id/deliveries", get(fetch_webhook_deliveries));
+
+    // Widget routes
+    let widget_routes = Router::new()
+        .route("/", get(fetch_widget).options(widget_preflight))
+        .route(
+            "/keys",
+            get(fetch_widget_api_keys).post(create_widget_api_key),
+        )
+        .route("/keys/:id", delete(revoke_widget_api_key))
+        .route("/keys/:id/usage", get(fetch_widget_usage));
+
+    // Feed routes
+    let feed_routes = Router::new()
+        .route("/prices.atom", get(fetch_price_feed))
+        .route("/providers/:id/prices.atom", get(fetch_provider_price_feed))
+        .route("/zones/:id/prices.atom", get(fetch_zone_price_feed));
+
+    // Export routes
+    let export_routes = Router::new().route("/prices", get(export_prices));
+
+    // Import routes
+    let import_routes = Router::new().route(
+        "/prices",
+        post(import_prices).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+    );
 
     // Scraper routes
     let scrape_run_routes = Router::new()
@@ -66,6 +228,24 @@ pub(crate) fn router(state: AppState) -> Router {
         .nest("/providers", provider_routes)
         .nest("/prices", price_routes)
         .nest("/zones", zone_routes)
+        .nest("/products", product_routes)
+        .nest("/vat_rates", vat_rate_routes)
+        .nest("/duty_rates", duty_rate_routes)
+        .nest("/references", reference_routes)
+        .nest("/fx_rates", fx_rate_routes)
+        .nest("/alerts", price_alert_routes)
+        .nest("/webhooks", webhook_routes)
+        .nest("/widget", widget_routes)
+        .nest("/feeds", feed_routes)
+        .nest("/exports", export_routes)
+        .nest("/imports", import_routes)
         .nest("/scraping_runs", scrape_run_routes)
+        .route("/quote", post(create_quote))
+        .route("/index", get(fetch_market_index))
+        .route("/index/forecast", get(fetch_market_forecast))
+        .route("/index/chart.svg", get(market_index_chart_svg))
+        .route("/index/chart.png", get(market_index_chart_png))
+        .route("/ws", get(live_updates))
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", api_doc()))
         .with_state(state)
 }
@@ -0,0 +1,2 @@
+pub(crate) mod events;
+pub(crate) mod provider_health;
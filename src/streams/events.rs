@@ -0,0 +1,199 @@
+use crate::models::live_events::LiveEvent;
+use crate::models::prices::PriceEvent;
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// The Postgres channel live events are published on.
+const CHANNEL: &str = "live_events";
+
+/// How many events a slow subscriber may fall behind before it starts missing events.
+const CAPACITY: usize = 256;
+
+/// The delay before retrying to connect the listener, doubled after every failed attempt.
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The longest delay between two attempts to connect the listener.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// A live event as sent over Postgres NOTIFY, tagged with the instance that published it.
+#[derive(Deserialize, Serialize)]
+struct Notification {
+    instance_id: String,
+    event: LiveEvent,
+}
+
+/// Fans live events such as newly inserted prices out to subscribers.
+///
+/// Events published on this instance are broadcast directly and also sent over Postgres
+/// NOTIFY, so subscribers connected to other instances receive them through their listener.
+pub(crate) struct EventBus {
+    sender: broadcast::Sender<LiveEvent>,
+    instance_id: String,
+}
+
+impl EventBus {
+    /// Creates a new `EventBus` with a random instance ID.
+    pub(crate) fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self {
+            sender,
+            instance_id: Alphanumeric.sample_string(&mut rand::thread_rng(), 16),
+        }
+    }
+
+    /// Subscribes to all events published from now on.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publishes an event to subscribers on this instance only.
+    ///
+    /// Meant for events every instance derives by itself, which would otherwise arrive twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to publish.
+    pub(crate) fn publish_local(&self, event: LiveEvent) {
+        // Sending only fails when nobody is subscribed on this instance
+        let _ = self.sender.send(event);
+    }
+
+    /// Publishes an event to subscribers on every instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database connection pool.
+    /// * `event` - The event to publish.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), sqlx::Error>` - `Ok(())` if the event was published, or a database error.
+    pub(crate) async fn publish(&self, db: &PgPool, event: LiveEvent) -> Result<(), sqlx::Error> {
+        let payload = serde_json::to_string(&Notification {
+            instance_id: self.instance_id.clone(),
+            event: event.clone(),
+        })
+        .unwrap_or_default();
+
+        self.publish_local(event);
+
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(CHANNEL)
+            .bind(payload)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Loads a stored price and publishes it to subscribers on every instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database connection pool.
+    /// * `price_id` - The ID of the inserted price.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), sqlx::Error>` - `Ok(())` if the event was published, or a database error.
+    pub(crate) async fn publish_price(
+        &self,
+        db: &PgPool,
+        price_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let Some(event) = sqlx::query_as::<_, PriceEvent>(
+            r#"
+            SELECT
+                op.id,
+                op.provider_id,
+                op.product_id,
+                pr.slug AS product,
+                ARRAY(
+                    SELECT pz.zone_id
+                    FROM provider_delivery_zones pz
+                    WHERE pz.provider_id = op.provider_id
+                    ORDER BY pz.zone_id
+                ) AS zone_ids,
+                ROUND(op.price * (1 + vat_rate_at(op.created_at)), 4) AS price,
+                op.price AS price_excl_vat,
+                op.currency,
+                op.unit,
+                op.created_at
+            FROM
+                oil_prices op
+            JOIN
+                products pr ON op.product_id = pr.id
+            WHERE
+                op.id = $1
+            "#,
+        )
+        .bind(price_id)
+        .fetch_optional(db)
+        .await?
+        else {
+            return Ok(());
+        };
+
+        self.publish(db, LiveEvent::Price(event)).await
+    }
+
+    /// Forwards events published by other instances to local subscribers.
+    ///
+    /// Runs until the application shuts down. Connecting the listener is retried with backoff
+    /// until it succeeds, after which it reconnects by itself when the connection is lost.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database connection pool.
+    pub(crate) async fn listen(&self, db: PgPool) {
+        let mut listener = connect_listener(&db).await;
+
+        loop {
+            match listener.recv().await {
+                Ok(notification) => {
+                    match serde_json::from_str::<Notification>(notification.payload()) {
+                        Ok(notification) if notification.instance_id != self.instance_id => {
+                            self.publish_local(notification.event);
+                        }
+                        Ok(_) => {}
+                        Err(e) => tracing::warn!("Ignoring malformed live event: {}", e),
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Error while receiving live events: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+}
+
+/// Connects a listener to the live events channel, retrying with exponential backoff until it
+/// succeeds.
+///
+/// # Arguments
+///
+/// * `db` - The database connection pool.
+///
+/// # Returns
+///
+/// * `PgListener` - The listener, listening on the live events channel.
+async fn connect_listener(db: &PgPool) -> PgListener {
+    let mut delay = BASE_RETRY_DELAY;
+    loop {
+        match PgListener::connect_with(db).await {
+            Ok(mut listener) => match listener.listen(CHANNEL).await {
+                Ok(()) => return listener,
+                Err(e) => tracing::error!("Error while listening for live events: {}", e),
+            },
+            Err(e) => tracing::error!("Error while connecting live event listener: {}", e),
+        }
+
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+    }
+}
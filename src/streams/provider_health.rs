@@ -0,0 +1,63 @@
+use crate::models::live_events::{LiveEvent, ProviderHealthEvent};
+use crate::models::providers::{ProviderHealth, ProviderLastPriceRow};
+use crate::streams::events::EventBus;
+use chrono::Utc;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often provider health is re-evaluated.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Watches provider health and publishes an event whenever it changes.
+///
+/// Every instance runs its own monitor and derives the same changes, so events are only
+/// published to local subscribers. Runs until the application shuts down.
+///
+/// # Arguments
+///
+/// * `db` - The database connection pool.
+/// * `events` - The bus to publish health changes on.
+pub(crate) async fn run(db: PgPool, events: Arc<EventBus>) {
+    let mut known: HashMap<i32, ProviderHealth> = HashMap::new();
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let rows = match sqlx::query_as::<_, ProviderLastPriceRow>(
+            r#"
+            SELECT p.id AS provider_id, MAX(op.last_seen_at) AS last_price_at
+            FROM providers p
+            LEFT JOIN oil_prices op ON op.provider_id = p.id AND op.deleted_at IS NULL
+            GROUP BY p.id
+            "#,
+        )
+        .fetch_all(&db)
+        .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::error!("Error while checking provider health: {}", e);
+                continue;
+            }
+        };
+
+        let now = Utc::now().naive_utc();
+        for row in rows {
+            let health = ProviderHealth::from_last_price(row.last_price_at, now);
+            match known.insert(row.provider_id, health) {
+                Some(previous) if previous != health => {
+                    events.publish_local(LiveEvent::ProviderHealth(ProviderHealthEvent {
+                        provider_id: row.provider_id,
+                        health,
+                        previous,
+                        last_price_at: row.last_price_at,
+                    }));
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::models::webhooks::WebhookEvent;
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgExecutor;
+
+/// Queues an event for delivery to every active webhook subscribed to it.
+///
+/// Passing a transaction as the executor makes the event part of the same commit as the change
+/// it describes.
+///
+/// # Arguments
+///
+/// * `executor` - The connection, pool or transaction to queue the deliveries on.
+/// * `event` - The type of event.
+/// * `data` - The event data sent in the payload.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` if the deliveries were queued, or a database error.
+pub(crate) async fn enqueue_event<'e, E>(
+    executor: E,
+    event: WebhookEvent,
+    data: serde_json::Value,
+) -> Result<(), sqlx::Error>
+where
+    E: PgExecutor<'e>,
+{
+    let payload = json!({
+        "event": event.as_str(),
+        "occurred_at": Utc::now(),
+        "data": data,
+    });
+
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event_type, payload)
+        SELECT id, $1, $2
+        FROM webhooks
+        WHERE active AND $1 = ANY(event_types)
+        "#,
+    )
+    .bind(event.as_str())
+    .bind(payload)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
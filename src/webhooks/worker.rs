@@ -0,0 +1,202 @@
+use crate::models::webhooks::PendingDeliveryRow;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// How often the queue is checked for due deliveries.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many deliveries are claimed per poll.
+const BATCH_SIZE: i64 = 20;
+
+/// How long a single delivery attempt may take.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long, in seconds, claimed deliveries are leased for. Deliveries in a batch are attempted
+/// one after another, so the lease outlasts a batch of attempts that all time out, with a margin
+/// for recording their outcomes.
+const LEASE_SECS: i64 = BATCH_SIZE * DELIVERY_TIMEOUT.as_secs() as i64 + 60;
+
+/// Deliveries are marked as failed after this many attempts.
+const MAX_ATTEMPTS: i32 = 8;
+
+/// The delay before the first retry, doubled for every further attempt.
+const BASE_BACKOFF_SECS: i64 = 30;
+
+/// The longest delay between two attempts.
+const MAX_BACKOFF_SECS: i64 = 6 * 60 * 60;
+
+/// Runs the webhook delivery worker until the application shuts down.
+///
+/// # Arguments
+///
+/// * `db` - The database connection pool.
+pub(crate) async fn run(db: PgPool) {
+    let client = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .expect("Failed to build webhook HTTP client");
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = deliver_pending(&db, &client).await {
+            tracing::error!("Error while delivering webhooks: {}", e);
+        }
+    }
+}
+
+/// Signs a payload with a webhook's secret.
+///
+/// The signature is an HMAC-SHA256 over the timestamp and the body joined by a dot, so receivers
+/// can reject replayed requests.
+///
+/// # Arguments
+///
+/// * `secret` - The webhook's secret.
+/// * `timestamp` - The UNIX timestamp sent in the `X-Webhook-Timestamp` header.
+/// * `body` - The serialized payload.
+///
+/// # Returns
+///
+/// * `String` - The hex encoded signature.
+fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Calculates the delay before the next attempt using exponential backoff.
+///
+/// # Arguments
+///
+/// * `attempts` - The number of attempts made so far.
+///
+/// # Returns
+///
+/// * `i64` - The delay in seconds.
+fn backoff_secs(attempts: i32) -> i64 {
+    let exponent = u32::try_from(attempts.saturating_sub(1))
+        .unwrap_or(0)
+        .min(20);
+    (BASE_BACKOFF_SECS << exponent).min(MAX_BACKOFF_SECS)
+}
+
+/// Claims the deliveries that are due and attempts to deliver them.
+///
+/// Claimed deliveries are leased for longer than the whole batch can take, so other instances
+/// skip them while they are in flight.
+///
+/// # Arguments
+///
+/// * `db` - The database connection pool.
+/// * `client` - The HTTP client used to deliver the payloads.
+///
+/// # Returns
+///
+/// * `Result<(), sqlx::Error>` - `Ok(())` once the batch has been processed, or a database error.
+async fn deliver_pending(db: &PgPool, client: &reqwest::Client) -> Result<(), sqlx::Error> {
+    let deliveries = sqlx::query_as::<_, PendingDeliveryRow>(
+        r#"
+        UPDATE webhook_deliveries d
+        SET next_attempt_at = LOCALTIMESTAMP + make_interval(secs => $2)
+        FROM webhooks w
+        WHERE d.webhook_id = w.id
+          AND d.id IN (
+              SELECT wd.id
+              FROM webhook_deliveries wd
+              JOIN webhooks wh ON wd.webhook_id = wh.id
+              WHERE wd.status = 'pending'
+                AND wd.next_attempt_at <= LOCALTIMESTAMP
+                AND wh.active
+              ORDER BY wd.next_attempt_at
+              LIMIT $1
+              FOR UPDATE OF wd SKIP LOCKED
+          )
+        RETURNING d.id, w.url, w.secret, d.event_type, d.payload, d.attempts
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .bind(LEASE_SECS as f64)
+    .fetch_all(db)
+    .await?;
+
+    for delivery in deliveries {
+        let body = serde_json::to_vec(&delivery.payload).unwrap_or_default();
+        let timestamp = Utc::now().timestamp();
+
+        let result = client
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header("X-Webhook-Event", &delivery.event_type)
+            .header("X-Webhook-Delivery", delivery.id.to_string())
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .header(
+                "X-Webhook-Signature",
+                format!("sha256={}", sign(&delivery.secret, timestamp, &body)),
+            )
+            .body(body)
+            .send()
+            .await;
+
+        let (status_code, error) = match result {
+            Ok(response) if response.status().is_success() => {
+                (Some(i32::from(response.status().as_u16())), None)
+            }
+            Ok(response) => (
+                Some(i32::from(response.status().as_u16())),
+                Some(format!("Unexpected status {}", response.status())),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        let attempts = delivery.attempts + 1;
+        match error {
+            None => {
+                sqlx::query(
+                    r#"
+                    UPDATE webhook_deliveries
+                    SET status = 'delivered', attempts = $2, last_status_code = $3,
+                        last_error = NULL, delivered_at = LOCALTIMESTAMP
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(delivery.id)
+                .bind(attempts)
+                .bind(status_code)
+                .execute(db)
+                .await?;
+            }
+            Some(error) => {
+                let status = if attempts >= MAX_ATTEMPTS {
+                    "failed"
+                } else {
+                    "pending"
+                };
+                sqlx::query(
+                    r#"
+                    UPDATE webhook_deliveries
+                    SET status = $2, attempts = $3, last_status_code = $4, last_error = $5,
+                        next_attempt_at = LOCALTIMESTAMP + make_interval(secs => $6)
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(delivery.id)
+                .bind(status)
+                .bind(attempts)
+                .bind(status_code)
+                .bind(error)
+                .bind(backoff_secs(attempts) as f64)
+                .execute(db)
+                .await?;
+            }
+        }
+    }
+
+    Ok(())
+}